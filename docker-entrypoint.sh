@@ -57,13 +57,9 @@ ln -sf /app/packages/cli/bin/tinyagi.mjs /usr/local/bin/tinyagi
 # Ensure log directory exists
 mkdir -p "$TINYAGI_HOME/logs"
 
-# Run with restart support.
-# Exit code 75 = restart requested; any other code = stop container.
-# The node process writes its own PID file on startup.
-while true; do
-    node /app/packages/main/dist/index.js
-    code=$?
-    [ "$code" -ne 75 ] && exit $code
-    echo "[tinyagi] Restarting..."
-    sleep 1
-done
+# Run in the foreground: logs on stdout, SIGTERM drains in-flight messages,
+# and a restart requested through the API (exit code 75) is handled by the CLI.
+# TINYAGI_CHANNELS, TINYAGI_PROVIDER, and TINYAGI_MODEL update settings.json.
+# The daemon script is exec'd directly (not through bin/tinyagi.mjs) so it is
+# PID 1 and receives docker stop's SIGTERM itself.
+exec node /app/packages/cli/dist/daemon.js start --foreground --no-tty
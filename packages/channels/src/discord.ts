@@ -11,9 +11,10 @@ import fs from 'fs';
 import path from 'path';
 import https from 'https';
 import http from 'http';
-import { ensureSenderPaired, genId } from '@tinyagi/core';
+import { ensureSenderPaired, genId, splitMessage } from '@tinyagi/core';
 import { createSSEClient } from './sse-client';
 import { applyDefaultAgent } from './default-agent';
+import { reportReady, reportFailed } from './startup';
 
 const API_PORT = parseInt(process.env.TINYAGI_API_PORT || '3777', 10);
 const API_BASE = `http://localhost:${API_PORT}`;
@@ -26,6 +27,9 @@ const SETTINGS_FILE = path.join(TINYAGI_HOME, 'settings.json');
 const FILES_DIR = path.join(TINYAGI_HOME, 'files');
 const PAIRING_FILE = path.join(TINYAGI_HOME, 'pairing.json');
 
+// Discord's per-message character limit
+const DISCORD_MAX_MESSAGE_LENGTH = 2000;
+
 // Ensure directories exist
 [path.dirname(LOG_FILE), FILES_DIR].forEach(dir => {
     if (!fs.existsSync(dir)) {
@@ -149,41 +153,6 @@ function getAgentListText(): string {
     }
 }
 
-// Split long messages for Discord's 2000 char limit
-function splitMessage(text: string, maxLength = 2000): string[] {
-    if (text.length <= maxLength) {
-        return [text];
-    }
-
-    const chunks: string[] = [];
-    let remaining = text;
-
-    while (remaining.length > 0) {
-        if (remaining.length <= maxLength) {
-            chunks.push(remaining);
-            break;
-        }
-
-        // Try to split at a newline boundary
-        let splitIndex = remaining.lastIndexOf('\n', maxLength);
-
-        // Fall back to space boundary
-        if (splitIndex <= 0) {
-            splitIndex = remaining.lastIndexOf(' ', maxLength);
-        }
-
-        // Hard-cut if no good boundary found
-        if (splitIndex <= 0) {
-            splitIndex = maxLength;
-        }
-
-        chunks.push(remaining.substring(0, splitIndex));
-        remaining = remaining.substring(splitIndex).replace(/^\n/, '');
-    }
-
-    return chunks;
-}
-
 function pairingMessage(code: string): string {
     return [
         'This sender is not paired yet.',
@@ -210,6 +179,7 @@ const client = new Client({
 client.on(Events.ClientReady, (readyClient) => {
     log('INFO', `Discord bot connected as ${readyClient.user.tag}`);
     log('INFO', 'Listening for DMs...');
+    reportReady();
 });
 
 // Message received - Write to queue
@@ -439,7 +409,7 @@ async function checkOutgoingQueue(): Promise<void> {
 
                     // Split message if needed (Discord 2000 char limit)
                     if (responseText) {
-                        const chunks = splitMessage(responseText);
+                        const chunks = splitMessage(responseText, DISCORD_MAX_MESSAGE_LENGTH);
 
                         if (chunks.length > 0) {
                             if (pending) {
@@ -519,4 +489,8 @@ process.on('SIGTERM', () => {
 
 // Start client
 log('INFO', 'Starting Discord client...');
-client.login(DISCORD_BOT_TOKEN);
+client.login(DISCORD_BOT_TOKEN).catch((error) => {
+    // Rejected token, missing intents, or Discord unreachable
+    log('ERROR', `Discord login failed: ${error.message}`);
+    reportFailed(error);
+});
@@ -0,0 +1,23 @@
+/**
+ * Startup reporting for channel clients.
+ *
+ * The channel manager forks each client with an IPC channel and waits for
+ * one of these messages: `ready` once the bot is logged in, or `failed` when
+ * login is rejected or the platform cannot be reached. Run standalone (no
+ * IPC), only the exit code is left.
+ */
+
+export type StartupMessage = { type: 'ready' } | { type: 'failed'; error: string };
+
+/** The bot is logged in and listening. */
+export function reportReady(): void {
+    const message: StartupMessage = { type: 'ready' };
+    process.send?.(message);
+}
+
+/** The bot could not start: tell the manager why, then exit non-zero. */
+export function reportFailed(error: unknown): void {
+    const message: StartupMessage = { type: 'failed', error: error instanceof Error ? error.message : String(error) };
+    if (!process.send) process.exit(1);
+    process.send(message, () => process.exit(1));
+}
@@ -14,9 +14,10 @@ import fs from 'fs';
 import path from 'path';
 import https from 'https';
 import http from 'http';
-import { ensureSenderPaired, genId } from '@tinyagi/core';
+import { ensureSenderPaired, genId, splitMessage } from '@tinyagi/core';
 import { createSSEClient } from './sse-client';
 import { applyDefaultAgent } from './default-agent';
+import { reportReady, reportFailed } from './startup';
 
 const API_PORT = parseInt(process.env.TINYAGI_API_PORT || '3777', 10);
 const API_BASE = `http://localhost:${API_PORT}`;
@@ -29,6 +30,9 @@ const SETTINGS_FILE = path.join(TINYAGI_HOME, 'settings.json');
 const FILES_DIR = path.join(TINYAGI_HOME, 'files');
 const PAIRING_FILE = path.join(TINYAGI_HOME, 'pairing.json');
 
+// Telegram's per-message character limit
+const TELEGRAM_MAX_MESSAGE_LENGTH = 4096;
+
 // Ensure directories exist
 [path.dirname(LOG_FILE), FILES_DIR].forEach(dir => {
     if (!fs.existsSync(dir)) {
@@ -132,41 +136,6 @@ function getAgentListText(): string {
     }
 }
 
-// Split long messages for Telegram's 4096 char limit
-function splitMessage(text: string, maxLength = 4096): string[] {
-    if (text.length <= maxLength) {
-        return [text];
-    }
-
-    const chunks: string[] = [];
-    let remaining = text;
-
-    while (remaining.length > 0) {
-        if (remaining.length <= maxLength) {
-            chunks.push(remaining);
-            break;
-        }
-
-        // Try to split at a newline boundary
-        let splitIndex = remaining.lastIndexOf('\n', maxLength);
-
-        // Fall back to space boundary
-        if (splitIndex <= 0) {
-            splitIndex = remaining.lastIndexOf(' ', maxLength);
-        }
-
-        // Hard-cut if no good boundary found
-        if (splitIndex <= 0) {
-            splitIndex = maxLength;
-        }
-
-        chunks.push(remaining.substring(0, splitIndex));
-        remaining = remaining.substring(splitIndex).replace(/^\n/, '');
-    }
-
-    return chunks;
-}
-
 async function sendTelegramMessage(
     chatId: number,
     text: string,
@@ -536,7 +505,7 @@ async function checkOutgoingQueue(): Promise<void> {
 
                     // Split message if needed (Telegram 4096 char limit)
                     if (responseText) {
-                        const chunks = splitMessage(responseText);
+                        const chunks = splitMessage(responseText, TELEGRAM_MAX_MESSAGE_LENGTH);
                         const parseMode = resp.metadata?.parseMode as ParseMode | undefined;
 
                         if (chunks.length > 0) {
@@ -627,5 +596,10 @@ bot.start({
     onStart: (botInfo) => {
         log('INFO', `Telegram bot connected as @${botInfo.username}`);
         log('INFO', 'Listening for messages...');
+        reportReady();
     },
+}).catch((error) => {
+    // getMe rejected (bad token) or Telegram unreachable, or polling stopped for good
+    log('ERROR', `Telegram client stopped: ${error.message}`);
+    reportFailed(error);
 });
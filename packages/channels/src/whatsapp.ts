@@ -12,6 +12,7 @@ import path from 'path';
 import { ensureSenderPaired, genId } from '@tinyagi/core';
 import { createSSEClient } from './sse-client';
 import { applyDefaultAgent } from './default-agent';
+import { reportReady, reportFailed } from './startup';
 
 const API_PORT = parseInt(process.env.TINYAGI_API_PORT || '3777', 10);
 const API_BASE = `http://localhost:${API_PORT}`;
@@ -207,6 +208,7 @@ client.on('ready', () => {
     // Create ready flag for tinyagi.sh
     const readyFile = path.join(TINYAGI_HOME, 'channels/whatsapp_ready');
     fs.writeFileSync(readyFile, Date.now().toString());
+    reportReady();
 });
 
 // Message received - Write to queue
@@ -480,7 +482,7 @@ createSSEClient({
 // Error handlers
 client.on('auth_failure', (msg: string) => {
     log('ERROR', `Authentication failure: ${msg}`);
-    process.exit(1);
+    reportFailed(`authentication failure: ${msg}`);
 });
 
 client.on('disconnected', (reason: string) => {
@@ -535,4 +537,7 @@ process.on('SIGTERM', async () => {
 
 // Start client
 log('INFO', 'Starting WhatsApp client...');
-client.initialize();
+client.initialize().catch((error) => {
+    log('ERROR', `WhatsApp client failed to start: ${error.message}`);
+    reportFailed(error);
+});
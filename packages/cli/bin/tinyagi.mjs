@@ -2,7 +2,9 @@
 
 import { execSync, spawn } from 'child_process';
 import fs from 'fs';
+import os from 'os';
 import path from 'path';
+import { readProfile } from '../lib/profiles.mjs';
 
 // ── Constants ────────────────────────────────────────────────────────────────
 
@@ -29,15 +31,54 @@ const CLI_DIR = path.join(REPO_ROOT, 'packages/cli/dist');
 function runCliScript(script, args) {
     const scriptPath = path.join(CLI_DIR, script);
     const child = spawn('node', [scriptPath, ...args], { stdio: 'inherit' });
+    // Pass stop signals on (supervisors, `kill`). A Ctrl+C at the terminal
+    // already reaches the child through the process group, so it is not sent twice.
+    process.on('SIGTERM', () => child.kill('SIGTERM'));
+    process.on('SIGINT', () => {
+        if (!process.stdin.isTTY) child.kill('SIGINT');
+    });
     child.on('exit', (code) => process.exit(code || 0));
 }
 
+// ── Profiles ─────────────────────────────────────────────────────────────────
+
+// `tinyagi --profile <name> <command>` (or TINYAGI_PROFILE) runs against the
+// profile's own home and API port; every child script inherits the env.
+const argv = process.argv.slice(2);
+let profileName = process.env.TINYAGI_PROFILE;
+if (argv[0] === '--profile' || argv[0]?.startsWith('--profile=')) {
+    profileName = argv[0] === '--profile' ? argv[1] : argv[0].slice('--profile='.length);
+    argv.splice(0, argv[0] === '--profile' ? 2 : 1);
+}
+if (profileName && profileName !== 'default') {
+    const profile = readProfile(profileName);
+    if (!profile) {
+        log(RED, `No profile '${profileName}'. Create it with: tinyagi profiles create ${profileName}`);
+        process.exit(1);
+    }
+    process.env.TINYAGI_PROFILE = profile.name;
+    process.env.TINYAGI_HOME = profile.home;
+    process.env.TINYAGI_API_PORT = String(profile.port);
+}
+
+// Otherwise the port saved by `tinyagi setup --port`
+if (!process.env.TINYAGI_API_PORT) {
+    const home = process.env.TINYAGI_HOME || path.join(os.homedir(), '.tinyagi');
+    try {
+        const port = JSON.parse(fs.readFileSync(path.join(home, 'settings.json'), 'utf8')).server?.port;
+        if (port) process.env.TINYAGI_API_PORT = String(port);
+    } catch {
+        // No settings yet: keep the default port
+    }
+}
+
 // ── CLI Dispatch ─────────────────────────────────────────────────────────────
 
-const command = process.argv[2] || 'run';
-const restArgs = process.argv.slice(3);
+const command = argv[0] || 'run';
+const restArgs = argv.slice(1);
 
-console.log(BANNER);
+// Keep stdout machine-readable for --json and when piped (e.g. export > file)
+if (process.stdout.isTTY && !restArgs.includes('--json') && !restArgs.includes('--no-tty')) console.log(BANNER);
 
 switch (command) {
     // ── Install & Run ───────────────────────────────────────────────────────
@@ -53,11 +94,12 @@ switch (command) {
     // ── Daemon ──────────────────────────────────────────────────────────────
 
     case 'start':
-        runCliScript('daemon.js', ['start', '--open']);
+        // --dry-run only checks and --foreground keeps running; otherwise open TinyOffice after starting
+        runCliScript('daemon.js', ['start', ...(restArgs.includes('--dry-run') || restArgs.includes('--foreground') ? [] : ['--open']), ...restArgs]);
         break;
 
     case 'stop':
-        runCliScript('daemon.js', ['stop']);
+        runCliScript('daemon.js', ['stop', ...restArgs]);
         break;
 
     case 'restart':
@@ -65,7 +107,23 @@ switch (command) {
         break;
 
     case 'status':
-        runCliScript('daemon.js', ['status']);
+        runCliScript('daemon.js', ['status', ...restArgs]);
+        break;
+
+    case 'install-service':
+        runCliScript('service.js', ['install', ...restArgs]);
+        break;
+
+    case 'uninstall-service':
+        runCliScript('service.js', ['uninstall']);
+        break;
+
+    case 'print-dockerfile':
+        runCliScript('service.js', ['dockerfile', ...restArgs]);
+        break;
+
+    case 'print-compose':
+        runCliScript('service.js', ['compose', ...restArgs]);
         break;
 
     // ── Logs ────────────────────────────────────────────────────────────────
@@ -77,11 +135,43 @@ switch (command) {
     // ── Messaging ───────────────────────────────────────────────────────────
 
     case 'send':
+        if (!restArgs[0] && process.stdin.isTTY) {
+            console.log('Usage: tinyagi send <message> [--wait] [--json] (or pipe the message on stdin)');
+            process.exit(1);
+        }
+        runCliScript('messaging.js', ['send', ...restArgs]);
+        break;
+
+    case 'chat':
+        runCliScript('chat.js', restArgs);
+        break;
+
+    // ── Usage ───────────────────────────────────────────────────────────────
+
+    case 'usage':
+        runCliScript('usage.js', restArgs);
+        break;
+
+    case 'history':
+        runCliScript('history.js', restArgs);
+        break;
+
+    case 'queue':
+        runCliScript('queue.js', restArgs);
+        break;
+
+    case 'export': case 'import':
+        runCliScript('transfer.js', [command, ...restArgs]);
+        break;
+
+    // ── Knowledge base ──────────────────────────────────────────────────────
+
+    case 'ingest':
         if (!restArgs[0]) {
-            console.log('Usage: tinyagi send <message>');
+            console.log('Usage: tinyagi ingest <path> | tinyagi ingest --list');
             process.exit(1);
         }
-        runCliScript('messaging.js', ['send', restArgs[0]]);
+        runCliScript('ingest.js', restArgs);
         break;
 
     // ── Agent reset (top-level shortcut) ────────────────────────────────────
@@ -108,8 +198,13 @@ switch (command) {
     // ── Heartbeat ───────────────────────────────────────────────────────────
 
     case 'heartbeat':
-        log(YELLOW, 'Heartbeat runs automatically as part of the main process.');
-        log(YELLOW, 'Configure via monitoring.heartbeat_interval in settings.json.');
+        if (!restArgs[0]) {
+            log(YELLOW, 'Heartbeat runs automatically as part of the main process.');
+            log(YELLOW, 'Configure via monitoring.heartbeat_interval in settings.json.');
+            log(YELLOW, 'Usage: tinyagi heartbeat {trigger [agent]|edit [agent]|route [<channel> <target>|off]}');
+            break;
+        }
+        runCliScript('heartbeat.js', restArgs);
         break;
 
     // ── Agents ──────────────────────────────────────────────────────────────
@@ -196,6 +291,13 @@ switch (command) {
         break;
     }
 
+    case 'top': {
+        const topScript = path.join(REPO_ROOT, 'packages/visualizer/dist/top.js');
+        const child = spawn('node', [topScript, ...restArgs], { stdio: 'inherit' });
+        child.on('exit', (code) => process.exit(code || 0));
+        break;
+    }
+
     // ── Providers ───────────────────────────────────────────────────────────
 
     case 'provider':
@@ -210,14 +312,14 @@ switch (command) {
                 if (!restArgs[1]) { console.log('Usage: tinyagi provider remove <provider_id>'); process.exit(1); }
                 runCliScript('agent.js', ['provider-remove', restArgs[1]]);
                 break;
-            case 'anthropic': case 'openai':
+            case 'anthropic': case 'openai': case 'ollama': case 'llamacpp':
                 runCliScript('provider.js', restArgs);
                 break;
             case undefined: case '':
                 runCliScript('provider.js', ['show']);
                 break;
             default:
-                console.log('Usage: tinyagi provider {anthropic|openai|list|add|remove} [--model MODEL]');
+                console.log('Usage: tinyagi provider {anthropic|openai|ollama|llamacpp|list|add|remove} [--model MODEL]');
                 process.exit(1);
         }
         break;
@@ -226,6 +328,18 @@ switch (command) {
         runCliScript('provider.js', ['model', restArgs[0] || '']);
         break;
 
+    case 'models':
+        runCliScript('models.js', restArgs);
+        break;
+
+    case 'pull':
+        if (!restArgs[0]) {
+            console.log('Usage: tinyagi pull <ollama-model> | tinyagi pull <url|hf:owner/repo/file.gguf> [--sha256 HEX] [--use]');
+            process.exit(1);
+        }
+        runCliScript('pull.js', restArgs);
+        break;
+
     // ── Office ──────────────────────────────────────────────────────────────
 
     case 'office': {
@@ -250,10 +364,22 @@ switch (command) {
         runCliScript('pairing.js', restArgs);
         break;
 
-    // ── Setup (legacy alias) ────────────────────────────────────────────────
+    // ── Config ──────────────────────────────────────────────────────────────
+
+    case 'config':
+        runCliScript('config.js', restArgs);
+        break;
+
+    // ── Profiles ────────────────────────────────────────────────────────────
+
+    case 'profiles': case 'profile':
+        runCliScript('profile.js', restArgs);
+        break;
+
+    // ── Setup ───────────────────────────────────────────────────────────────
 
     case 'setup':
-        runCliScript('channel.js', ['setup']);
+        runCliScript('setup.js', restArgs);
         break;
 
     // ── Update ──────────────────────────────────────────────────────────────
@@ -272,27 +398,43 @@ switch (command) {
 
     case '--help': case '-h': case 'help':
         console.log('');
-        console.log('Usage: tinyagi [command]');
+        console.log('Usage: tinyagi [--profile <name>] [command]');
         console.log('');
         console.log('Quick Start:');
         console.log('  run                      Install, configure defaults, and start (default)');
         console.log('  install                  Install TinyAGI only');
         console.log('');
         console.log('Daemon:');
-        console.log('  start                    Start TinyAGI');
-        console.log('  stop                     Stop all processes');
+        console.log('  start [--verbose]        Start TinyAGI (--dry-run: check settings, backends, tokens)');
+        console.log('  start --foreground       Run attached, logs on stdout (containers; --no-tty: plain output)');
+        console.log('  stop [--force]           Stop after in-flight messages finish');
         console.log('  restart                  Restart TinyAGI');
-        console.log('  status                   Show current status');
+        console.log('  status [--json]          Show current status');
+        console.log('  install-service [--apply] Run as a systemd/launchd/Task Scheduler user service');
+        console.log('  uninstall-service        Remove the service');
+        console.log('  print-dockerfile         Print a Dockerfile for this setup');
+        console.log('  print-compose            Print a docker-compose.yml for this setup');
         console.log('');
         console.log('Config:');
+        console.log('  config get|set|unset ... Read or change settings.json by dot path');
+        console.log('  profiles list|create|remove  Separate data dirs: tinyagi --profile <name> <command>');
         console.log('  office                   Start TinyOffice web portal (http://localhost:3000)');
         console.log('');
         console.log('Messaging:');
-        console.log('  send <msg>               Send message to AI');
+        console.log('  send <msg> [--wait]      Send message to AI (--json, --file, --attach, stdin)');
+        console.log('  chat [--agent id]        Interactive chat with an agent');
+        console.log('  heartbeat trigger|edit|route  Fire the heartbeat now, edit heartbeat.md, route replies');
         console.log('  logs [type]              View logs (discord|whatsapp|telegram|heartbeat|daemon|queue|all)');
+        console.log('  usage [--by x] [--since] Usage report by sender, channel, or agent (--json)');
+        console.log('  history [search <q>]     Recent exchanges, or full-text search over them');
+        console.log('  queue [list|show|retry|purge|dead]  Inspect and manage queued messages (--json)');
+        console.log('  export [--format md|json] Dump transcripts (JSON also has conversations and memory)');
+        console.log('  import <file.json>       Load an export from another instance');
+        console.log('  ingest <path>            Add files to the local RAG knowledge base');
         console.log('');
         console.log('Channels & Services:');
         console.log('  channel setup            Configure channels interactively');
+        console.log('  setup [options] --yes    Configure without prompts (setup --help)');
         console.log('  channel start <ch>       Start a channel');
         console.log('  channel stop <ch>        Stop a channel');
         console.log('  channel restart <ch>     Restart a channel');
@@ -315,11 +457,15 @@ switch (command) {
         console.log('  team remove-agent <t> <a> Remove an agent from a team');
         console.log('  team visualize [id]      Live TUI dashboard');
         console.log('  chatroom <team_id>       Live chat room viewer');
+        console.log('  top                      Live monitor: queue, throughput, inference, errors, memory');
         console.log('');
         console.log('Providers:');
         console.log('  provider [name] [--model model]  Show or switch AI provider');
         console.log('  provider list|add|remove         Manage custom providers');
         console.log('  model [name]                     Show or switch AI model');
+        console.log('  models [installed|refresh]       Local model catalog and installed models (--json)');
+        console.log('  models path | prune [--dry-run]  Where models are stored and their disk usage; delete unused ones');
+        console.log('  pull <model|url|hf:...>          Download an Ollama model or GGUF file');
         console.log('');
         console.log('Other:');
         console.log('  reset <id> [...]         Reset specific agent conversation(s)');
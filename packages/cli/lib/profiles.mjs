@@ -0,0 +1,69 @@
+import fs from 'fs';
+import path from 'path';
+import os from 'os';
+
+/**
+ * Named profiles — separate TINYAGI_HOME directories (settings, queue,
+ * conversations, logs) with their own API port, so several assistants can
+ * run side by side. The default profile is ~/.tinyagi itself.
+ */
+
+export const PROFILES_DIR = path.join(os.homedir(), '.tinyagi', 'profiles');
+export const DEFAULT_PORT = 3777;
+
+const NAME_PATTERN = /^[a-z0-9][a-z0-9_-]*$/;
+const PROFILE_FILE = 'profile.json';
+
+export function isValidProfileName(name) {
+    return NAME_PATTERN.test(name) && name !== 'default';
+}
+
+export function profileHome(name) {
+    return path.join(PROFILES_DIR, name);
+}
+
+/** { name, home, port } for an existing profile, or null. */
+export function readProfile(name) {
+    if (!isValidProfileName(name)) return null;
+    const home = profileHome(name);
+    try {
+        const meta = JSON.parse(fs.readFileSync(path.join(home, PROFILE_FILE), 'utf8'));
+        return { name, home, port: meta.port };
+    } catch {
+        return null;
+    }
+}
+
+export function listProfiles() {
+    if (!fs.existsSync(PROFILES_DIR)) return [];
+    return fs.readdirSync(PROFILES_DIR)
+        .map(readProfile)
+        .filter(Boolean)
+        .sort((a, b) => a.name.localeCompare(b.name));
+}
+
+/** Create a profile directory; the port defaults to the next free one after 3777. */
+export function createProfile(name, port) {
+    if (!isValidProfileName(name)) {
+        throw new Error(`Invalid profile name '${name}' (lowercase letters, digits, - and _)`);
+    }
+    if (readProfile(name)) throw new Error(`Profile '${name}' already exists`);
+
+    const used = new Set([DEFAULT_PORT, ...listProfiles().map(p => p.port)]);
+    if (port !== undefined && used.has(port)) throw new Error(`Port ${port} is already used by another profile`);
+    if (port === undefined) {
+        port = DEFAULT_PORT + 1;
+        while (used.has(port)) port++;
+    }
+
+    const home = profileHome(name);
+    fs.mkdirSync(home, { recursive: true });
+    fs.writeFileSync(path.join(home, PROFILE_FILE), JSON.stringify({ port }, null, 2) + '\n');
+    return { name, home, port };
+}
+
+export function removeProfile(name) {
+    const profile = readProfile(name);
+    if (!profile) throw new Error(`No profile '${name}'`);
+    fs.rmSync(profile.home, { recursive: true, force: true });
+}
@@ -334,6 +334,8 @@ function agentProvider(agentId: string, providerArg?: string, flag?: string, mod
         case 'anthropic':
         case 'openai':
         case 'opencode':
+        case 'ollama':
+        case 'llamacpp':
             agent.provider = providerArg;
             if (model) agent.model = model;
             break;
@@ -342,7 +344,7 @@ function agentProvider(agentId: string, providerArg?: string, flag?: string, mod
                 agent.provider = providerArg;
                 if (model) agent.model = model;
             } else {
-                p.log.error('Usage: tinyagi agent provider <agent_id> {anthropic|openai|opencode|custom:<id>} [--model MODEL]');
+                p.log.error('Usage: tinyagi agent provider <agent_id> {anthropic|openai|opencode|ollama|llamacpp|custom:<id>} [--model MODEL]');
                 process.exit(1);
             }
     }
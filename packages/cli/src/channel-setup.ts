@@ -0,0 +1,78 @@
+/**
+ * Channel setup wizard — shared by `tinyagi setup` and `tinyagi channel setup`.
+ * No CLI dispatch here, so importing it has no side effects.
+ */
+
+import * as p from '@clack/prompts';
+import { unwrap, required, readSettings, writeSettings, printBanner } from './shared.ts';
+
+const ALL_CHANNELS = ['telegram', 'discord', 'whatsapp'] as const;
+
+const CHANNEL_DISPLAY: Record<string, string> = {
+    telegram: 'Telegram',
+    discord: 'Discord',
+    whatsapp: 'WhatsApp',
+};
+
+const CHANNEL_TOKEN_PROMPT: Record<string, string> = {
+    discord: 'Enter your Discord bot token',
+    telegram: 'Enter your Telegram bot token',
+};
+
+const CHANNEL_TOKEN_HELP: Record<string, string> = {
+    discord: 'Get one at: https://discord.com/developers/applications',
+    telegram: 'Create a bot via @BotFather on Telegram to get a token',
+};
+
+export async function channelSetup() {
+    printBanner();
+    p.intro('TinyAGI - Channel Setup');
+
+    const settings = readSettings();
+
+    const enabledChannels = unwrap(await p.multiselect({
+        message: 'Which messaging channels do you want to enable?',
+        options: ALL_CHANNELS.map(ch => ({
+            value: ch,
+            label: CHANNEL_DISPLAY[ch],
+            initialSelected: settings.channels?.enabled?.includes(ch),
+        })),
+        required: false,
+    }));
+
+    // Collect tokens for channels that need them
+    const tokens: Record<string, string> = {};
+    for (const ch of enabledChannels) {
+        if (CHANNEL_TOKEN_PROMPT[ch]) {
+            const existing = (settings.channels as any)?.[ch]?.bot_token;
+            const token = unwrap(await p.password({
+                message: `${CHANNEL_TOKEN_PROMPT[ch]} (${CHANNEL_TOKEN_HELP[ch]})${existing ? ' [leave empty to keep current]' : ''}`,
+                validate: existing ? undefined : required,
+            }));
+            tokens[ch] = token || existing || '';
+        }
+    }
+
+    // Update settings
+    if (!settings.channels) settings.channels = { enabled: [] };
+    settings.channels.enabled = enabledChannels as string[];
+    for (const ch of ALL_CHANNELS) {
+        if (CHANNEL_TOKEN_PROMPT[ch]) {
+            if (tokens[ch]) {
+                (settings.channels as any)[ch] = { bot_token: tokens[ch] };
+            }
+        }
+    }
+    if (enabledChannels.includes('whatsapp')) {
+        (settings.channels as any).whatsapp = (settings.channels as any).whatsapp || {};
+    }
+
+    writeSettings(settings);
+    p.log.success('Channel configuration saved');
+
+    if (enabledChannels.length > 0) {
+        p.outro('Run `tinyagi restart` to apply changes.');
+    } else {
+        p.outro('No channels enabled. You can add them later.');
+    }
+}
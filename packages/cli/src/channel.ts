@@ -47,7 +47,7 @@ switch (command) {
 
     case 'setup': {
         // Delegate to messaging module
-        const { channelSetup } = await import('./messaging.ts');
+        const { channelSetup } = await import('./channel-setup.ts');
         await channelSetup();
         break;
     }
@@ -0,0 +1,262 @@
+#!/usr/bin/env node
+/**
+ * Interactive chat with an agent on the running instance. Token deltas are
+ * streamed from the SSE feed; replies are collected from the `chat` channel's
+ * response queue like any other channel client.
+ */
+
+import * as p from '@clack/prompts';
+import fs from 'fs';
+import os from 'os';
+import path from 'path';
+import readline from 'readline';
+import { TINYAGI_HOME, getAgentResetFlag } from '@tinyagi/core';
+import { readSettings, writeSettings, requireSettings, providerOptions } from './shared.ts';
+import { api, takeReplies, followEvents, isStatusNote, stripSignature } from './client.ts';
+
+const CHANNEL = 'chat';
+const SENDER = 'CLI';
+const SENDER_ID = `chat-${process.pid}`;
+const HISTORY_FILE = path.join(TINYAGI_HOME, 'chat_history');
+const HISTORY_SIZE = 500;
+const POLL_MS = 500;
+
+const DIM = '\x1b[2m';
+const NC = '\x1b[0m';
+
+const HELP = `Commands:
+  /agent [id]       Show or switch the agent you are talking to
+  /model [p/model]  Show or change the agent's model (saved to settings)
+  /reset            Start a fresh conversation with the agent
+  /exit             Leave (also Ctrl+D)
+Other slash commands (/stop, /verbose, /undo, /branch, ...) go to the agent.
+Ctrl+C while waiting for a reply sends /stop.`;
+
+let agentId = '';
+let rl: readline.Interface;
+/** Messages sent and not yet answered. */
+const waiting = new Set<string>();
+/** Text streamed so far per message, compared with the final reply. */
+const streamed = new Map<string, string>();
+let streamingId: string | null = null;
+
+// ── Output ──────────────────────────────────────────────────────────────────
+
+function endStream(): void {
+    if (streamingId) process.stdout.write('\n');
+    streamingId = null;
+}
+
+/** Print a line above the prompt without mangling what the user is typing. */
+function print(text: string): void {
+    endStream();
+    readline.clearLine(process.stdout, 0);
+    readline.cursorTo(process.stdout, 0);
+    console.log(text);
+    rl.prompt(true);
+}
+
+function showDelta(messageId: string, delta: string): void {
+    if (streamingId !== messageId) {
+        endStream();
+        readline.clearLine(process.stdout, 0);
+        readline.cursorTo(process.stdout, 0);
+        streamingId = messageId;
+    }
+    streamed.set(messageId, (streamed.get(messageId) || '') + delta);
+    process.stdout.write(delta);
+}
+
+function showReply(messageId: string, message: string): void {
+    waiting.delete(messageId);
+    const body = stripSignature(message);
+    const preview = streamed.get(messageId);
+    streamed.delete(messageId);
+    if (preview !== undefined && preview.trim() === body.trim()) {
+        endStream();
+        rl.prompt(true);
+        return;
+    }
+    print(body);
+}
+
+// ── Server ──────────────────────────────────────────────────────────────────
+
+async function send(message: string): Promise<void> {
+    const result = await api('POST', '/api/message', {
+        message, channel: CHANNEL, sender: SENDER, senderId: SENDER_ID, agent: agentId,
+    });
+    if (!result.ok) {
+        print(`Could not send: ${result.error || JSON.stringify(result)}`);
+        return;
+    }
+    // Approval answers and /stop get no reply of their own
+    if (!result.approval && result.cancelled === undefined) waiting.add(result.messageId);
+}
+
+/** Deliver queued replies addressed to this session. */
+async function pollReplies(): Promise<void> {
+    for (const reply of await takeReplies(CHANNEL, SENDER_ID)) {
+        if (isStatusNote(reply.message)) {
+            print(`${DIM}${reply.message}${NC}`);
+        } else {
+            showReply(reply.messageId, reply.message);
+        }
+        for (const file of reply.files || []) print(`${DIM}[file: ${file}]${NC}`);
+    }
+}
+
+// ── Local commands ──────────────────────────────────────────────────────────
+
+function describeAgent(id: string): string {
+    const agent = readSettings().agents?.[id];
+    return agent ? `@${id} (${agent.provider}${agent.model ? `/${agent.model}` : ''})` : `@${id}`;
+}
+
+function switchAgent(id: string): void {
+    const agents = readSettings().agents || {};
+    if (!agents[id]) {
+        print(`No agent '${id}'. Agents: ${Object.keys(agents).map(a => `@${a}`).join(', ')}`);
+        return;
+    }
+    agentId = id;
+    print(`Talking to ${describeAgent(id)}`);
+}
+
+/** `provider/model` changes both; a bare name only changes the model. */
+function changeModel(spec: string): void {
+    const settings = readSettings();
+    const agent = settings.agents?.[agentId];
+    if (!agent) return;
+    const providers = providerOptions().map(o => o.value);
+    const slash = spec.indexOf('/');
+    const prefix = slash > 0 ? spec.slice(0, slash) : '';
+    if (providers.includes(prefix) || prefix.startsWith('custom:')) {
+        agent.provider = prefix;
+        agent.model = spec.slice(slash + 1);
+    } else {
+        agent.model = spec;
+    }
+    writeSettings(settings);
+    print(`${describeAgent(agentId)} — saved to settings, used from the next message`);
+}
+
+function resetAgent(): void {
+    const settings = readSettings();
+    const workspacePath = settings.workspace?.path || path.join(os.homedir(), 'tinyagi-workspace');
+    const flag = getAgentResetFlag(agentId, workspacePath);
+    fs.mkdirSync(path.dirname(flag), { recursive: true });
+    fs.writeFileSync(flag, '');
+    print(`The next message to @${agentId} starts a fresh conversation.`);
+}
+
+/** Handle a REPL-only command; returns false for messages to send. */
+function localCommand(line: string): boolean {
+    const [command, ...rest] = line.split(/\s+/);
+    const arg = rest.join(' ');
+    switch (command.toLowerCase()) {
+        case '/help':
+            print(HELP);
+            return true;
+        case '/exit': case '/quit':
+            rl.close();
+            return true;
+        case '/agent':
+            if (arg) switchAgent(arg.replace(/^@/, ''));
+            else print(`Talking to ${describeAgent(agentId)}`);
+            return true;
+        case '/model':
+            if (arg) changeModel(arg);
+            else print(describeAgent(agentId));
+            return true;
+        case '/reset':
+            resetAgent();
+            return true;
+        default:
+            return false;
+    }
+}
+
+// ── REPL ────────────────────────────────────────────────────────────────────
+
+function loadHistory(): string[] {
+    try {
+        return fs.readFileSync(HISTORY_FILE, 'utf8').split('\n').filter(Boolean).reverse().slice(0, HISTORY_SIZE);
+    } catch {
+        return [];
+    }
+}
+
+async function chat(initialAgent?: string): Promise<void> {
+    const settings = requireSettings();
+    const agents = Object.keys(settings.agents || {});
+    agentId = initialAgent || (agents.includes('tinyagi') ? 'tinyagi' : agents[0]);
+    if (!agentId || !settings.agents?.[agentId]) {
+        p.log.error(initialAgent ? `Agent '${initialAgent}' not found.` : 'No agents configured.');
+        process.exit(1);
+    }
+
+    try {
+        await api('GET', '/api/status');
+    } catch {
+        p.log.error('TinyAGI is not running. Start it with: tinyagi start');
+        process.exit(1);
+    }
+
+    rl = readline.createInterface({
+        input: process.stdin,
+        output: process.stdout,
+        prompt: '> ',
+        history: loadHistory(),
+        historySize: HISTORY_SIZE,
+    });
+    rl.on('history', (history: string[]) => {
+        fs.writeFileSync(HISTORY_FILE, [...history].reverse().join('\n') + '\n');
+    });
+
+    console.log(`Talking to ${describeAgent(agentId)}. /help for commands, /exit to leave.`);
+
+    followEvents((type, event) => {
+        if (type === 'agent:delta' && waiting.has(event.messageId)) showDelta(event.messageId, event.delta);
+    }).catch(err => print(`${DIM}Live streaming unavailable: ${err.message}${NC}`));
+    const poller = setInterval(() => {
+        pollReplies().catch(() => { /* server restarting; try again next tick */ });
+    }, POLL_MS);
+
+    rl.on('line', (input) => {
+        const line = input.trim();
+        if (!line) {
+            rl.prompt();
+            return;
+        }
+        if (localCommand(line)) return;
+        send(line).catch(err => print(`Could not send: ${err.message}`));
+        rl.prompt();
+    });
+    rl.on('SIGINT', () => {
+        if (waiting.size > 0) {
+            print('Stopping…');
+            send('/stop').catch(() => {});
+            return;
+        }
+        rl.close();
+    });
+    rl.on('close', () => {
+        clearInterval(poller);
+        console.log('');
+        process.exit(0);
+    });
+
+    rl.prompt();
+}
+
+// --- CLI dispatch ---
+
+const args = process.argv.slice(2);
+const agentIndex = args.findIndex(a => a === '--agent' || a === '-a');
+const agentArg = agentIndex >= 0 ? args[agentIndex + 1] : args.find(a => a.startsWith('@'))?.slice(1);
+
+chat(agentArg).catch(err => {
+    p.log.error(err.message);
+    process.exit(1);
+});
@@ -0,0 +1,67 @@
+/**
+ * Small client for the running instance's HTTP API, shared by `send` and
+ * `chat`: JSON requests, the SSE event feed, and collecting queued replies.
+ */
+
+const API_PORT = process.env.TINYAGI_API_PORT || '3777';
+export const API_URL = `http://localhost:${API_PORT}`;
+
+export interface QueuedReply {
+    id: number;
+    messageId: string;
+    senderId?: string;
+    agent?: string;
+    message: string;
+    files?: string[];
+}
+
+export async function api(method: string, route: string, body?: unknown): Promise<any> {
+    const res = await fetch(`${API_URL}${route}`, {
+        method,
+        headers: body ? { 'Content-Type': 'application/json' } : undefined,
+        body: body ? JSON.stringify(body) : undefined,
+    });
+    return res.json();
+}
+
+/** Fetch and acknowledge pending replies on `channel` addressed to `senderId`. */
+export async function takeReplies(channel: string, senderId: string): Promise<QueuedReply[]> {
+    const responses = await api('GET', `/api/responses/pending?channel=${encodeURIComponent(channel)}`) as QueuedReply[];
+    const mine = responses.filter(r => r.senderId === senderId);
+    for (const reply of mine) await api('POST', `/api/responses/${reply.id}/ack`);
+    return mine;
+}
+
+/** Progress notes sent to /verbose senders start with an ellipsis. */
+export function isStatusNote(message: string): boolean {
+    return message.startsWith('… ');
+}
+
+/** Drop the `- [agent]` signature added to direct replies. */
+export function stripSignature(message: string): string {
+    return message.replace(/\n\n- \[[^\]]+\]$/, '');
+}
+
+/**
+ * Follow the SSE feed, calling `onEvent` for each event until the stream
+ * ends or `signal` aborts.
+ */
+export async function followEvents(onEvent: (type: string, data: any) => void, signal?: AbortSignal): Promise<void> {
+    const res = await fetch(`${API_URL}/api/events/stream`, { signal });
+    const reader = res.body!.getReader();
+    const decoder = new TextDecoder();
+    let buffer = '';
+    for (;;) {
+        const { value, done } = await reader.read();
+        if (done) return;
+        buffer += decoder.decode(value, { stream: true });
+        let end: number;
+        while ((end = buffer.indexOf('\n\n')) !== -1) {
+            const block = buffer.slice(0, end);
+            buffer = buffer.slice(end + 2);
+            const type = block.match(/^event: (.*)$/m)?.[1];
+            const data = block.match(/^data: (.*)$/m)?.[1];
+            if (type && data) onEvent(type, JSON.parse(data));
+        }
+    }
+}
@@ -0,0 +1,143 @@
+#!/usr/bin/env node
+/**
+ * Non-interactive settings access — get, set, and unset values by dot path,
+ * e.g. `config set models.ollama.model phi4-mini`.
+ */
+
+import * as p from '@clack/prompts';
+import fs from 'fs';
+import { SETTINGS_FILE } from '@tinyagi/core';
+import { readSettings, writeSettings } from './shared.ts';
+
+const USAGE = `Usage: config get [path]
+       config set <path> <value> [--string] [--force]
+       config set <path> --stdin
+       config unset <path>`;
+
+/** Top-level sections of settings.json (see Settings in @tinyagi/core). */
+const KNOWN_SECTIONS = [
+    'workspace', 'channels', 'models', 'agents', 'custom_providers', 'teams', 'monitoring',
+    'routing', 'refine', 'processing', 'rag', 'moderation', 'stt', 'tools',
+];
+
+function splitPath(dotPath: string): string[] {
+    const keys = dotPath.split('.');
+    if (keys.some(k => !k)) {
+        p.log.error(`Invalid path '${dotPath}'.`);
+        process.exit(1);
+    }
+    return keys;
+}
+
+function lookup(root: any, keys: string[]): unknown {
+    let node = root;
+    for (const key of keys) {
+        if (node === null || typeof node !== 'object' || !(key in node)) return undefined;
+        node = node[key];
+    }
+    return node;
+}
+
+function typeName(value: unknown): string {
+    if (value === null) return 'null';
+    return Array.isArray(value) ? 'array' : typeof value;
+}
+
+/** JSON values (numbers, booleans, arrays, objects) are parsed; anything else is a string. */
+function parseValue(raw: string, asString: boolean): unknown {
+    if (asString) return raw;
+    try {
+        return JSON.parse(raw);
+    } catch {
+        return raw;
+    }
+}
+
+function configGet(dotPath?: string) {
+    const value = dotPath ? lookup(readSettings(), splitPath(dotPath)) : readSettings();
+    if (value === undefined) {
+        p.log.error(`${dotPath} is not set.`);
+        process.exit(1);
+    }
+    console.log(typeof value === 'string' ? value : JSON.stringify(value, null, 2));
+}
+
+function configSet(dotPath: string, value: unknown, force: boolean) {
+    const keys = splitPath(dotPath);
+    if (!KNOWN_SECTIONS.includes(keys[0]) && !force) {
+        p.log.error(`Unknown settings section '${keys[0]}'. Known: ${KNOWN_SECTIONS.join(', ')} (--force to set it anyway).`);
+        process.exit(1);
+    }
+
+    const settings: any = readSettings();
+    const current = lookup(settings, keys);
+    if (current !== undefined && current !== null && !force && typeName(current) !== typeName(value)) {
+        p.log.error(`${dotPath} is a ${typeName(current)}, not a ${typeName(value)} (--string to store text, --force to replace).`);
+        process.exit(1);
+    }
+
+    let node = settings;
+    for (const key of keys.slice(0, -1)) {
+        if (node[key] === undefined) node[key] = {};
+        if (typeof node[key] !== 'object' || node[key] === null || Array.isArray(node[key])) {
+            p.log.error(`Cannot set ${dotPath}: ${key} is a ${typeName(node[key])}.`);
+            process.exit(1);
+        }
+        node = node[key];
+    }
+    node[keys[keys.length - 1]] = value;
+    writeSettings(settings);
+    p.log.success(`Set ${dotPath}`);
+}
+
+function configUnset(dotPath: string) {
+    const keys = splitPath(dotPath);
+    const settings: any = readSettings();
+    const parent = keys.length > 1 ? lookup(settings, keys.slice(0, -1)) : settings;
+    const last = keys[keys.length - 1];
+    if (!parent || typeof parent !== 'object' || !(last in (parent as object))) {
+        p.log.message(`${dotPath} is not set.`);
+        return;
+    }
+    delete (parent as Record<string, unknown>)[last];
+    writeSettings(settings);
+    p.log.success(`Removed ${dotPath}`);
+}
+
+// --- CLI dispatch ---
+
+const args = process.argv.slice(2);
+const flags = new Set(args.filter(a => a.startsWith('--')));
+const [command, dotPath, rawValue] = args.filter(a => !a.startsWith('--'));
+
+if (command === 'set' && !fs.existsSync(SETTINGS_FILE)) {
+    p.log.warn(`No settings file yet; creating ${SETTINGS_FILE}.`);
+}
+
+switch (command) {
+    case 'get':
+        configGet(dotPath);
+        break;
+    case 'set': {
+        if (!dotPath || (rawValue === undefined && !flags.has('--stdin'))) {
+            p.log.error(USAGE);
+            process.exit(1);
+        }
+        // --stdin keeps secrets out of shell history; always stored as text
+        const value = flags.has('--stdin')
+            ? fs.readFileSync(0, 'utf8').replace(/\r?\n$/, '')
+            : parseValue(rawValue, flags.has('--string'));
+        configSet(dotPath, value, flags.has('--force'));
+        break;
+    }
+    case 'unset':
+        if (!dotPath) {
+            p.log.error(USAGE);
+            process.exit(1);
+        }
+        configUnset(dotPath);
+        break;
+    default:
+        p.log.error(USAGE);
+        process.exit(1);
+}
@@ -2,10 +2,11 @@
  * Daemon lifecycle — start, stop, restart, status.
  */
 
-import { execSync, spawn } from 'child_process';
+import { spawn, spawnSync } from 'child_process';
 import fs from 'fs';
+import os from 'os';
 import path from 'path';
-import { TINYAGI_HOME, SCRIPT_DIR } from '@tinyagi/core';
+import { TINYAGI_HOME, SCRIPT_DIR, getSettings, getAgents } from '@tinyagi/core';
 
 // ── Constants ────────────────────────────────────────────────────────────────
 
@@ -25,7 +26,7 @@ function log(color: string, msg: string): void {
 
 // ── Helpers ──────────────────────────────────────────────────────────────────
 
-function getMainScript(): string | null {
+export function getMainScript(): string | null {
     const local = path.join(SCRIPT_DIR, 'packages/main/dist/index.js');
     const installed = path.join(TINYAGI_HOME, 'packages/main/dist/index.js');
     if (fs.existsSync(local)) return local;
@@ -54,7 +55,7 @@ async function fetchStatus(): Promise<any> {
     }
 }
 
-async function waitForServer(maxWait = 8000): Promise<any> {
+export async function waitForServer(maxWait = 8000): Promise<any> {
     const start = Date.now();
     while (Date.now() - start < maxWait) {
         const status = await fetchStatus();
@@ -74,7 +75,7 @@ export function formatUptime(seconds: number): string {
 
 // ── Commands ─────────────────────────────────────────────────────────────────
 
-export async function startDaemon(): Promise<void> {
+export async function startDaemon(verbose = false): Promise<void> {
     if (isRunning()) {
         log(YELLOW, 'TinyAGI is already running');
         return;
@@ -94,7 +95,8 @@ export async function startDaemon(): Promise<void> {
     const child = spawn('node', [mainScript], {
         detached: true,
         stdio: ['ignore', out, out],
-        env: { ...process.env, TINYAGI_HOME },
+        // TINYAGI_DETACHED: nothing relaunches it on exit, so restart is stop + start
+        env: { ...process.env, TINYAGI_HOME, TINYAGI_DETACHED: '1', ...(verbose ? { TINYAGI_LOG_LEVEL: 'trace' } : {}) },
     });
 
     fs.writeFileSync(PID_FILE, String(child.pid));
@@ -102,17 +104,28 @@ export async function startDaemon(): Promise<void> {
 
     log(GREEN, `TinyAGI started (PID: ${child.pid})`);
 
-    const status = await waitForServer();
+    let status = await waitForServer();
     if (status) {
         log(GREEN, `  Server:    http://localhost:${status.server?.port || API_PORT}`);
 
+        status = await settleChannels(status);
         const channels = status.channels || {};
         const channelNames = Object.keys(channels);
         if (channelNames.length > 0) {
             for (const ch of channelNames) {
                 const c = channels[ch];
-                const icon = c.running ? GREEN + '●' : RED + '○';
-                log(NC, `  Channel:   ${icon} ${ch}${c.pid ? ` (PID: ${c.pid})` : ''}${NC}`);
+                if (c.ready) {
+                    log(NC, `  Channel:   ${GREEN}OK${NC}      ${ch}${c.pid ? ` (PID: ${c.pid})` : ''}`);
+                } else if (c.running) {
+                    log(NC, `  Channel:   ${YELLOW}WAITING${NC} ${ch} — not logged in yet; see: tinyagi logs ${ch}`);
+                } else {
+                    log(NC, `  Channel:   ${RED}FAILED${NC}  ${ch} — ${c.error || 'not running'}`);
+                }
+            }
+            if (channelNames.every(ch => !channels[ch].running)) {
+                log(RED, '  No channel started. TinyAGI is running, but only the API and "tinyagi send" will work.');
+                log(RED, `  Check the channel settings, or: tinyagi logs ${channelNames[0]}`);
+                process.exitCode = 1;
             }
         } else {
             log(NC, `  Channels:  ${YELLOW}none enabled${NC}`);
@@ -128,32 +141,360 @@ export async function startDaemon(): Promise<void> {
         log(YELLOW, '  (waiting for server...)');
     }
 
-    log(NC, `  Logs:      ${logFile}`);
+    log(NC, `  Logs:      ${logFile}${verbose ? ' (trace level: every event)' : ''}`);
+}
+
+// ── Foreground ───────────────────────────────────────────────────────────────
+
+/** Environment variables applied as `tinyagi setup` flags before a foreground start. */
+const SETUP_ENV: Record<string, string> = {
+    TINYAGI_CHANNELS: '--channel',
+    TINYAGI_PROVIDER: '--provider',
+    TINYAGI_MODEL: '--model',
+    TINYAGI_AGENT: '--agent',
+    TINYAGI_WORKSPACE: '--workspace',
+};
+
+/**
+ * Write settings from the environment, so a container needs no mounted
+ * settings.json: defaults on first start, then TINYAGI_CHANNELS,
+ * TINYAGI_PROVIDER, etc. on every start. Tokens are read by setup itself.
+ * With none of those set and settings in place, settings.json is left alone.
+ */
+function applyEnvSettings(): boolean {
+    const args = Object.entries(SETUP_ENV)
+        .filter(([key]) => process.env[key])
+        .flatMap(([key, flag]) => [flag, process.env[key]!]);
+    if (args.length === 0 && fs.existsSync(path.join(TINYAGI_HOME, 'settings.json'))) return true;
+    const result = spawnSync('node', [path.join(SCRIPT_DIR, 'packages/cli/dist/setup.js'), ...args, '--yes'], {
+        stdio: 'inherit',
+        // Already starting: setup skips its "run tinyagi start" hint
+        env: { ...process.env, TINYAGI_SETUP_FROM_START: '1' },
+    });
+    return result.status === 0;
+}
+
+/**
+ * `start --foreground`: run the processor attached to this process instead
+ * of detaching, with its logs on stdout — for containers and supervisors.
+ * SIGTERM/SIGINT are passed on so in-flight messages drain, and exit code
+ * 75 (restart requested through the API) starts it again.
+ */
+export async function runForeground(noTty = false): Promise<void> {
+    const say = (color: string, msg: string) => noTty ? console.log(`[tinyagi] ${msg}`) : log(color, msg);
+
+    if (isRunning()) {
+        say(RED, 'TinyAGI is already running; stop it first');
+        process.exit(1);
+    }
+    const mainScript = getMainScript();
+    if (!mainScript) {
+        say(RED, 'TinyAGI is not built. Run "npm run build" first.');
+        process.exit(1);
+    }
+    if (!applyEnvSettings()) {
+        say(RED, 'Could not apply settings from the environment');
+        process.exit(1);
+    }
+
+    for (;;) {
+        // Own process group, so a terminal Ctrl-C reaches the processor once
+        // (forwarded below) instead of twice, which would skip the drain
+        const child = spawn('node', [mainScript], {
+            detached: true,
+            stdio: 'inherit',
+            env: { ...process.env, TINYAGI_HOME },
+        });
+        const forward = (signal: NodeJS.Signals) => child.kill(signal);
+        process.on('SIGTERM', forward);
+        process.on('SIGINT', forward);
+
+        const code = await new Promise<number>(resolve => child.on('exit', (exitCode, signal) =>
+            resolve(exitCode ?? 128 + (signal ? os.constants.signals[signal] : 0))));
+        process.off('SIGTERM', forward);
+        process.off('SIGINT', forward);
+
+        if (code !== 75) {
+            if (code !== 0) say(RED, `TinyAGI exited with code ${code}`);
+            process.exitCode = code;
+            return;
+        }
+        say(YELLOW, 'Restarting...');
+        await new Promise(r => setTimeout(r, 1000));
+    }
+}
+
+// ── Dry run ──────────────────────────────────────────────────────────────────
+
+/** CLI each built-in provider runs; local backends are checked over HTTP instead. */
+const PROVIDER_BINARIES: Record<string, string> = {
+    anthropic: 'claude',
+    openai: 'codex',
+    opencode: 'opencode',
+};
+
+const TOKEN_ENV: Record<string, string> = {
+    discord: 'DISCORD_BOT_TOKEN',
+    telegram: 'TELEGRAM_BOT_TOKEN',
+};
+
+/** Whether `cmd` is a path that exists or a program on PATH (PATHEXT on Windows). */
+function commandExists(cmd: string): boolean {
+    if (path.isAbsolute(cmd)) return fs.existsSync(cmd);
+    const exts = process.platform === 'win32' ? (process.env.PATHEXT || '.EXE;.CMD;.BAT').split(';') : [''];
+    return (process.env.PATH || '').split(path.delimiter)
+        .some(dir => dir && exts.some(ext => fs.existsSync(path.join(dir, cmd + ext))));
+}
+
+async function reachable(url: string, init?: RequestInit): Promise<{ ok: boolean; detail: string; body?: any }> {
+    try {
+        const res = await fetch(url, { ...init, signal: AbortSignal.timeout(5000) });
+        const body = await res.json().catch(() => undefined);
+        return { ok: res.ok, detail: `HTTP ${res.status}`, body };
+    } catch (err) {
+        return { ok: false, detail: (err as Error).message };
+    }
+}
+
+async function checkBackend(provider: string, model: string | undefined, settings: ReturnType<typeof getSettings>): Promise<[boolean, string]> {
+    if (provider === 'ollama') {
+        const base = (settings.models?.ollama?.base_url || process.env.OLLAMA_HOST || 'http://localhost:11434').replace(/\/+$/, '');
+        const res = await reachable(`${base}/api/tags`);
+        if (!res.ok) return [false, `Ollama not reachable at ${base} (${res.detail})`];
+        const tags: string[] = (res.body?.models || []).map((m: any) => m.name);
+        const wanted = model || settings.models?.ollama?.model;
+        if (wanted && !tags.includes(wanted) && !tags.includes(`${wanted}:latest`)) {
+            return [false, `Ollama is up but ${wanted} is not pulled (tinyagi pull ${wanted})`];
+        }
+        return [true, `Ollama at ${base}${wanted ? `, ${wanted} available` : ''}`];
+    }
+    if (provider === 'llamacpp') {
+        const conf = settings.models?.llamacpp || {};
+        if (conf.model_path) {
+            if (!fs.existsSync(conf.model_path)) return [false, `GGUF not found: ${conf.model_path}`];
+            const binary = conf.server_binary || 'llama-server';
+            if (!commandExists(binary)) return [false, `${binary} not found in PATH`];
+            return [true, `llama-server will be launched with ${path.basename(conf.model_path)}`];
+        }
+        const base = (conf.base_url || `http://127.0.0.1:${conf.port || 8080}`).replace(/\/+$/, '');
+        const res = await reachable(`${base}/health`);
+        return res.ok ? [true, `llama-server at ${base}`] : [false, `llama-server not reachable at ${base} (${res.detail})`];
+    }
+    if (provider.startsWith('custom:')) {
+        const custom = settings.custom_providers?.[provider.slice('custom:'.length)];
+        if (!custom) return [false, `${provider} is not defined in custom_providers`];
+        const binary = custom.harness === 'codex' ? 'codex' : 'claude';
+        return commandExists(binary) ? [true, `${binary} CLI via ${custom.base_url}`] : [false, `${binary} CLI not found in PATH`];
+    }
+    const binary = PROVIDER_BINARIES[provider];
+    if (!binary) return [false, `unknown provider '${provider}'`];
+    return commandExists(binary) ? [true, `${binary} CLI found`] : [false, `${binary} CLI not found in PATH`];
+}
+
+async function checkChannel(channel: string, settings: ReturnType<typeof getSettings>): Promise<[boolean, string]> {
+    if (channel === 'whatsapp') {
+        const session = path.join(TINYAGI_HOME, 'whatsapp-session');
+        return [true, fs.existsSync(session) ? 'session found' : 'no session yet; a QR code will be shown in the logs'];
+    }
+    const envKey = TOKEN_ENV[channel];
+    if (!envKey) return [false, 'unknown channel'];
+    const token = process.env[envKey] || (settings.channels as any)?.[channel]?.bot_token;
+    if (!token) return [false, `no token (${envKey} or channels.${channel}.bot_token); it would be skipped`];
+
+    const res = channel === 'telegram'
+        ? await reachable(`https://api.telegram.org/bot${token}/getMe`)
+        : await reachable('https://discord.com/api/v10/users/@me', { headers: { Authorization: `Bot ${token}` } });
+    if (!res.ok) return [false, `token rejected or API unreachable (${res.detail})`];
+    const name = res.body?.result?.username || res.body?.username;
+    return [true, `token valid${name ? ` (@${name})` : ''}`];
+}
+
+/**
+ * `start --dry-run`: validate settings and everything start would touch,
+ * print what would be started, and exit non-zero if something would fail.
+ */
+export async function dryRunStart(): Promise<void> {
+    let failures = 0;
+    const check = (ok: boolean, label: string, detail: string) => {
+        if (!ok) failures++;
+        log(NC, `  ${ok ? `${GREEN}✓` : `${RED}✗`}${NC} ${label.padEnd(10)} ${detail}`);
+    };
+
+    log(GREEN, 'Dry run: nothing will be started');
+    log(NC, `  Data dir:   ${TINYAGI_HOME}`);
+
+    const settingsFile = path.join(TINYAGI_HOME, 'settings.json');
+    let settingsOk = false;
+    try {
+        JSON.parse(fs.readFileSync(settingsFile, 'utf8'));
+        settingsOk = true;
+    } catch (err) {
+        const reason = fs.existsSync(settingsFile) ? `invalid JSON: ${(err as Error).message}` : 'missing (run tinyagi setup)';
+        check(false, 'Settings', `${settingsFile} ${reason}`);
+    }
+    if (settingsOk) check(true, 'Settings', settingsFile);
+    const settings = getSettings();
+
+    const mainScript = getMainScript();
+    check(!!mainScript, 'Build', mainScript || 'not built (npm run build)');
+    check(!isRunning(), 'Process', isRunning() ? 'TinyAGI is already running' : `would run node ${mainScript ?? '<main>'}`);
+    const portInUse = (await reachable(`${API_URL}/api/status`)).detail.startsWith('HTTP');
+    check(!portInUse, 'API', portInUse ? `port ${API_PORT} is already in use` : `would listen on http://localhost:${API_PORT}`);
+
+    // getAgents: with no agents configured, the default agent from models.provider
+    for (const [id, agent] of Object.entries(getAgents(settings))) {
+        const [ok, detail] = await checkBackend(agent.provider, agent.model, settings);
+        check(ok, `@${id}`, `${agent.provider}${agent.model ? `/${agent.model}` : ''} — ${detail}`);
+    }
+
+    const channels = settings.channels?.enabled ?? [];
+    if (channels.length === 0) log(NC, `  ${YELLOW}-${NC} Channels   none enabled`);
+    for (const ch of channels) {
+        const [ok, detail] = await checkChannel(ch, settings);
+        check(ok, ch, detail);
+    }
+
+    const interval = settings.monitoring?.heartbeat_interval ?? 3600;
+    log(NC, `  ${GREEN}✓${NC} ${'Heartbeat'.padEnd(10)} every ${interval}s`);
+
+    if (failures > 0) {
+        log(RED, `${failures} problem${failures === 1 ? '' : 's'} found`);
+        process.exitCode = 1;
+    } else {
+        log(GREEN, 'Everything checks out; run "tinyagi start" to start');
+    }
 }
 
-export function stopDaemon(): void {
+/** How long the startup summary waits for channels to log in. */
+const CHANNEL_READY_TIMEOUT_MS = 20000;
+
+/**
+ * Channels report when they are logged in, or exit when their token is
+ * rejected or the platform cannot be reached. Poll until every channel has
+ * done one or the other (or the timeout passes), so the summary reflects it.
+ */
+async function settleChannels(status: any): Promise<any> {
+    const deadline = Date.now() + CHANNEL_READY_TIMEOUT_MS;
+    for (;;) {
+        const channels: Record<string, any> = status.channels || {};
+        const pending = Object.values(channels).some(c => c.running && !c.ready);
+        if (!pending || Date.now() > deadline) return status;
+        await new Promise(r => setTimeout(r, 500));
+        status = (await fetchStatus()) ?? status;
+    }
+}
+
+/** How long `stop --force` waits for the processor to exit before SIGKILL. */
+const FORCE_STOP_GRACE_SEC = 5;
+
+function isAlive(pid: number): boolean {
+    try {
+        process.kill(pid, 0);
+        return true;
+    } catch {
+        return false;
+    }
+}
+
+/**
+ * Ask the processor to shut down and wait while it finishes in-flight
+ * messages. `force` skips the wait (the processor exits on a second signal).
+ */
+export async function stopDaemon(force = false): Promise<void> {
     if (!fs.existsSync(PID_FILE)) {
         log(YELLOW, 'TinyAGI is not running');
         return;
     }
 
     const pid = parseInt(fs.readFileSync(PID_FILE, 'utf8').trim(), 10);
-    if (pid === 1) {
-        log(RED, 'TinyAGI is running as PID 1 (container mode). Use "tinyagi restart" or "docker restart" instead.');
-        return;
-    }
+
+    const working: any[] = force ? [] : (await fetchStatus())?.processing || [];
     try {
         process.kill(pid, 'SIGTERM');
-        log(GREEN, `TinyAGI stopped (PID: ${pid})`);
     } catch {
         log(YELLOW, 'Process already exited');
+        try { fs.unlinkSync(PID_FILE); } catch {}
+        return;
     }
+
+    const waitForExit = async (ms: number) => {
+        const deadline = Date.now() + ms;
+        while (isAlive(pid) && Date.now() < deadline) {
+            await new Promise(r => setTimeout(r, 250));
+        }
+    };
+
+    // The processor exits without draining on a second signal. Send it as a
+    // separate signal after a pause, since two sent back-to-back can coalesce.
+    const drainSec = getSettings().processing?.drain_timeout ?? 60;
+    const graceSec = force ? FORCE_STOP_GRACE_SEC : drainSec + 10;
+    if (force) {
+        await waitForExit(500);
+        try { process.kill(pid, 'SIGTERM'); } catch {}
+    } else if (working.length > 0) {
+        log(YELLOW, `Waiting for ${working.length} in-flight message(s) to finish ("tinyagi stop --force" skips this)...`);
+    }
+    await waitForExit(graceSec * 1000);
+    if (isAlive(pid)) {
+        log(RED, `TinyAGI did not exit within ${graceSec}s; killing PID ${pid}`);
+        try { process.kill(pid, 'SIGKILL'); } catch {}
+    }
+
+    log(GREEN, `TinyAGI stopped (PID: ${pid})`);
     try { fs.unlinkSync(PID_FILE); } catch {}
 }
 
-export async function statusDaemon(): Promise<void> {
+/**
+ * `status --json`: a stable, machine-readable snapshot. Fields are only ever
+ * added; `running: false` means the rest is from settings only.
+ */
+async function statusJson(): Promise<void> {
+    const settings = getSettings();
+    const agents = Object.entries(getAgents(settings)).map(([id, a]) => ({ id, provider: a.provider, model: a.model || null }));
+    const running = isRunning();
+    const status = running ? await fetchStatus() : null;
+    const queue = status?.ok
+        ? await fetch(`${API_URL}/api/queue/status`).then(r => r.json()).catch(() => null)
+        : null;
+    const channelStatus = status?.channels ?? {};
+
+    console.log(JSON.stringify({
+        running,
+        responding: !!status?.ok,
+        pid: running ? parseInt(fs.readFileSync(PID_FILE, 'utf8').trim(), 10) : null,
+        uptime: status?.uptime ?? null,
+        port: status?.server?.port ?? API_PORT,
+        agents,
+        channels: (status?.ok ? Object.keys(channelStatus) : settings.channels?.enabled ?? []).map(name => ({
+            name,
+            running: !!channelStatus[name]?.running,
+            ready: !!channelStatus[name]?.ready,
+            pid: channelStatus[name]?.pid ?? null,
+        })),
+        queue: queue && {
+            incoming: queue.incoming, queued: queue.queued, processing: queue.processing,
+            completed: queue.completed, dead: queue.dead, outgoing: queue.outgoing,
+        },
+        processing: status?.processing ?? [],
+        lastInference: status?.lastInference ?? null,
+        llamaServer: status?.llamaServer ?? null,
+        backends: status?.backends ?? {},
+        heartbeat: status?.heartbeat ?? null,
+    }, null, 2));
+    if (!running) process.exitCode = 3;
+}
+
+export async function statusDaemon(json = false): Promise<void> {
+    if (json) return statusJson();
+
     if (!isRunning()) {
-        log(YELLOW, 'TinyAGI is not running');
+        log(RED, '○ TinyAGI is stopped');
+        // Configured channels are not connected while the processor is down
+        for (const ch of getSettings().channels?.enabled ?? []) {
+            log(NC, `  Channel:   ${RED}○ ${ch}${NC} — not running (start TinyAGI with "tinyagi start")`);
+        }
+        // LSB convention: 3 = program is not running
+        process.exitCode = 3;
         return;
     }
 
@@ -161,14 +502,25 @@ export async function statusDaemon(): Promise<void> {
     const status = await fetchStatus();
 
     if (!status?.ok) {
-        log(GREEN, `TinyAGI is running (PID: ${pid})`);
+        log(YELLOW, `● TinyAGI is running (PID: ${pid}) but its API is not responding`);
         log(YELLOW, '  Server:    not responding');
         return;
     }
 
-    log(GREEN, `TinyAGI is running (PID: ${pid}, uptime: ${formatUptime(status.uptime)})`);
+    log(GREEN, `● TinyAGI is running (PID: ${pid}, uptime: ${formatUptime(status.uptime)})`);
     log(NC, `  Server:    ${GREEN}● http://localhost:${status.server?.port || API_PORT}${NC}`);
 
+    // Current agent models, and how the last inference went
+    for (const [id, agent] of Object.entries(getAgents(getSettings()))) {
+        log(NC, `  Agent:     @${id} — ${agent.provider}${agent.model ? `/${agent.model}` : ''}`);
+    }
+    const last = status.lastInference;
+    if (last) {
+        const model = [last.provider, last.model].filter(Boolean).join('/') || 'unknown model';
+        const ago = formatUptime(Math.max(0, Math.floor((Date.now() - last.at) / 1000)));
+        log(NC, `  Last run:  @${last.agentId} on ${model} took ${(last.durationMs / 1000).toFixed(1)}s (${ago} ago)`);
+    }
+
     // Queue status
     try {
         const qRes = await fetch(`${API_URL}/api/queue/status`);
@@ -183,20 +535,34 @@ export async function statusDaemon(): Promise<void> {
         log(NC, `  Queue:     ${YELLOW}? unknown${NC}`);
     }
 
+    // Messages being processed right now
+    for (const m of status.processing || []) {
+        const tokens = m.tokens > 0 ? `, ~${m.tokens} tokens` : '';
+        log(NC, `  Working:   ${GREEN}●${NC} @${m.agentId} ← ${m.channel}/${m.sender} (${formatUptime(Math.floor(m.elapsedMs / 1000))}${tokens})${m.status ? ` — ${m.status}` : ''}`);
+    }
+
     // Channels
     const channels = status.channels || {};
     const channelNames = Object.keys(channels);
     if (channelNames.length > 0) {
         for (const ch of channelNames) {
             const c = channels[ch];
-            const icon = c.running ? GREEN + '●' : RED + '○';
-            const state = c.running ? 'running' : 'stopped';
+            const icon = c.ready ? GREEN + '●' : c.running ? YELLOW + '●' : RED + '○';
+            const state = c.ready ? 'running' : c.running ? 'starting' : c.error ? `failed: ${c.error}` : 'stopped';
             log(NC, `  Channel:   ${icon} ${ch}${NC} — ${state}${c.pid ? ` (PID: ${c.pid})` : ''}`);
         }
     } else {
         log(NC, `  Channels:  ${YELLOW}none enabled${NC}`);
     }
 
+    // Managed llama-server
+    const llama = status.llamaServer;
+    if (llama?.managed) {
+        const icon = llama.state === 'healthy' ? GREEN + '●' : llama.state === 'stopped' ? YELLOW + '○' : RED + '●';
+        const restarts = llama.restarts > 0 ? `, ${llama.restarts} restart${llama.restarts === 1 ? '' : 's'}` : '';
+        log(NC, `  Llama:     ${icon} ${llama.state}${NC}${llama.pid ? ` (PID: ${llama.pid}${restarts})` : restarts ? ` (${restarts.slice(2)})` : ''}`);
+    }
+
     // Heartbeat
     const hb = status.heartbeat || {};
     if (hb.running) {
@@ -211,30 +577,48 @@ export async function statusDaemon(): Promise<void> {
 }
 
 export async function restartDaemon(): Promise<void> {
-    // Try API-based restart first (works in container mode and normal mode)
+    // A plain `tinyagi start` has no supervisor to relaunch it after the
+    // API's restart exit (code 75), so stop and start it here
+    const status = await fetchStatus();
+    if (!status?.ok || status.detached) {
+        await stopDaemon();
+        await startDaemon();
+        return;
+    }
+
+    // Under start --foreground, systemd, or launchd: the supervisor relaunches it
+    const readPid = () => {
+        try {
+            return parseInt(fs.readFileSync(PID_FILE, 'utf8').trim(), 10);
+        } catch {
+            return null;
+        }
+    };
+    const oldPid = readPid();
     try {
         const res = await fetch(`${API_URL}/api/services/restart`, { method: 'POST' });
         const data = await res.json() as any;
-        if (data.ok) {
-            log(GREEN, 'TinyAGI restarting...');
-            // Wait for the process to come back up
-            await new Promise(r => setTimeout(r, 2000));
-            const status = await waitForServer();
-            if (status) {
-                log(GREEN, 'TinyAGI restarted successfully');
-            } else {
-                log(YELLOW, 'TinyAGI is restarting (may take a moment)');
-            }
+        if (!data.ok) throw new Error(data.error);
+    } catch {
+        await stopDaemon();
+        await startDaemon();
+        return;
+    }
+    log(GREEN, 'TinyAGI restarting...');
+
+    // Success only once a new process is up: the old one answers while it drains
+    const waitSec = (getSettings().processing?.drain_timeout ?? 60) + 30;
+    const deadline = Date.now() + waitSec * 1000;
+    while (Date.now() < deadline) {
+        const pid = readPid();
+        if (pid && pid !== oldPid && isAlive(pid) && (await fetchStatus())?.ok) {
+            log(GREEN, `TinyAGI restarted successfully (PID: ${pid})`);
             return;
         }
-    } catch {
-        // API not available, fall back to kill+respawn
+        await new Promise(r => setTimeout(r, 500));
     }
-
-    // Fallback: stop and start (non-container mode)
-    stopDaemon();
-    await new Promise(r => setTimeout(r, 1000));
-    await startDaemon();
+    log(YELLOW, `TinyAGI did not come back within ${waitSec}s; check "tinyagi logs"`);
+    process.exitCode = 1;
 }
 
 export async function openOffice(): Promise<void> {
@@ -256,16 +640,25 @@ const flags = process.argv.slice(3);
 
 switch (command) {
     case 'start':
-        await startDaemon();
+        if (flags.includes('--dry-run')) {
+            await dryRunStart();
+            break;
+        }
+        if (flags.includes('--foreground')) {
+            if (flags.includes('--verbose') || flags.includes('-v')) process.env.TINYAGI_LOG_LEVEL = 'trace';
+            await runForeground(flags.includes('--no-tty') || !process.stdout.isTTY);
+            break;
+        }
+        await startDaemon(flags.includes('--verbose') || flags.includes('-v'));
         if (flags.includes('--open')) await openOffice();
         break;
     case 'stop':
-        stopDaemon();
+        await stopDaemon(flags.includes('--force'));
         break;
     case 'restart':
         await restartDaemon();
         break;
     case 'status':
-        await statusDaemon();
+        await statusDaemon(flags.includes('--json'));
         break;
 }
@@ -0,0 +1,155 @@
+#!/usr/bin/env node
+/**
+ * Heartbeat — fire it now, edit an agent's heartbeat.md, and choose where
+ * heartbeat replies are delivered.
+ */
+
+import * as p from '@clack/prompts';
+import { spawnSync } from 'child_process';
+import fs from 'fs';
+import path from 'path';
+import { getAgents, Settings } from '@tinyagi/core';
+import { readSettings, writeSettings } from './shared.ts';
+import { api } from './client.ts';
+
+const USAGE = `Usage: heartbeat trigger [agent]
+       heartbeat edit [agent]
+       heartbeat route <channel> <sender_id> [sender]
+       heartbeat route ntfy <topic_url> [--token t]
+       heartbeat route email <address> [--from address]
+       heartbeat route [off]`;
+
+const DEFAULT_PROMPT = 'Quick status check: Any pending tasks? Keep response brief.\n';
+const CHAT_CHANNELS = ['telegram', 'discord', 'whatsapp'];
+
+type HeartbeatRoute = NonNullable<NonNullable<Settings['monitoring']>['heartbeat_route']>;
+
+async function heartbeatTrigger(agentId?: string) {
+    let result: any;
+    try {
+        result = await api('POST', '/api/heartbeat/trigger', { agent: agentId });
+    } catch {
+        p.log.error('TinyAGI is not running. Start it with: tinyagi start');
+        process.exit(1);
+    }
+    if (!result.ok) {
+        p.log.error(result.error || 'Heartbeat failed');
+        process.exit(1);
+    }
+    if (result.queued.length === 0) {
+        p.log.warn('No agents have heartbeats enabled.');
+        return;
+    }
+    p.log.success(`Heartbeat queued for ${result.queued.map((id: string) => `@${id}`).join(', ')}`);
+    const route = readSettings().monitoring?.heartbeat_route;
+    p.log.message(route
+        ? `Replies go to ${describeRoute(route)}.`
+        : 'Replies stay on the heartbeat channel; see `tinyagi heartbeat route`.');
+}
+
+function describeRoute(route: HeartbeatRoute): string {
+    if (route.channel === 'ntfy') return `ntfy (${route.url})`;
+    if (route.channel === 'email') return `email (${route.to})`;
+    return `${route.channel}/${route.sender || route.sender_id}`;
+}
+
+function flagValue(args: string[], flag: string): string | undefined {
+    const i = args.indexOf(flag);
+    return i >= 0 ? args[i + 1] : undefined;
+}
+
+function heartbeatEdit(agentId?: string) {
+    const settings = readSettings();
+    const agents = getAgents(settings);
+    const id = agentId || (agents.tinyagi ? 'tinyagi' : Object.keys(agents)[0]);
+    const agent = agents[id];
+    if (!agent) {
+        p.log.error(`Agent '${id}' not found.`);
+        process.exit(1);
+    }
+
+    const workspacePath = settings.workspace?.path || path.join(process.env.HOME || '', 'tinyagi-workspace');
+    const file = path.join(agent.working_directory || path.join(workspacePath, id), 'heartbeat.md');
+    if (!fs.existsSync(file)) {
+        fs.mkdirSync(path.dirname(file), { recursive: true });
+        fs.writeFileSync(file, DEFAULT_PROMPT);
+    }
+
+    const editor = process.env.VISUAL || process.env.EDITOR || 'vi';
+    const result = spawnSync(editor, [file], { stdio: 'inherit', shell: true });
+    if (result.status !== 0) {
+        p.log.error(`${editor} exited with status ${result.status}`);
+        process.exit(1);
+    }
+    p.log.success(`Saved ${file}; it is read again on the next heartbeat.`);
+}
+
+function heartbeatRoute(args: string[]) {
+    const settings = readSettings();
+    const current = settings.monitoring?.heartbeat_route;
+    const retention = settings.monitoring?.heartbeat_retention_hours ?? 24;
+
+    if (args.length === 0) {
+        p.log.message(current
+            ? `Heartbeat replies go to ${describeRoute(current)}${current.sender_id ? ` (${current.sender_id})` : ''}.`
+            : 'Heartbeat replies are not delivered anywhere (they stay on the heartbeat channel).');
+        p.log.message(`Replies are deleted after ${retention}h (monitoring.heartbeat_retention_hours).`);
+        return;
+    }
+
+    if (args[0] === 'off') {
+        if (settings.monitoring) delete settings.monitoring.heartbeat_route;
+        writeSettings(settings);
+        p.log.success('Heartbeat replies will no longer be delivered.');
+        return;
+    }
+
+    const [channel, target] = args;
+    if (!target) {
+        p.log.error(USAGE);
+        process.exit(1);
+    }
+
+    let route: HeartbeatRoute;
+    if (channel === 'ntfy') {
+        if (!/^https?:\/\//.test(target)) {
+            p.log.error('The ntfy target is the full topic URL, e.g. https://ntfy.sh/my-topic');
+            process.exit(1);
+        }
+        route = { channel, url: target, token: flagValue(args, '--token') };
+    } else if (channel === 'email') {
+        route = { channel, to: target, sender: flagValue(args, '--from') };
+        p.log.message('Email is sent with the local `sendmail -t`; make sure an MTA is configured.');
+    } else if (CHAT_CHANNELS.includes(channel)) {
+        route = { channel, sender: args[2] || target, sender_id: target };
+        if (!settings.channels?.enabled?.includes(channel)) {
+            p.log.warn(`${channel} is not enabled; replies will wait until it is.`);
+        }
+    } else {
+        p.log.error(`Unknown channel '${channel}'. Choose from: ${[...CHAT_CHANNELS, 'ntfy', 'email'].join(', ')}`);
+        process.exit(1);
+    }
+
+    settings.monitoring = { ...settings.monitoring, heartbeat_route: route };
+    writeSettings(settings);
+    p.log.success(`Heartbeat replies will be sent to ${describeRoute(route)}.`);
+}
+
+// --- CLI dispatch ---
+
+const [command, ...rest] = process.argv.slice(2);
+
+switch (command) {
+    case 'trigger': case 'now':
+        await heartbeatTrigger(rest[0]?.replace(/^@/, ''));
+        break;
+    case 'edit':
+        heartbeatEdit(rest[0]?.replace(/^@/, ''));
+        break;
+    case 'route':
+        heartbeatRoute(rest);
+        break;
+    default:
+        p.log.error(USAGE);
+        process.exit(1);
+}
@@ -0,0 +1,72 @@
+#!/usr/bin/env node
+/**
+ * Transcript history — recent exchanges, or full-text search over them.
+ */
+
+import * as p from '@clack/prompts';
+import { initQueueDb, listTranscripts, searchTranscripts, TranscriptRow, TranscriptFilter } from '@tinyagi/core';
+import { parseDuration } from './shared.ts';
+
+function oneLine(text: string, max: number): string {
+    const flat = text.replace(/\s+/g, ' ').trim();
+    return flat.length > max ? `${flat.slice(0, max - 1)}…` : flat;
+}
+
+function printTranscript(row: TranscriptRow) {
+    const when = new Date(row.created_at).toLocaleString();
+    const model = [row.provider, row.model].filter(Boolean).join('/') || 'unknown';
+    const tokens = row.input_tokens || row.output_tokens ? `, ${row.input_tokens}→${row.output_tokens} tokens` : '';
+    console.log(`${when}  ${row.channel}/${row.sender} → @${row.agent_id}  (${model}, ${(row.duration_ms / 1000).toFixed(1)}s${tokens})`);
+    console.log(`  > ${oneLine(row.prompt, 160)}`);
+    console.log(`  < ${oneLine(row.snippet ?? row.response, 160)}`);
+    console.log('');
+}
+
+function history(query: string | undefined, filter: TranscriptFilter) {
+    try {
+        initQueueDb();
+    } catch (err) {
+        p.log.error(`Could not open queue database: ${(err as Error).message}`);
+        process.exit(1);
+    }
+
+    const rows = query ? searchTranscripts(query, filter) : listTranscripts(filter);
+    if (rows.length === 0) {
+        p.log.message(query ? `No transcripts match '${query}'.` : 'No transcripts recorded yet.');
+        return;
+    }
+    for (const row of query ? rows : [...rows].reverse()) printTranscript(row);
+}
+
+// --- CLI dispatch ---
+
+const USAGE = 'Usage: history [search <query>] [--agent id] [--channel ch] [--since 7d] [--limit n]';
+const args = process.argv.slice(2);
+const filter: TranscriptFilter = { limit: 20 };
+const search = args[0] === 'search';
+const words: string[] = [];
+
+for (let i = search ? 1 : 0; i < args.length; i++) {
+    const value = args[i + 1];
+    if (args[i] === '--agent' && value) {
+        filter.agentId = args[++i];
+    } else if (args[i] === '--channel' && value) {
+        filter.channel = args[++i];
+    } else if (args[i] === '--limit' && value && /^\d+$/.test(value)) {
+        filter.limit = parseInt(args[++i], 10);
+    } else if (args[i] === '--since' && value && parseDuration(value) !== null) {
+        filter.since = Date.now() - parseDuration(args[++i])!;
+    } else if (search && !args[i].startsWith('--')) {
+        words.push(args[i]);
+    } else {
+        p.log.error(USAGE);
+        process.exit(1);
+    }
+}
+
+if (search && words.length === 0) {
+    p.log.error(USAGE);
+    process.exit(1);
+}
+
+history(search ? words.join(' ') : undefined, filter);
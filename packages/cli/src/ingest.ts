@@ -0,0 +1,64 @@
+#!/usr/bin/env node
+/**
+ * RAG ingest — embed files into the local knowledge base, or list what is indexed.
+ */
+
+import * as p from '@clack/prompts';
+import fs from 'fs';
+import { initQueueDb, ingestPath, getRagSources } from '@tinyagi/core';
+import { formatTable } from './shared.ts';
+
+function openDb() {
+    try {
+        initQueueDb();
+    } catch (err) {
+        p.log.error(`Could not open queue database: ${(err as Error).message}`);
+        process.exit(1);
+    }
+}
+
+async function ingest(target: string) {
+    if (!fs.existsSync(target)) {
+        p.log.error(`No such file or directory: ${target}`);
+        process.exit(1);
+    }
+    openDb();
+
+    const s = p.spinner();
+    s.start(`Ingesting ${target}`);
+    try {
+        const result = await ingestPath(target);
+        s.stop(`Indexed ${result.indexed} file(s) (${result.chunks} chunks), ${result.unchanged} unchanged, ${result.removed} removed`);
+    } catch (err) {
+        s.stop('Ingest failed');
+        p.log.error((err as Error).message);
+        p.log.message('Is Ollama running, and is the embedding model pulled? (ollama pull nomic-embed-text)');
+        process.exit(1);
+    }
+}
+
+function listSources() {
+    openDb();
+    const sources = getRagSources();
+    if (sources.length === 0) {
+        p.log.message('Nothing indexed yet. Run: tinyagi ingest <path>');
+        return;
+    }
+    console.log(formatTable(
+        ['SOURCE', 'CHUNKS', 'MODIFIED'],
+        sources.map(s => [s.source, s.chunks, new Date(s.mtime).toLocaleString()]),
+    ));
+}
+
+// --- CLI dispatch ---
+
+const args = process.argv.slice(2);
+
+if (args[0] === '--list' || args[0] === 'list') {
+    listSources();
+} else if (args.length === 1) {
+    ingest(args[0]);
+} else {
+    p.log.error('Usage: ingest <path> | ingest --list');
+    process.exit(1);
+}
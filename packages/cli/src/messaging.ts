@@ -2,50 +2,165 @@
 import * as p from '@clack/prompts';
 import fs from 'fs';
 import path from 'path';
-import http from 'http';
-import { SCRIPT_DIR } from '@tinyagi/core';
-import { unwrap, required, readSettings, writeSettings, printBanner } from './shared.ts';
-
-const API_PORT = process.env.TINYAGI_API_PORT || '3777';
-const API_URL = `http://localhost:${API_PORT}`;
-
-function sendMessage(message: string, source = 'cli') {
-    const payload = JSON.stringify({ message, channel: 'cli', sender: source });
-
-    const url = new URL(`${API_URL}/api/message`);
-    const req = http.request({
-        hostname: url.hostname,
-        port: url.port,
-        path: url.pathname,
-        method: 'POST',
-        headers: {
-            'Content-Type': 'application/json',
-            'Content-Length': Buffer.byteLength(payload),
-        },
-    }, (res) => {
-        let body = '';
-        res.on('data', (chunk) => body += chunk);
-        res.on('end', () => {
-            try {
-                const result = JSON.parse(body);
-                if (result.ok) {
-                    console.log(`Message enqueued: ${result.messageId}`);
-                } else {
-                    console.error(`Failed to enqueue message: ${body}`);
-                }
-            } catch {
-                console.error(`Failed to parse response: ${body}`);
+import { SCRIPT_DIR, genId } from '@tinyagi/core';
+import { channelSetup } from './channel-setup.ts';
+import { api, takeReplies, followEvents, isStatusNote, stripSignature, QueuedReply } from './client.ts';
+
+const SEND_USAGE = 'Usage: send [message|-] [--file path] [--attach path]... [--agent id] [--wait] [--json] [--timeout 600]';
+const SEND_POLL_MS = 500;
+
+interface SendOptions {
+    message: string;
+    file?: string;
+    useStdin: boolean;
+    attach: string[];
+    agent?: string;
+    wait: boolean;
+    json: boolean;
+    timeoutSec: number;
+}
+
+function parseSendArgs(args: string[]): SendOptions {
+    const opts: SendOptions = { message: '', useStdin: false, attach: [], wait: false, json: false, timeoutSec: 600 };
+    const words: string[] = [];
+    for (let i = 0; i < args.length; i++) {
+        const arg = args[i];
+        if (arg === '--wait') opts.wait = true;
+        else if (arg === '--json') opts.json = true;
+        else if (arg === '-') opts.useStdin = true;
+        else if (!arg.startsWith('--')) words.push(arg);
+        else {
+            const value = args[++i];
+            if (value === undefined) fail(SEND_USAGE);
+            if (arg === '--file') opts.file = value;
+            else if (arg === '--attach') opts.attach.push(value);
+            else if (arg === '--agent') opts.agent = value.replace(/^@/, '');
+            else if (arg === '--timeout' && /^\d+$/.test(value)) opts.timeoutSec = parseInt(value, 10);
+            else fail(SEND_USAGE);
+        }
+    }
+    opts.message = words.join(' ');
+    return opts;
+}
+
+function fail(message: string): never {
+    p.log.error(message);
+    process.exit(1);
+}
+
+/** The message text: arguments, --file, or stdin (when piped or given as "-"). */
+function readMessageText(opts: SendOptions): string {
+    const parts: string[] = [];
+    if (opts.message) parts.push(opts.message);
+    if (opts.file) {
+        try {
+            parts.push(fs.readFileSync(opts.file, 'utf8'));
+        } catch (err) {
+            fail(`Could not read ${opts.file}: ${(err as Error).message}`);
+        }
+    }
+    if (opts.useStdin || (parts.length === 0 && !process.stdin.isTTY)) {
+        parts.push(fs.readFileSync(0, 'utf8'));
+    }
+    const text = parts.join('\n\n').trim();
+    if (!text) fail(SEND_USAGE);
+
+    // Attachments use the same [file: …] references as channel uploads
+    const refs = opts.attach.map(file => {
+        const abs = path.resolve(file);
+        if (!fs.existsSync(abs)) fail(`Attachment not found: ${file}`);
+        return `[file: ${abs}]`;
+    });
+    return refs.length > 0 ? `${text}\n\n${refs.join('\n')}` : text;
+}
+
+async function sendMessage(args: string[]) {
+    const opts = parseSendArgs(args);
+    const message = readMessageText(opts);
+    const wait = opts.wait || opts.json;
+    const senderId = `send-${process.pid}`;
+    const messageId = genId('cli');
+    const startedAt = Date.now();
+
+    // Stream token deltas for this message while waiting (not in --json mode),
+    // and watch for the queue to finish with it
+    const events = new AbortController();
+    let streamed = '';
+    const progress: { finished?: { status: string; error?: string }; eventsFailed: boolean } = { eventsFailed: false };
+    if (wait) {
+        followEvents((type, event) => {
+            if (event.messageId !== messageId) return;
+            if (type === 'agent:delta' && !opts.json) {
+                streamed += event.delta;
+                process.stdout.write(event.delta);
+            } else if (type === 'message:finished' && event.status !== 'pending') {
+                progress.finished = event;
             }
+        }, events.signal).catch(() => { progress.eventsFailed = true; });
+    }
+
+    let result: any;
+    try {
+        result = await api('POST', '/api/message', {
+            message, channel: 'cli', sender: 'CLI', senderId, agent: opts.agent, messageId,
         });
-    });
+    } catch (err) {
+        fail(`Failed to send message: ${(err as Error).message} (is TinyAGI running?)`);
+    }
+    if (!result.ok) fail(`Failed to enqueue message: ${JSON.stringify(result)}`);
 
-    req.on('error', (err) => {
-        console.error(`Failed to send message: ${err.message}`);
-        process.exit(1);
-    });
+    if (!wait) {
+        console.log(`Message enqueued: ${result.messageId}`);
+        return;
+    }
 
-    req.write(payload);
-    req.end();
+    // Collect every reply until the message is finished; without the event
+    // stream, until a poll after the first reply brings nothing new
+    const deadline = startedAt + opts.timeoutSec * 1000;
+    const replies: QueuedReply[] = [];
+    for (let done = false; !done && Date.now() < deadline;) {
+        await new Promise(r => setTimeout(r, SEND_POLL_MS));
+        const wasFinished = progress.finished !== undefined;
+        const batch = (await takeReplies('cli', senderId).catch(() => []))
+            .filter(r => r.messageId === messageId && !isStatusNote(r.message));
+        replies.push(...batch);
+        done = wasFinished || (progress.eventsFailed && replies.length > 0 && batch.length === 0);
+    }
+    events.abort();
+    if (replies.length === 0) {
+        if (progress.finished?.error) fail(`Message ${messageId} failed: ${progress.finished.error}`);
+        fail(`No reply within ${opts.timeoutSec}s (message ${messageId} is still queued or running)`);
+    }
+
+    const latencyMs = Date.now() - startedAt;
+    const text = replies.map(r => stripSignature(r.message)).join('\n\n');
+    const files = replies.flatMap(r => r.files ?? []);
+    const reply = replies[replies.length - 1];
+
+    if (!opts.json) {
+        if (streamed.trim() === text.trim()) process.stdout.write('\n');
+        else console.log(streamed ? `\n${text}` : text);
+        for (const file of files) console.log(`[file: ${file}]`);
+        return;
+    }
+
+    // Usage is recorded before the replies are queued
+    let transcript: any;
+    for (let i = 0; i < 10 && !transcript; i++) {
+        transcript = (await api('GET', `/api/transcripts?messageId=${encodeURIComponent(messageId)}&limit=1`).catch(() => []))[0];
+        if (!transcript) await new Promise(r => setTimeout(r, 200));
+    }
+    console.log(JSON.stringify({
+        messageId,
+        agent: reply.agent ?? transcript?.agent_id ?? null,
+        message: text,
+        files,
+        latencyMs,
+        provider: transcript?.provider ?? null,
+        model: transcript?.model ?? null,
+        inputTokens: transcript?.input_tokens ?? null,
+        outputTokens: transcript?.output_tokens ?? null,
+    }, null, 2));
 }
 
 export function channelsReset(channel: string) {
@@ -76,77 +191,6 @@ export function channelsReset(channel: string) {
     p.log.message(`Or manually edit .tinyagi/settings.json to change the ${channel} token.`);
 }
 
-const ALL_CHANNELS = ['telegram', 'discord', 'whatsapp'] as const;
-
-const CHANNEL_DISPLAY: Record<string, string> = {
-    telegram: 'Telegram',
-    discord: 'Discord',
-    whatsapp: 'WhatsApp',
-};
-
-const CHANNEL_TOKEN_PROMPT: Record<string, string> = {
-    discord: 'Enter your Discord bot token',
-    telegram: 'Enter your Telegram bot token',
-};
-
-const CHANNEL_TOKEN_HELP: Record<string, string> = {
-    discord: 'Get one at: https://discord.com/developers/applications',
-    telegram: 'Create a bot via @BotFather on Telegram to get a token',
-};
-
-export async function channelSetup() {
-    printBanner();
-    p.intro('TinyAGI - Channel Setup');
-
-    const settings = readSettings();
-
-    const enabledChannels = unwrap(await p.multiselect({
-        message: 'Which messaging channels do you want to enable?',
-        options: ALL_CHANNELS.map(ch => ({
-            value: ch,
-            label: CHANNEL_DISPLAY[ch],
-            initialSelected: settings.channels?.enabled?.includes(ch),
-        })),
-        required: false,
-    }));
-
-    // Collect tokens for channels that need them
-    const tokens: Record<string, string> = {};
-    for (const ch of enabledChannels) {
-        if (CHANNEL_TOKEN_PROMPT[ch]) {
-            const existing = (settings.channels as any)?.[ch]?.bot_token;
-            const token = unwrap(await p.password({
-                message: `${CHANNEL_TOKEN_PROMPT[ch]} (${CHANNEL_TOKEN_HELP[ch]})${existing ? ' [leave empty to keep current]' : ''}`,
-                validate: existing ? undefined : required,
-            }));
-            tokens[ch] = token || existing || '';
-        }
-    }
-
-    // Update settings
-    if (!settings.channels) settings.channels = { enabled: [] };
-    settings.channels.enabled = enabledChannels as string[];
-    for (const ch of ALL_CHANNELS) {
-        if (CHANNEL_TOKEN_PROMPT[ch]) {
-            if (tokens[ch]) {
-                (settings.channels as any)[ch] = { bot_token: tokens[ch] };
-            }
-        }
-    }
-    if (enabledChannels.includes('whatsapp')) {
-        (settings.channels as any).whatsapp = (settings.channels as any).whatsapp || {};
-    }
-
-    writeSettings(settings);
-    p.log.success('Channel configuration saved');
-
-    if (enabledChannels.length > 0) {
-        p.outro('Run `tinyagi restart` to apply changes.');
-    } else {
-        p.outro('No channels enabled. You can add them later.');
-    }
-}
-
 // --- CLI dispatch ---
 
 const command = process.argv[2];
@@ -154,11 +198,7 @@ const arg = process.argv[3];
 
 switch (command) {
     case 'send':
-        if (!arg) {
-            p.log.error('Usage: messaging send <message>');
-            process.exit(1);
-        }
-        sendMessage(arg);
+        sendMessage(process.argv.slice(3)).catch(err => fail(err.message));
         break;
     case 'channels-reset':
         if (!arg) {
@@ -0,0 +1,188 @@
+#!/usr/bin/env node
+/**
+ * Model catalog — suggested local models, which ones are downloaded, where
+ * they are stored, and pruning the ones no agent uses.
+ */
+
+import * as p from '@clack/prompts';
+import fs from 'fs';
+import {
+    MODELS_DIR, getModelCatalog, refreshModelCatalog, getInstalledGgufModels, getInstalledOllamaModels,
+    getOllamaModelsDir, getModelsInUse, isModelInUse, deleteOllamaModel, InstalledModel,
+} from '@tinyagi/core';
+import { formatTable, formatBytes, readSettings } from './shared.ts';
+
+const USAGE = 'Usage: models [installed|refresh|path|prune [--dry-run] [--yes]] [--json]';
+
+const json = process.argv.includes('--json');
+
+async function installedModels(): Promise<InstalledModel[]> {
+    const gguf = getInstalledGgufModels();
+    try {
+        return [...await getInstalledOllamaModels(), ...gguf];
+    } catch (err) {
+        // Keep stdout clean for --json consumers
+        const warning = `Ollama not reachable (${(err as Error).message}); showing GGUF files only.`;
+        if (json) console.error(warning);
+        else p.log.warn(warning);
+        return gguf;
+    }
+}
+
+async function showCatalog() {
+    const installed = await installedModels();
+    const ollamaTags = new Set(installed.filter(m => m.backend === 'ollama').map(m => m.name));
+    const ggufFiles = new Set(installed.filter(m => m.backend === 'llamacpp').map(m => m.name));
+
+    const catalog = getModelCatalog().map(m => {
+        const have: string[] = [];
+        // Ollama reports untagged pulls as name:latest
+        if (m.ollama && (ollamaTags.has(m.ollama) || ollamaTags.has(`${m.ollama}:latest`))) have.push('ollama');
+        if (m.gguf && ggufFiles.has(m.gguf.split('/').pop()!)) have.push('gguf');
+        return { ...m, installed: have };
+    });
+    if (json) {
+        console.log(JSON.stringify(catalog, null, 2));
+        return;
+    }
+
+    const rows = catalog.map(m => [
+        m.id,
+        m.label,
+        m.quantization || '',
+        m.size_gb ? `${m.size_gb} GB` : '',
+        m.ram_gb ? `${m.ram_gb} GB` : '',
+        m.license || '',
+        m.tools ? 'yes' : '',
+        m.installed.join(', '),
+    ]);
+    console.log(formatTable(['ID', 'MODEL', 'QUANT', 'SIZE', 'RAM', 'LICENSE', 'TOOLS', 'INSTALLED'], rows));
+    p.log.message('Download with: tinyagi pull <id> (Ollama) or tinyagi pull <id> --gguf (llama.cpp)');
+}
+
+async function showInstalled() {
+    const installed = await installedModels();
+    if (json) {
+        console.log(JSON.stringify(installed.map(m => ({ ...m, path: m.path ?? null })), null, 2));
+        return;
+    }
+    if (installed.length === 0) {
+        p.log.message('No local models installed. See: tinyagi models');
+        return;
+    }
+    console.log(formatTable(
+        ['BACKEND', 'MODEL', 'SIZE', 'PATH'],
+        installed.map(m => [m.backend, m.name, formatBytes(m.bytes), m.path || '']),
+    ));
+    const total = installed.reduce((sum, m) => sum + m.bytes, 0);
+    p.log.info(`Total disk usage: ${formatBytes(total)}`);
+}
+
+async function refresh() {
+    try {
+        const count = await refreshModelCatalog();
+        if (!json) p.log.success(`Catalog updated (${count} models)`);
+    } catch (err) {
+        p.log.error((err as Error).message);
+        process.exit(1);
+    }
+}
+
+async function showPaths() {
+    const installed = await installedModels();
+    const inUse = getModelsInUse(readSettings());
+    const ollamaDir = getOllamaModelsDir();
+    const models = installed.map(m => ({ ...m, path: m.path ?? null, inUse: isModelInUse(m, inUse) }));
+
+    if (json) {
+        console.log(JSON.stringify({ ggufDir: MODELS_DIR, ollamaDir, models }, null, 2));
+        return;
+    }
+    p.log.message(`GGUF files:    ${MODELS_DIR}`);
+    p.log.message(`Ollama models: ${ollamaDir ?? 'on a remote server (models.ollama.base_url)'}`);
+    if (models.length === 0) return;
+    console.log(formatTable(
+        ['BACKEND', 'MODEL', 'SIZE', 'IN USE', 'PATH'],
+        models.map(m => [m.backend, m.name, formatBytes(m.bytes), m.inUse ? 'yes' : '', m.path || '']),
+    ));
+    const total = models.reduce((sum, m) => sum + m.bytes, 0);
+    const unused = models.filter(m => !m.inUse).reduce((sum, m) => sum + m.bytes, 0);
+    p.log.info(`Total disk usage: ${formatBytes(total)} (${formatBytes(unused)} unused; see: tinyagi models prune)`);
+}
+
+async function prune(dryRun: boolean, yes: boolean) {
+    const inUse = getModelsInUse(readSettings());
+    const unused = (await installedModels()).filter(m => !isModelInUse(m, inUse));
+    const total = unused.reduce((sum, m) => sum + m.bytes, 0);
+
+    if (unused.length === 0) {
+        if (json) console.log(JSON.stringify({ removed: [], bytes: 0 }, null, 2));
+        else p.log.success('Every installed model is in use; nothing to prune.');
+        return;
+    }
+    if (!json) {
+        console.log(formatTable(['BACKEND', 'MODEL', 'SIZE'], unused.map(m => [m.backend, m.name, formatBytes(m.bytes)])));
+        p.log.info(`${unused.length} unused model(s), ${formatBytes(total)}`);
+    }
+    if (dryRun) {
+        if (json) console.log(JSON.stringify({ removed: [], unused, bytes: total }, null, 2));
+        return;
+    }
+    if (!yes) {
+        if (!process.stdin.isTTY) {
+            p.log.error('Pruning deletes model files; add --yes to confirm.');
+            process.exit(1);
+        }
+        const confirmed = await p.confirm({ message: `Delete ${unused.length} model(s) and free ${formatBytes(total)}?`, initialValue: false });
+        if (p.isCancel(confirmed) || !confirmed) {
+            p.log.message('Cancelled.');
+            return;
+        }
+    }
+
+    const removed: InstalledModel[] = [];
+    for (const m of unused) {
+        try {
+            if (m.backend === 'ollama') await deleteOllamaModel(m.name);
+            else fs.unlinkSync(m.path!);
+            removed.push(m);
+        } catch (err) {
+            const warning = `Could not remove ${m.name}: ${(err as Error).message}`;
+            if (json) console.error(warning);
+            else p.log.warn(warning);
+        }
+    }
+    const freed = removed.reduce((sum, m) => sum + m.bytes, 0);
+    if (json) console.log(JSON.stringify({ removed, bytes: freed }, null, 2));
+    else p.log.success(`Removed ${removed.length} model(s), freed ${formatBytes(freed)}`);
+    if (removed.length < unused.length) process.exitCode = 1;
+}
+
+// --- CLI dispatch ---
+
+const args = process.argv.slice(2).filter(a => a !== '--json');
+
+switch (args[0]) {
+    case undefined:
+    case 'list':
+        showCatalog();
+        break;
+    case 'installed':
+    case '--installed':
+        showInstalled();
+        break;
+    case 'refresh':
+    case '--refresh':
+        refresh().then(showCatalog);
+        break;
+    case 'path':
+    case 'paths':
+        showPaths();
+        break;
+    case 'prune':
+        prune(args.includes('--dry-run'), args.includes('--yes') || args.includes('-y'));
+        break;
+    default:
+        p.log.error(USAGE);
+        process.exit(1);
+}
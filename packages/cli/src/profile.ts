@@ -0,0 +1,127 @@
+#!/usr/bin/env node
+/**
+ * Profiles — list, create, and remove named data directories. Use one with
+ * `tinyagi --profile <name> <command>`.
+ */
+
+import * as p from '@clack/prompts';
+import fs from 'fs';
+import os from 'os';
+import path from 'path';
+import { formatTable } from './shared.ts';
+
+interface Profile {
+    name: string;
+    home: string;
+    port: number;
+}
+
+// @ts-ignore — untyped .mjs helper
+const profiles = await import('../lib/profiles.mjs') as {
+    DEFAULT_PORT: number;
+    listProfiles: () => Profile[];
+    readProfile: (name: string) => Profile | null;
+    createProfile: (name: string, port?: number) => Profile;
+    removeProfile: (name: string) => void;
+};
+
+const USAGE = 'Usage: profiles {list|create <name> [--port n]|remove <name> [--yes]}';
+const DEFAULT_HOME = path.join(os.homedir(), '.tinyagi');
+
+function isRunning(home: string): boolean {
+    try {
+        const pid = parseInt(fs.readFileSync(path.join(home, 'tinyagi.pid'), 'utf8').trim(), 10);
+        process.kill(pid, 0);
+        return true;
+    } catch {
+        return false;
+    }
+}
+
+function displayHome(home: string): string {
+    return home.startsWith(os.homedir()) ? `~${home.slice(os.homedir().length)}` : home;
+}
+
+function profileList() {
+    const rows = [
+        { name: 'default', home: DEFAULT_HOME, port: profiles.DEFAULT_PORT },
+        ...profiles.listProfiles(),
+    ].map(prof => [
+        prof.name === (process.env.TINYAGI_PROFILE || 'default') ? `* ${prof.name}` : `  ${prof.name}`,
+        displayHome(prof.home),
+        prof.port,
+        isRunning(prof.home) ? 'running' : 'stopped',
+    ]);
+    console.log(formatTable(['  PROFILE', 'HOME', 'PORT', 'STATUS'], rows));
+}
+
+function profileCreate(name: string, args: string[]) {
+    let port: number | undefined;
+    if (args[0] === '--port') {
+        port = parseInt(args[1], 10);
+        if (!port || port < 1 || port > 65535) {
+            p.log.error(USAGE);
+            process.exit(1);
+        }
+    } else if (args.length > 0) {
+        p.log.error(USAGE);
+        process.exit(1);
+    }
+
+    let profile: Profile;
+    try {
+        profile = profiles.createProfile(name, port);
+    } catch (err) {
+        p.log.error((err as Error).message);
+        process.exit(1);
+    }
+    p.log.success(`Created profile '${name}' in ${displayHome(profile.home)} (API port ${profile.port})`);
+    p.log.message(`Configure it with: tinyagi --profile ${name} setup ... --yes`);
+    p.log.message(`Start it with:     tinyagi --profile ${name} start`);
+}
+
+async function profileRemove(name: string, yes: boolean) {
+    const profile = profiles.readProfile(name);
+    if (!profile) {
+        p.log.error(`No profile '${name}'.`);
+        process.exit(1);
+    }
+    if (isRunning(profile.home)) {
+        p.log.error(`Profile '${name}' is running. Stop it first: tinyagi --profile ${name} stop`);
+        process.exit(1);
+    }
+    if (!yes) {
+        const confirmed = await p.confirm({
+            message: `Delete ${displayHome(profile.home)} with its settings, queue, and conversations?`,
+            initialValue: false,
+        });
+        if (p.isCancel(confirmed) || !confirmed) {
+            p.log.message('Cancelled.');
+            return;
+        }
+    }
+    profiles.removeProfile(name);
+    p.log.success(`Removed profile '${name}'`);
+}
+
+// --- CLI dispatch ---
+
+const [command, name, ...rest] = process.argv.slice(2);
+
+switch (command) {
+    case 'list': case 'ls': case undefined:
+        profileList();
+        break;
+    case 'create':
+    case 'remove': case 'rm':
+        if (!name) {
+            p.log.error(USAGE);
+            process.exit(1);
+        }
+        if (command === 'create') profileCreate(name, rest);
+        else await profileRemove(name, rest.includes('--yes') || rest.includes('-y'));
+        break;
+    default:
+        p.log.error(USAGE);
+        process.exit(1);
+}
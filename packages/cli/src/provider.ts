@@ -7,8 +7,7 @@ import { readSettings, writeSettings, requireSettings } from './shared.ts';
 function providerShow() {
     const settings = requireSettings();
     const provider = settings.models?.provider || 'anthropic';
-    const modelSection = provider === 'openai' ? settings.models?.openai : settings.models?.anthropic;
-    const model = modelSection?.model || '';
+    const model = (settings.models as Record<string, any> | undefined)?.[provider]?.model || '';
 
     if (model) {
         p.log.info(`Global default: ${provider}/${model}`);
@@ -29,6 +28,13 @@ function providerShow() {
 
 // --- provider set ---
 
+const PROVIDER_LABELS: Record<string, string> = {
+    anthropic: 'Anthropic',
+    openai: 'OpenAI/Codex',
+    ollama: 'Ollama',
+    llamacpp: 'llama.cpp',
+};
+
 function providerSet(providerName: string, args: string[]) {
     const settings = requireSettings();
 
@@ -36,6 +42,8 @@ function providerSet(providerName: string, args: string[]) {
     let modelArg = '';
     let oauthTokenArg = '';
     let apiKeyArg = '';
+    let baseUrlArg = '';
+    let modelPathArg = '';
     for (let i = 0; i < args.length; i++) {
         if (args[i] === '--model' && args[i + 1]) {
             modelArg = args[++i];
@@ -43,11 +51,15 @@ function providerSet(providerName: string, args: string[]) {
             oauthTokenArg = args[++i];
         } else if (args[i] === '--api-key' && args[i + 1]) {
             apiKeyArg = args[++i];
+        } else if (args[i] === '--base-url' && args[i + 1]) {
+            baseUrlArg = args[++i];
+        } else if (args[i] === '--model-path' && args[i + 1]) {
+            modelPathArg = args[++i];
         }
     }
 
-    if (providerName !== 'anthropic' && providerName !== 'openai') {
-        p.log.error('Usage: provider {anthropic|openai} [--model MODEL] [--oauth-token TOKEN] [--api-key KEY]');
+    if (!PROVIDER_LABELS[providerName]) {
+        p.log.error('Usage: provider {anthropic|openai|ollama|llamacpp} [--model MODEL] [--oauth-token TOKEN] [--api-key KEY] [--base-url URL] [--model-path GGUF]');
         process.exit(1);
     }
 
@@ -71,13 +83,19 @@ function providerSet(providerName: string, args: string[]) {
             }
         }
 
-        p.log.success(`Switched to ${providerName === 'anthropic' ? 'Anthropic' : 'OpenAI/Codex'} provider with model: ${modelArg}`);
+        p.log.success(`Switched to ${PROVIDER_LABELS[providerName]} provider with model: ${modelArg}`);
         if (updatedCount > 0) {
             p.log.message(`  Updated ${updatedCount} agent(s) from ${oldProvider} to ${providerName}/${modelArg}`);
         }
     } else {
-        p.log.success(`Switched to ${providerName === 'anthropic' ? 'Anthropic' : 'OpenAI/Codex'} provider`);
-        if (providerName === 'openai') {
+        p.log.success(`Switched to ${PROVIDER_LABELS[providerName]} provider`);
+        if (providerName === 'ollama') {
+            p.log.message("Use 'tinyagi provider ollama --model NAME' to set the model (default: llama3.2).");
+            p.log.message("Note: Make sure the Ollama server is running ('ollama serve').");
+        } else if (providerName === 'llamacpp') {
+            p.log.message("Use 'tinyagi provider llamacpp --model-path FILE.gguf' to have TinyAGI launch llama-server,");
+            p.log.message("or '--base-url URL' to use one that is already running.");
+        } else if (providerName === 'openai') {
             p.log.message("Use 'tinyagi model {gpt-5.3-codex|gpt-5.2}' to set the model.");
             p.log.message("Note: Make sure you have the 'codex' CLI installed.");
         } else {
@@ -91,6 +109,18 @@ function providerSet(providerName: string, args: string[]) {
         p.log.success(`OAuth token saved for ${providerName}`);
     }
 
+    if (baseUrlArg) {
+        if (!settings.models[providerName]) settings.models[providerName] = {};
+        (settings.models as any)[providerName].base_url = baseUrlArg;
+        p.log.success(`Base URL saved for ${providerName}: ${baseUrlArg}`);
+    }
+
+    if (modelPathArg) {
+        if (!settings.models[providerName]) settings.models[providerName] = {};
+        (settings.models as any)[providerName].model_path = modelPathArg;
+        p.log.success(`Model path saved for ${providerName}: ${modelPathArg}`);
+    }
+
     if (apiKeyArg) {
         if (!settings.models[providerName]) settings.models[providerName] = {};
         (settings.models as any)[providerName].api_key = apiKeyArg;
@@ -105,8 +135,7 @@ function providerSet(providerName: string, args: string[]) {
 function modelShow() {
     const settings = requireSettings();
     const provider = settings.models?.provider || 'anthropic';
-    const modelSection = provider === 'openai' ? settings.models?.openai : settings.models?.anthropic;
-    const model = modelSection?.model || '';
+    const model = (settings.models as Record<string, any> | undefined)?.[provider]?.model || '';
 
     if (model) {
         p.log.info(`Global default: ${provider}/${model}`);
@@ -185,6 +214,8 @@ switch (command) {
         break;
     case 'anthropic':
     case 'openai':
+    case 'ollama':
+    case 'llamacpp':
         providerSet(command, args);
         break;
     case 'model':
@@ -196,7 +227,7 @@ switch (command) {
         break;
     default:
         p.log.error(`Unknown provider command: ${command}`);
-        p.log.message('Usage: provider {show|anthropic|openai} [--model MODEL] [--oauth-token TOKEN] [--api-key KEY]');
+        p.log.message('Usage: provider {show|anthropic|openai|ollama|llamacpp} [--model MODEL] [--oauth-token TOKEN] [--api-key KEY] [--base-url URL] [--model-path GGUF]');
         p.log.message('       provider model [name]');
         process.exit(1);
 }
@@ -0,0 +1,111 @@
+#!/usr/bin/env node
+/**
+ * Model download — `ollama pull` through the API, or a GGUF file for llama.cpp.
+ */
+
+import * as p from '@clack/prompts';
+import { downloadModel, pullOllamaModel, findCatalogModel, DownloadProgress } from '@tinyagi/core';
+import { readSettings, writeSettings, formatBytes } from './shared.ts';
+
+/** Redraw a single progress line (no-op when stdout is not a terminal). */
+function renderProgress({ status, completed, total }: DownloadProgress) {
+    if (!process.stdout.isTTY) return;
+    let line = status;
+    if (total > 0) {
+        const width = 30;
+        const filled = Math.round((completed / total) * width);
+        line = `${status.padEnd(12)} [${'#'.repeat(filled)}${'.'.repeat(width - filled)}] `
+            + `${Math.floor((completed / total) * 100)}%  ${formatBytes(completed)} / ${formatBytes(total)}`;
+    } else if (completed > 0) {
+        line = `${status}  ${formatBytes(completed)}`;
+    }
+    process.stdout.write(`\r${line.slice(0, (process.stdout.columns || 80) - 1).padEnd((process.stdout.columns || 80) - 1)}`);
+}
+
+function endProgress() {
+    if (process.stdout.isTTY) process.stdout.write('\n');
+}
+
+async function pullGguf(spec: string, sha256: string | undefined, use: boolean) {
+    let file: string;
+    try {
+        file = await downloadModel(spec, { sha256, onProgress: renderProgress });
+        endProgress();
+    } catch (err) {
+        endProgress();
+        p.log.error((err as Error).message);
+        p.log.message('Run the same command again to resume the download.');
+        process.exit(1);
+    }
+    p.log.success(`Saved ${file}`);
+
+    if (use) {
+        const settings = readSettings();
+        settings.models = settings.models || {};
+        settings.models.llamacpp = { ...settings.models.llamacpp, model_path: file };
+        writeSettings(settings);
+        p.log.success('Set models.llamacpp.model_path');
+    } else {
+        p.log.message(`Use it with: tinyagi provider llamacpp --model-path ${file}`);
+    }
+}
+
+async function pullOllama(name: string) {
+    try {
+        await pullOllamaModel(name, renderProgress);
+        endProgress();
+    } catch (err) {
+        endProgress();
+        p.log.error((err as Error).message);
+        process.exit(1);
+    }
+    p.log.success(`Pulled ${name} into Ollama`);
+}
+
+// --- CLI dispatch ---
+
+const args = process.argv.slice(2);
+let spec: string | undefined;
+let sha256: string | undefined;
+let use = false;
+let gguf = false;
+
+for (let i = 0; i < args.length; i++) {
+    if (args[i] === '--sha256' && args[i + 1]) {
+        sha256 = args[++i];
+    } else if (args[i] === '--use') {
+        use = true;
+    } else if (args[i] === '--gguf') {
+        gguf = true;
+    } else if (!spec && !args[i].startsWith('--')) {
+        spec = args[i];
+    } else {
+        spec = undefined;
+        break;
+    }
+}
+
+if (!spec) {
+    p.log.error('Usage: pull <catalog-id|ollama-model> [--gguf] | pull <https://…/file.gguf | hf:owner/repo/file.gguf> [--sha256 HEX] [--use]');
+    process.exit(1);
+}
+
+// Catalog ids (see `tinyagi models`) map to an Ollama tag, or with --gguf to a GGUF file
+const entry = findCatalogModel(spec);
+if (entry) {
+    const target = gguf ? entry.gguf : entry.ollama;
+    if (!target) {
+        p.log.error(`${entry.id} has no ${gguf ? 'GGUF download' : 'Ollama tag'} in the catalog`);
+        process.exit(1);
+    }
+    spec = target;
+} else if (gguf) {
+    p.log.error(`--gguf needs a catalog id; '${spec}' is not in the catalog (see: tinyagi models)`);
+    process.exit(1);
+}
+
+if (/^(https?:|hf:)/.test(spec)) {
+    pullGguf(spec, sha256, use);
+} else {
+    pullOllama(spec);
+}
@@ -0,0 +1,196 @@
+#!/usr/bin/env node
+/**
+ * Queue management — inspect, retry, and purge messages in the queue database.
+ */
+
+import * as p from '@clack/prompts';
+import {
+    initQueueDb, getQueuedMessages, getDeadMessages, getQueueMessage,
+    retryDeadMessage, purgeMessages,
+} from '@tinyagi/core';
+import { parseDuration, formatTable } from './shared.ts';
+
+const USAGE = 'Usage: queue {list|show <id>|retry <id>|purge [--older-than 1h | --all] [--dead] [--yes]|dead} [--json]';
+
+let json = false;
+
+/** Stable --json shape for a queue row (independent of the DB column names). */
+function messageJson(row: any) {
+    return {
+        id: row.id,
+        messageId: row.message_id,
+        status: row.status,
+        channel: row.channel,
+        sender: row.sender,
+        senderId: row.sender_id ?? null,
+        agent: row.agent ?? null,
+        fromAgent: row.from_agent ?? null,
+        retryCount: row.retry_count,
+        lastError: row.last_error ?? null,
+        message: row.message,
+        createdAt: row.created_at,
+        updatedAt: row.updated_at,
+    };
+}
+
+function printJson(value: unknown) {
+    console.log(JSON.stringify(value, null, 2));
+}
+
+function formatAge(ms: number): string {
+    const s = Math.max(0, Math.floor(ms / 1000));
+    if (s < 60) return `${s}s`;
+    if (s < 3600) return `${Math.floor(s / 60)}m`;
+    if (s < 86400) return `${Math.floor(s / 3600)}h ${Math.floor((s % 3600) / 60)}m`;
+    return `${Math.floor(s / 86400)}d`;
+}
+
+function preview(text: string, max = 50): string {
+    const flat = (text || '').replace(/\s+/g, ' ').trim();
+    return flat.length > max ? `${flat.slice(0, max - 1)}…` : flat;
+}
+
+function printMessages(rows: any[], dead: boolean) {
+    const now = Date.now();
+    console.log(formatTable(
+        ['ID', 'AGE', 'STATUS', 'CHANNEL', 'SENDER', 'AGENT', 'RETRIES', dead ? 'ERROR' : 'MESSAGE'],
+        rows.map(r => [
+            r.id, formatAge(now - r.created_at), r.status, r.channel,
+            r.from_agent ? `@${r.from_agent}` : r.sender, r.agent || 'default', r.retry_count,
+            preview(dead ? r.last_error : r.message),
+        ]),
+    ));
+}
+
+function queueList() {
+    const rows = getQueuedMessages();
+    if (json) return printJson(rows.map(messageJson));
+    if (rows.length === 0) {
+        p.log.message('Queue is empty.');
+        return;
+    }
+    printMessages(rows, false);
+}
+
+function queueDead() {
+    const rows = getDeadMessages();
+    if (json) return printJson(rows.map(messageJson));
+    if (rows.length === 0) {
+        p.log.message('No dead messages.');
+        return;
+    }
+    printMessages(rows, true);
+    p.log.message('Retry one with: tinyagi queue retry <id>');
+}
+
+function queueShow(id: string) {
+    const row = getQueueMessage(id);
+    if (!row) {
+        p.log.error(`No message '${id}'.`);
+        process.exit(1);
+    }
+    if (json) return printJson(messageJson(row));
+    const fields: [string, string][] = [
+        ['ID', `${row.id} (${row.message_id})`],
+        ['Status', `${row.status}${row.retry_count ? `, ${row.retry_count} failed attempt(s)` : ''}`],
+        ['Channel', row.channel],
+        ['Sender', row.from_agent ? `@${row.from_agent} (internal)` : `${row.sender}${row.sender_id ? ` (${row.sender_id})` : ''}`],
+        ['Agent', row.agent || 'default'],
+        ['Created', `${new Date(row.created_at).toLocaleString()} (${formatAge(Date.now() - row.created_at)} ago)`],
+        ['Updated', new Date(row.updated_at).toLocaleString()],
+    ];
+    if (row.last_error) fields.push(['Last error', row.last_error]);
+    for (const [label, value] of fields) console.log(`${`${label}:`.padEnd(12)}${value}`);
+    console.log('');
+    console.log(row.message);
+}
+
+function queueRetry(id: string) {
+    const row = getQueueMessage(id);
+    if (!row) {
+        p.log.error(`No message '${id}'.`);
+        process.exit(1);
+    }
+    if (!retryDeadMessage(row.id)) {
+        p.log.error(`Message ${row.id} is ${row.status}; only dead messages can be retried.`);
+        process.exit(1);
+    }
+    if (json) printJson({ ok: true, id: row.id });
+    else p.log.success(`Message ${row.id} re-queued.`);
+}
+
+async function queuePurge(args: string[]) {
+    let olderThan = 0;
+    let dead = false;
+    let confirmed = false;
+    for (let i = 0; i < args.length; i++) {
+        const ms = args[i] === '--older-than' && args[i + 1] ? parseDuration(args[i + 1]) : null;
+        if (ms !== null) {
+            olderThan = ms;
+            i++;
+        } else if (args[i] === '--dead') {
+            dead = true;
+        } else if (args[i] === '--all' || args[i] === '--yes' || args[i] === '-y') {
+            confirmed = true;
+        } else {
+            p.log.error(USAGE);
+            process.exit(1);
+        }
+    }
+
+    // Without --older-than every message goes; make sure that was meant
+    const status = dead ? 'dead' : 'pending';
+    if (!olderThan && !confirmed) {
+        if (json || !process.stdin.isTTY) {
+            p.log.error(`This deletes every ${status} message; add --all to confirm, or narrow it with --older-than.`);
+            process.exit(1);
+        }
+        const answer = await p.confirm({ message: `Delete every ${status} message?`, initialValue: false });
+        if (p.isCancel(answer) || !answer) {
+            p.log.message('Cancelled.');
+            return;
+        }
+    }
+
+    const count = purgeMessages(dead ? 'dead' : 'pending', olderThan);
+    if (json) return printJson({ ok: true, status: dead ? 'dead' : 'pending', deleted: count });
+    const age = olderThan ? ` older than ${formatAge(olderThan)}` : '';
+    p.log.success(`Deleted ${count} ${dead ? 'dead' : 'pending'} message(s)${age}.`);
+}
+
+// --- CLI dispatch ---
+
+const args = process.argv.slice(2);
+json = args.includes('--json');
+const [command, ...rest] = args.filter(a => a !== '--json');
+
+try {
+    initQueueDb();
+} catch (err) {
+    p.log.error(`Could not open queue database: ${(err as Error).message}`);
+    process.exit(1);
+}
+
+switch (command) {
+    case 'list': case 'ls': case undefined:
+        queueList();
+        break;
+    case 'dead':
+        queueDead();
+        break;
+    case 'show':
+    case 'retry':
+        if (!rest[0]) {
+            p.log.error(USAGE);
+            process.exit(1);
+        }
+        if (command === 'show') queueShow(rest[0]);
+        else queueRetry(rest[0]);
+        break;
+    case 'purge':
+        await queuePurge(rest);
+        break;
+    default:
+        p.log.error(USAGE);
+        process.exit(1);
+}
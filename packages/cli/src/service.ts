@@ -0,0 +1,378 @@
+#!/usr/bin/env node
+/**
+ * Service install — run TinyAGI under systemd (Linux), launchd (macOS), or
+ * Task Scheduler (Windows) as a user-level service, so it starts at login
+ * and restarts after a crash. Also prints a Dockerfile and docker-compose.yml
+ * for running it in a container.
+ */
+
+import { execFileSync } from 'child_process';
+import fs from 'fs';
+import os from 'os';
+import path from 'path';
+import { TINYAGI_HOME, SCRIPT_DIR, getSettings, getAgents } from '@tinyagi/core';
+import { getMainScript, isRunning, stopDaemon, waitForServer } from './daemon.ts';
+
+// Each profile (tinyagi --profile <name>) gets its own service
+const PROFILE = process.env.TINYAGI_PROFILE;
+const SERVICE_NAME = PROFILE ? `tinyagi-${PROFILE}` : 'tinyagi';
+const LAUNCHD_LABEL = PROFILE ? `com.tinyagi.agent.${PROFILE}` : 'com.tinyagi.agent';
+const LOG_DIR = path.join(TINYAGI_HOME, 'logs');
+const API_PORT = process.env.TINYAGI_API_PORT || '3777';
+
+const GREEN = '\x1b[32m';
+const YELLOW = '\x1b[33m';
+const RED = '\x1b[31m';
+const NC = '\x1b[0m';
+
+function log(color: string, msg: string): void {
+    process.stdout.write(`${color}${msg}${NC}\n`);
+}
+
+// ── Unit files ───────────────────────────────────────────────────────────────
+
+interface ServiceFile {
+    path: string;
+    content: string;
+    /** Commands that load and start it, shown when not applying. */
+    load: string[][];
+    unload: string[][];
+    encoding?: BufferEncoding;
+}
+
+function systemdUnit(mainScript: string): ServiceFile {
+    // Leave time for in-flight messages to drain on stop
+    const stopTimeout = (getSettings().processing?.drain_timeout ?? 60) + 15;
+    const file = path.join(os.homedir(), '.config/systemd/user', `${SERVICE_NAME}.service`);
+    const content = `[Unit]
+Description=TinyAGI
+After=network-online.target
+
+[Service]
+Type=simple
+ExecStart=${process.execPath} ${mainScript}
+WorkingDirectory=${SCRIPT_DIR}
+Environment=TINYAGI_HOME=${TINYAGI_HOME}
+Environment=TINYAGI_API_PORT=${API_PORT}
+Environment=PATH=${process.env.PATH}
+# Exit code 75 is a restart requested through the API
+Restart=on-failure
+RestartSec=5
+TimeoutStopSec=${stopTimeout}
+
+[Install]
+WantedBy=default.target
+`;
+    return {
+        path: file,
+        content,
+        load: [['systemctl', '--user', 'daemon-reload'], ['systemctl', '--user', 'enable', '--now', `${SERVICE_NAME}.service`]],
+        unload: [['systemctl', '--user', 'disable', '--now', `${SERVICE_NAME}.service`]],
+    };
+}
+
+function escapeXml(text: string): string {
+    return text.replace(/&/g, '&amp;').replace(/</g, '&lt;').replace(/>/g, '&gt;');
+}
+
+function launchdPlist(mainScript: string): ServiceFile {
+    const file = path.join(os.homedir(), 'Library/LaunchAgents', `${LAUNCHD_LABEL}.plist`);
+    const logFile = path.join(LOG_DIR, 'daemon.log');
+    const content = `<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>${LAUNCHD_LABEL}</string>
+    <key>ProgramArguments</key>
+    <array>
+        <string>${escapeXml(process.execPath)}</string>
+        <string>${escapeXml(mainScript)}</string>
+    </array>
+    <key>WorkingDirectory</key>
+    <string>${escapeXml(SCRIPT_DIR)}</string>
+    <key>EnvironmentVariables</key>
+    <dict>
+        <key>TINYAGI_HOME</key>
+        <string>${escapeXml(TINYAGI_HOME)}</string>
+        <key>TINYAGI_API_PORT</key>
+        <string>${API_PORT}</string>
+        <key>PATH</key>
+        <string>${escapeXml(process.env.PATH || '')}</string>
+    </dict>
+    <key>RunAtLoad</key>
+    <true/>
+    <key>KeepAlive</key>
+    <dict>
+        <key>SuccessfulExit</key>
+        <false/>
+    </dict>
+    <key>StandardOutPath</key>
+    <string>${escapeXml(logFile)}</string>
+    <key>StandardErrorPath</key>
+    <string>${escapeXml(logFile)}</string>
+</dict>
+</plist>
+`;
+    return {
+        path: file,
+        content,
+        load: [['launchctl', 'load', '-w', file]],
+        unload: [['launchctl', 'unload', '-w', file]],
+    };
+}
+
+/**
+ * Windows: a Task Scheduler task that starts at logon and runs TinyAGI in the
+ * foreground, which restarts it after a restart request. This is not a
+ * Windows service: Node cannot answer the service control manager without a
+ * wrapper such as WinSW, so there is no SCM start/stop, no event-log output,
+ * and nothing runs before logon. `schtasks /End` kills the process without a
+ * drain; `tinyagi stop` is the clean way to stop it.
+ */
+function windowsTask(): ServiceFile {
+    const file = path.join(TINYAGI_HOME, `${SERVICE_NAME}-task.xml`);
+    const logFile = path.join(LOG_DIR, 'daemon.log');
+    const cli = path.join(SCRIPT_DIR, 'packages/cli/bin/tinyagi.mjs');
+    const command = `set "TINYAGI_HOME=${TINYAGI_HOME}" && set "TINYAGI_API_PORT=${API_PORT}" && `
+        + `"${process.execPath}" "${cli}" start --foreground --no-tty >> "${logFile}" 2>&1`;
+    const content = `<?xml version="1.0" encoding="UTF-16"?>
+<Task version="1.2" xmlns="http://schemas.microsoft.com/windows/2004/02/mit/task">
+  <RegistrationInfo>
+    <Description>TinyAGI</Description>
+  </RegistrationInfo>
+  <Triggers>
+    <LogonTrigger>
+      <Enabled>true</Enabled>
+    </LogonTrigger>
+  </Triggers>
+  <Settings>
+    <MultipleInstancesPolicy>IgnoreNew</MultipleInstancesPolicy>
+    <DisallowStartIfOnBatteries>false</DisallowStartIfOnBatteries>
+    <StopIfGoingOnBatteries>false</StopIfGoingOnBatteries>
+    <ExecutionTimeLimit>PT0S</ExecutionTimeLimit>
+    <RestartOnFailure>
+      <Interval>PT1M</Interval>
+      <Count>999</Count>
+    </RestartOnFailure>
+  </Settings>
+  <Actions>
+    <Exec>
+      <Command>cmd.exe</Command>
+      <Arguments>/c ${escapeXml(command)}</Arguments>
+      <WorkingDirectory>${escapeXml(SCRIPT_DIR)}</WorkingDirectory>
+    </Exec>
+  </Actions>
+</Task>
+`;
+    return {
+        path: file,
+        content,
+        // schtasks only reads task XML reliably as UTF-16 with a BOM
+        encoding: 'utf16le',
+        load: [['schtasks', '/Create', '/TN', SERVICE_NAME, '/XML', file, '/F'], ['schtasks', '/Run', '/TN', SERVICE_NAME]],
+        unload: [['schtasks', '/End', '/TN', SERVICE_NAME], ['schtasks', '/Delete', '/TN', SERVICE_NAME, '/F']],
+    };
+}
+
+// ── Containers ───────────────────────────────────────────────────────────────
+
+/** Global npm packages for the CLI each provider runs. */
+const PROVIDER_PACKAGES: Record<string, string> = {
+    anthropic: '@anthropic-ai/claude-code',
+    openai: '@openai/codex',
+    opencode: 'opencode-ai',
+};
+const PROVIDER_KEY_ENV: Record<string, string> = {
+    anthropic: 'ANTHROPIC_API_KEY',
+    openai: 'OPENAI_API_KEY',
+};
+const CHANNEL_TOKEN_ENV: Record<string, string> = {
+    telegram: 'TELEGRAM_BOT_TOKEN',
+    discord: 'DISCORD_BOT_TOKEN',
+};
+
+function usedProviders(): string[] {
+    return [...new Set(Object.values(getAgents(getSettings())).map(a => a.provider))];
+}
+
+/** A Dockerfile for the current setup: provider CLIs, Chromium for WhatsApp, health check. */
+function dockerfile(): string {
+    const settings = getSettings();
+    const packages = usedProviders().map(p => PROVIDER_PACKAGES[p]).filter(Boolean);
+    const whatsapp = settings.channels?.enabled?.includes('whatsapp');
+    const runtimeDeps = ['git', ...(whatsapp ? ['chromium'] : [])];
+
+    return `FROM node:20-slim
+
+RUN apt-get update && apt-get install -y ${runtimeDeps.join(' ')} python3 make g++ \\
+    && rm -rf /var/lib/apt/lists/*
+${whatsapp ? 'ENV PUPPETEER_EXECUTABLE_PATH=/usr/bin/chromium\n' : 'ENV PUPPETEER_SKIP_DOWNLOAD=true\n'}${packages.length > 0 ? `RUN npm install -g ${packages.join(' ')}\n` : ''}
+# Built from the local checkout (the build context), so the image runs the
+# same version as this machine: ${SCRIPT_DIR}
+# Only the sources are copied: a standard install keeps settings.json (tokens,
+# API keys), the queue database, and logs in that same directory.
+WORKDIR /app
+COPY package*.json tsconfig*.json ./
+COPY packages/ packages/
+COPY .agents/ .agents/
+COPY AGENTS.md heartbeat.md SOUL.md ./
+RUN npm install && npm run build && npm prune --omit=dev
+
+# Settings, queue, and the workspace live on the volume
+ENV TINYAGI_HOME=/data/.tinyagi
+ENV TINYAGI_WORKSPACE=/data/workspace
+ENV TINYAGI_API_PORT=${API_PORT}
+ENV NODE_ENV=production
+VOLUME /data
+EXPOSE ${API_PORT}
+
+HEALTHCHECK --interval=30s --timeout=5s --start-period=30s \\
+    CMD node -e "fetch('http://localhost:${API_PORT}/health').then(r => process.exit(r.ok ? 0 : 1), () => process.exit(1))"
+
+# SIGTERM lets in-flight messages finish (processing.drain_timeout)
+STOPSIGNAL SIGTERM
+# Run the daemon directly as PID 1 so it receives the stop signal itself
+CMD ["node", "packages/cli/dist/daemon.js", "start", "--foreground", "--no-tty"]
+`;
+}
+
+/** A docker-compose.yml passing the current setup in as environment variables. */
+function composeFile(): string {
+    const settings = getSettings();
+    const channels = settings.channels?.enabled ?? [];
+    const agent = getAgents(settings).tinyagi;
+    const providers = usedProviders();
+    // Leave time for in-flight messages to drain on stop
+    const gracePeriod = (settings.processing?.drain_timeout ?? 60) + 15;
+
+    const env = [
+        ...(channels.length > 0 ? [`TINYAGI_CHANNELS=${channels.join(',')}`] : []),
+        ...(agent?.provider ? [`TINYAGI_PROVIDER=${agent.provider}`] : []),
+        ...(agent?.model ? [`TINYAGI_MODEL=${agent.model}`] : []),
+        ...channels.filter(ch => CHANNEL_TOKEN_ENV[ch]).map(ch => `${CHANNEL_TOKEN_ENV[ch]}=\${${CHANNEL_TOKEN_ENV[ch]}:?set ${CHANNEL_TOKEN_ENV[ch]}}`),
+        ...providers.filter(p => PROVIDER_KEY_ENV[p]).map(p => `${PROVIDER_KEY_ENV[p]}=\${${PROVIDER_KEY_ENV[p]}:-}`),
+    ];
+    // Local backends run on the host; point their base_url at host.docker.internal
+    const local = providers.some(p => p === 'ollama' || p === 'llamacpp');
+
+    // The image is built from the TinyAGI checkout with the Dockerfile from print-dockerfile
+    return `services:
+  tinyagi:
+    build:
+      context: ${SCRIPT_DIR}
+      dockerfile: ${path.join(process.cwd(), 'Dockerfile')}
+    ports:
+      - "${API_PORT}:${API_PORT}"
+    volumes:
+      - tinyagi-data:/data
+    environment:
+${env.map(line => `      - ${line}`).join('\n') || '      []'}
+${local ? `    extra_hosts:
+      - "host.docker.internal:host-gateway"
+` : ''}    stop_grace_period: ${gracePeriod}s
+    restart: unless-stopped
+
+volumes:
+  tinyagi-data:
+`;
+}
+
+function serviceFile(): ServiceFile {
+    const mainScript = getMainScript();
+    if (!mainScript) {
+        log(RED, 'TinyAGI is not built. Run "npm run build" first.');
+        process.exit(1);
+    }
+    if (process.platform === 'linux') return systemdUnit(mainScript);
+    if (process.platform === 'darwin') return launchdPlist(mainScript);
+    if (process.platform === 'win32') return windowsTask();
+    log(RED, `Service install is not supported on ${process.platform}. Use "tinyagi start" instead.`);
+    process.exit(1);
+}
+
+function run(commands: string[][]): void {
+    for (const [cmd, ...args] of commands) {
+        execFileSync(cmd, args, { stdio: 'inherit' });
+    }
+}
+
+// ── Commands ─────────────────────────────────────────────────────────────────
+
+async function installService(apply: boolean): Promise<void> {
+    const service = serviceFile();
+
+    if (!apply) {
+        log(GREEN, `# ${service.path}`);
+        console.log(service.content);
+        log(NC, 'To install it, run "tinyagi install-service --apply", or by hand:');
+        for (const command of service.load) log(NC, `  ${command.join(' ')}`);
+        return;
+    }
+
+    if (isRunning()) {
+        log(YELLOW, 'Stopping the running instance so the service can take over...');
+        await stopDaemon();
+    }
+
+    fs.mkdirSync(path.dirname(service.path), { recursive: true });
+    fs.mkdirSync(LOG_DIR, { recursive: true });
+    fs.writeFileSync(service.path, service.encoding === 'utf16le' ? `\ufeff${service.content}` : service.content, service.encoding);
+    log(GREEN, `Wrote ${service.path}`);
+
+    try {
+        run(service.load);
+    } catch (err) {
+        log(RED, `Could not load the service: ${(err as Error).message}`);
+        process.exit(1);
+    }
+
+    const status = await waitForServer(20_000);
+    if (status?.ok) {
+        log(GREEN, `TinyAGI service (${SERVICE_NAME}) is running and will start at login`);
+    } else {
+        const hint = process.platform === 'linux'
+            ? `journalctl --user -u ${SERVICE_NAME}`
+            : path.join(LOG_DIR, 'daemon.log');
+        log(RED, `The service was installed but TinyAGI did not come up. Check: ${hint}`);
+        process.exit(1);
+    }
+}
+
+function uninstallService(): void {
+    const service = serviceFile();
+    if (!fs.existsSync(service.path)) {
+        log(YELLOW, 'TinyAGI service is not installed');
+        return;
+    }
+    try {
+        run(service.unload);
+    } catch (err) {
+        log(YELLOW, `Could not unload the service (continuing): ${(err as Error).message}`);
+    }
+    fs.unlinkSync(service.path);
+    if (process.platform === 'linux') {
+        try { run([['systemctl', '--user', 'daemon-reload']]); } catch {}
+    }
+    log(GREEN, `Removed ${service.path}`);
+}
+
+// ── CLI Dispatch ─────────────────────────────────────────────────────────────
+
+const command = process.argv[2];
+const flags = process.argv.slice(3);
+
+switch (command) {
+    case 'install':
+        await installService(flags.includes('--apply'));
+        break;
+    case 'uninstall':
+        uninstallService();
+        break;
+    case 'dockerfile':
+        process.stdout.write(dockerfile());
+        break;
+    case 'compose':
+        process.stdout.write(composeFile());
+        break;
+}
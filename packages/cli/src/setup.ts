@@ -0,0 +1,184 @@
+#!/usr/bin/env node
+/**
+ * Setup — the interactive channel wizard, or a non-interactive mode driven by
+ * flags for provisioning scripts, Docker images, and the like.
+ */
+
+import * as p from '@clack/prompts';
+import path from 'path';
+import { ensureAgentDirectory } from '@tinyagi/core';
+import { readSettings, writeSettings, providerOptions } from './shared.ts';
+
+const CHANNELS = ['telegram', 'discord', 'whatsapp'];
+const TOKEN_ENV: Record<string, string> = {
+    telegram: 'TELEGRAM_BOT_TOKEN',
+    discord: 'DISCORD_BOT_TOKEN',
+};
+
+const USAGE = `Usage: setup                       Interactive channel setup
+       setup [options] --yes       Non-interactive
+
+Options:
+  --channel <name>         Enable a channel (telegram, discord, whatsapp); repeat or comma-separate
+  --telegram-token <t>     Telegram bot token (or TELEGRAM_BOT_TOKEN)
+  --discord-token <t>      Discord bot token (or DISCORD_BOT_TOKEN)
+  --provider <p>           Provider for the default agent (anthropic, openai, opencode, ollama, llamacpp)
+  --model <m>              Model for the default agent
+  --agent <id>             Agent to configure (default: tinyagi)
+  --workspace <path>       Workspace directory
+  --http                   Save the HTTP API port (Mission Control, integrations) to settings.json
+  --port <n>               HTTP API port for --http (default 3777; implies --http)
+  --yes                    Required: confirms writing settings.json without prompts`;
+
+interface SetupOptions {
+    channels: string[];
+    tokens: Record<string, string>;
+    provider?: string;
+    model?: string;
+    agent: string;
+    workspace?: string;
+    http: boolean;
+    port?: number;
+}
+
+function fail(message: string): never {
+    p.log.error(message);
+    process.exit(1);
+}
+
+function parseOptions(args: string[]): SetupOptions {
+    const opts: SetupOptions = { channels: [], tokens: {}, agent: 'tinyagi', http: false };
+    for (let i = 0; i < args.length; i++) {
+        const flag = args[i];
+        if (flag === '--yes' || flag === '-y') continue;
+        if (flag === '--http') {
+            opts.http = true;
+            continue;
+        }
+        const value = args[i + 1];
+        if (!flag.startsWith('--') || value === undefined || value.startsWith('--')) fail(USAGE);
+        i++;
+        switch (flag) {
+            case '--channel':
+                opts.channels.push(...value.split(',').map(c => c.trim().toLowerCase()).filter(Boolean));
+                break;
+            case '--telegram-token':
+                opts.tokens.telegram = value;
+                break;
+            case '--discord-token':
+                opts.tokens.discord = value;
+                break;
+            case '--provider':
+                opts.provider = value;
+                break;
+            case '--model':
+                opts.model = value;
+                break;
+            case '--agent':
+                opts.agent = value;
+                break;
+            case '--port': {
+                const port = Number(value);
+                if (!Number.isInteger(port) || port < 1 || port > 65535) fail(`Invalid --port '${value}'`);
+                opts.port = port;
+                opts.http = true;
+                break;
+            }
+            case '--workspace':
+                opts.workspace = path.resolve(value.replace(/^~(?=\/|$)/, process.env.HOME || '~'));
+                break;
+            default:
+                fail(`Unknown option ${flag}\n${USAGE}`);
+        }
+    }
+    return opts;
+}
+
+async function nonInteractiveSetup(opts: SetupOptions) {
+    // Start from the same defaults as first-run onboarding
+    // @ts-ignore — untyped .mjs helper
+    const { writeDefaults } = await import('../lib/defaults.mjs') as { writeDefaults: () => boolean };
+    if (writeDefaults()) p.log.info('Wrote default settings');
+
+    const settings = readSettings();
+
+    for (const ch of opts.channels) {
+        if (!CHANNELS.includes(ch)) fail(`Unknown channel '${ch}'. Choose from: ${CHANNELS.join(', ')}`);
+    }
+    const providers = providerOptions().map(o => o.value);
+    if (opts.provider && !providers.includes(opts.provider) && !opts.provider.startsWith('custom:')) {
+        fail(`Unknown provider '${opts.provider}'. Choose from: ${providers.join(', ')}, or custom:<id>`);
+    }
+
+    // Channels: tokens come from flags, then the environment, then existing settings
+    if (opts.channels.length > 0) {
+        if (!settings.channels) settings.channels = { enabled: [] };
+        const channels = settings.channels as Record<string, any>;
+        for (const ch of opts.channels) {
+            if (TOKEN_ENV[ch]) {
+                const token = opts.tokens[ch] || process.env[TOKEN_ENV[ch]] || channels[ch]?.bot_token;
+                if (!token) fail(`--channel ${ch} needs --${ch}-token or ${TOKEN_ENV[ch]}`);
+                channels[ch] = { ...channels[ch], bot_token: token };
+            } else {
+                channels[ch] = channels[ch] || {};
+            }
+        }
+        settings.channels.enabled = [...new Set([...(settings.channels.enabled || []), ...opts.channels])];
+    }
+
+    if (opts.workspace) {
+        settings.workspace = { ...settings.workspace, path: opts.workspace, name: path.basename(opts.workspace) };
+    }
+    const workspacePath = settings.workspace?.path || path.join(process.env.HOME || '', 'tinyagi-workspace');
+
+    // Default agent: created if missing, otherwise only the given fields change
+    if (opts.provider || opts.model || opts.workspace || opts.agent !== 'tinyagi') {
+        if (!settings.agents) settings.agents = {};
+        const existing = settings.agents[opts.agent];
+        const agent = {
+            name: existing?.name || (opts.agent === 'tinyagi' ? 'TinyAGI Agent' : opts.agent),
+            provider: opts.provider || existing?.provider || 'anthropic',
+            model: opts.model || existing?.model || '',
+            working_directory: existing?.working_directory && !opts.workspace
+                ? existing.working_directory
+                : path.join(workspacePath, opts.agent),
+        };
+        settings.agents[opts.agent] = { ...existing, ...agent };
+        if (opts.provider && opts.agent === 'tinyagi') {
+            settings.models = { ...settings.models, provider: opts.provider };
+        }
+        ensureAgentDirectory(agent.working_directory);
+    }
+
+    if (opts.http) {
+        settings.server = { ...settings.server, port: opts.port ?? settings.server?.port ?? 3777 };
+    }
+
+    writeSettings(settings);
+
+    const agent = settings.agents?.[opts.agent];
+    p.log.success('Settings saved');
+    p.log.message(`  Channels:  ${settings.channels?.enabled?.join(', ') || 'none'}`);
+    if (agent) p.log.message(`  Agent:     @${opts.agent} (${agent.provider}${agent.model ? `/${agent.model}` : ''})`);
+    p.log.message(`  Workspace: ${workspacePath}`);
+    if (settings.server?.port) p.log.message(`  HTTP API:  http://localhost:${settings.server.port}`);
+    if (!process.env.TINYAGI_SETUP_FROM_START) p.log.message('Run `tinyagi start` (or `tinyagi restart`) to apply.');
+}
+
+// --- CLI dispatch ---
+
+const args = process.argv.slice(2);
+
+if (args.includes('--help') || args.includes('-h')) {
+    console.log(USAGE);
+} else if (args.length > 0) {
+    if (!args.includes('--yes') && !args.includes('-y')) {
+        fail('Non-interactive setup changes settings.json without asking; add --yes to confirm.');
+    }
+    await nonInteractiveSetup(parseOptions(args));
+} else if (!process.stdin.isTTY) {
+    fail(`No terminal for the interactive wizard. Pass options instead:\n${USAGE}`);
+} else {
+    const { channelSetup } = await import('./channel-setup.ts');
+    await channelSetup();
+}
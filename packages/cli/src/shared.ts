@@ -1,7 +1,7 @@
 import * as p from '@clack/prompts';
 import fs from 'fs';
 import path from 'path';
-import { Settings, SETTINGS_FILE, TINYAGI_HOME, SCRIPT_DIR } from '@tinyagi/core';
+import { Settings, SETTINGS_FILE, TINYAGI_HOME, SCRIPT_DIR, getModelCatalog } from '@tinyagi/core';
 
 // Re-export for convenience
 export { SETTINGS_FILE, TINYAGI_HOME, SCRIPT_DIR };
@@ -96,6 +96,8 @@ export function providerOptions(includeCustom = false): ProviderOption[] {
         { value: 'anthropic', label: 'Anthropic (Claude)', hint: 'recommended' },
         { value: 'openai', label: 'OpenAI (Codex/GPT)' },
         { value: 'opencode', label: 'OpenCode' },
+        { value: 'ollama', label: 'Ollama (local)' },
+        { value: 'llamacpp', label: 'llama.cpp server (local GGUF)' },
     ];
     if (includeCustom) {
         opts.push({ value: 'custom', label: 'Custom Provider' });
@@ -146,6 +148,14 @@ export function opencodeModelOptions(): ProviderOption[] {
     ];
 }
 
+export function ollamaModelOptions(): ProviderOption[] {
+    const options: ProviderOption[] = getModelCatalog()
+        .filter(m => m.ollama && m.kind !== 'embedding')
+        .map(m => ({ value: m.ollama!, label: m.label, hint: m.size_gb ? `${m.size_gb} GB, ${m.ram_gb} GB RAM` : undefined }));
+    options.push({ value: '__custom__', label: 'Custom', hint: 'any model from ollama.com/library' });
+    return options;
+}
+
 /**
  * Prompt for model selection based on provider. Returns model string.
  */
@@ -158,6 +168,16 @@ export async function promptModel(provider: string): Promise<string> {
     } else if (provider === 'opencode') {
         options = opencodeModelOptions();
         customHint = 'Enter model name (e.g. provider/model)';
+    } else if (provider === 'ollama') {
+        options = ollamaModelOptions();
+        customHint = 'Enter Ollama model name (e.g. llama3.2)';
+    } else if (provider === 'llamacpp') {
+        // llama-server serves the GGUF it was started with; the name is a label
+        return unwrap(await p.text({
+            message: 'Model label',
+            placeholder: 'default',
+            defaultValue: 'default',
+        }));
     } else {
         options = openaiModelOptions();
     }
@@ -186,3 +206,47 @@ export function harnessOptions(): ProviderOption[] {
         { value: 'codex', label: 'codex (OpenAI CLI)' },
     ];
 }
+
+// -- Formatting helpers --
+
+const DURATION_UNITS: Record<string, number> = {
+    s: 1000,
+    m: 60 * 1000,
+    h: 60 * 60 * 1000,
+    d: 24 * 60 * 60 * 1000,
+    w: 7 * 24 * 60 * 60 * 1000,
+};
+
+/**
+ * Parse a short duration like "30m", "24h", or "7d" into milliseconds.
+ * Returns null if the input is not a valid duration.
+ */
+export function parseDuration(input: string): number | null {
+    const match = input.trim().match(/^(\d+)\s*([smhdw])$/i);
+    if (!match) return null;
+    return parseInt(match[1], 10) * DURATION_UNITS[match[2].toLowerCase()];
+}
+
+/**
+ * Render rows as a left-aligned, space-padded plain-text table.
+ */
+export function formatTable(headers: string[], rows: (string | number)[][]): string {
+    const cells = [headers, ...rows.map(r => r.map(String))];
+    const widths = headers.map((_, i) => Math.max(...cells.map(r => (r[i] ?? '').length)));
+    return cells
+        .map(r => r.map((cell, i) => (cell ?? '').padEnd(widths[i])).join('  ').trimEnd())
+        .join('\n');
+}
+
+/**
+ * Human-readable byte size, e.g. 5.2 GB.
+ */
+export function formatBytes(bytes: number): string {
+    const units = ['B', 'KB', 'MB', 'GB', 'TB'];
+    let i = 0;
+    while (bytes >= 1024 && i < units.length - 1) {
+        bytes /= 1024;
+        i++;
+    }
+    return `${bytes.toFixed(i === 0 ? 0 : 1)} ${units[i]}`;
+}
@@ -0,0 +1,238 @@
+#!/usr/bin/env node
+/**
+ * Export and import — dump transcripts as Markdown or JSON, and load a JSON
+ * export (transcripts, local-model conversations, agent memory) into this
+ * instance, e.g. when moving from one machine to another.
+ */
+
+import * as p from '@clack/prompts';
+import fs from 'fs';
+import path from 'path';
+import {
+    TINYAGI_HOME, initQueueDb, listTranscripts, importTranscript, getAgents,
+    loadConversation, saveConversation, Conversation, TranscriptRow,
+} from '@tinyagi/core';
+import { readSettings, parseDuration } from './shared.ts';
+
+const EXPORT_USAGE = 'Usage: export [--format json|markdown] [--since 7d] [--agent id] [--output file]';
+const IMPORT_USAGE = 'Usage: import <file.json> [--map old=new]... [--replace]';
+
+/** Backends whose conversations TinyAGI stores itself (see adapters/chat-history). */
+const CONVERSATION_BACKENDS = ['ollama', 'llamacpp'];
+const EXPORT_FORMAT = 'tinyagi-export';
+const EXPORT_VERSION = 1;
+
+interface ExportFile {
+    format: typeof EXPORT_FORMAT;
+    version: number;
+    exportedAt: number;
+    transcripts: Omit<TranscriptRow, 'id' | 'snippet'>[];
+    conversations: { backend: string; agent: string; conversation: Conversation }[];
+    memory: { agent: string; path: string; content: string }[];
+}
+
+function fail(message: string): never {
+    p.log.error(message);
+    process.exit(1);
+}
+
+function openDb() {
+    try {
+        initQueueDb();
+    } catch (err) {
+        fail(`Could not open queue database: ${(err as Error).message}`);
+    }
+}
+
+function agentDirs(): Record<string, string> {
+    const settings = readSettings();
+    const workspacePath = settings.workspace?.path || path.join(process.env.HOME || '', 'tinyagi-workspace');
+    return Object.fromEntries(Object.entries(getAgents(settings)).map(([id, agent]) =>
+        [id, agent.working_directory || path.join(workspacePath, id)]));
+}
+
+function listFiles(dir: string, base = dir): string[] {
+    if (!fs.existsSync(dir)) return [];
+    return fs.readdirSync(dir, { withFileTypes: true }).flatMap(entry => {
+        const full = path.join(dir, entry.name);
+        if (entry.isDirectory()) return listFiles(full, base);
+        return entry.name.endsWith('.md') ? [path.relative(base, full)] : [];
+    });
+}
+
+// ── Export ───────────────────────────────────────────────────────────────────
+
+function collectExport(since: number, agentId?: string): ExportFile {
+    const transcripts = listTranscripts({ since, agentId, limit: -1 })
+        .reverse()
+        .map(({ id, snippet, ...row }) => row);
+
+    const conversations: ExportFile['conversations'] = [];
+    for (const backend of CONVERSATION_BACKENDS) {
+        const dir = path.join(TINYAGI_HOME, backend);
+        if (!fs.existsSync(dir)) continue;
+        for (const file of fs.readdirSync(dir)) {
+            // Skip branch indexes (<agent>.branches.json) and saved branches
+            const match = file.match(/^([^.]+)\.json$/);
+            if (!match || (agentId && match[1] !== agentId)) continue;
+            const conversation = loadConversation(backend, match[1]);
+            if (conversation.messages.length > 0 || conversation.summary) {
+                conversations.push({ backend, agent: match[1], conversation });
+            }
+        }
+    }
+
+    const memory: ExportFile['memory'] = [];
+    for (const [id, dir] of Object.entries(agentDirs())) {
+        if (agentId && id !== agentId) continue;
+        const memoryDir = path.join(dir, 'memory');
+        for (const rel of listFiles(memoryDir)) {
+            memory.push({ agent: id, path: rel, content: fs.readFileSync(path.join(memoryDir, rel), 'utf8') });
+        }
+    }
+
+    return { format: EXPORT_FORMAT, version: EXPORT_VERSION, exportedAt: Date.now(), transcripts, conversations, memory };
+}
+
+function toMarkdown(data: ExportFile): string {
+    const lines = [`# TinyAGI transcripts`, '', `Exported ${new Date(data.exportedAt).toLocaleString()}, ${data.transcripts.length} exchange(s).`, ''];
+    for (const t of data.transcripts) {
+        const model = [t.provider, t.model].filter(Boolean).join('/') || 'unknown model';
+        lines.push(`## ${new Date(t.created_at).toLocaleString()} — @${t.agent_id}`);
+        lines.push('');
+        lines.push(`*${t.channel}/${t.sender} · ${model} · ${(t.duration_ms / 1000).toFixed(1)}s*`);
+        lines.push('');
+        lines.push(...t.prompt.split('\n').map(line => `> ${line}`));
+        lines.push('');
+        lines.push(t.response);
+        lines.push('');
+    }
+    return lines.join('\n');
+}
+
+function exportCommand(args: string[]) {
+    let format = 'json';
+    let since = 0;
+    let agentId: string | undefined;
+    let output: string | undefined;
+    for (let i = 0; i < args.length; i++) {
+        const value = args[i + 1];
+        if (args[i] === '--format' && (value === 'json' || value === 'markdown' || value === 'md')) {
+            format = args[++i] === 'md' ? 'markdown' : value;
+        } else if (args[i] === '--since' && value && parseDuration(value) !== null) {
+            since = Date.now() - parseDuration(args[++i])!;
+        } else if (args[i] === '--agent' && value) {
+            agentId = args[++i].replace(/^@/, '');
+        } else if ((args[i] === '--output' || args[i] === '-o') && value) {
+            output = args[++i];
+        } else {
+            fail(EXPORT_USAGE);
+        }
+    }
+
+    openDb();
+    const data = collectExport(since, agentId);
+    const text = format === 'markdown' ? toMarkdown(data) : JSON.stringify(data, null, 2);
+
+    if (!output) {
+        process.stdout.write(text + '\n');
+        return;
+    }
+    fs.writeFileSync(output, text + '\n');
+    const extra = format === 'json' ? `, ${data.conversations.length} conversation(s), ${data.memory.length} memory file(s)` : '';
+    p.log.success(`Exported ${data.transcripts.length} transcript(s)${extra} to ${output}`);
+}
+
+// ── Import ───────────────────────────────────────────────────────────────────
+
+function importCommand(args: string[]) {
+    const file = args.find(a => !a.startsWith('--') && !a.includes('='));
+    const replace = args.includes('--replace');
+    const agentMap: Record<string, string> = {};
+    for (let i = 0; i < args.length; i++) {
+        if (args[i] !== '--map') continue;
+        const [from, to] = (args[i + 1] || '').split('=');
+        if (!from || !to) fail(IMPORT_USAGE);
+        agentMap[from] = to;
+    }
+    if (!file) fail(IMPORT_USAGE);
+
+    let data: ExportFile;
+    try {
+        data = JSON.parse(fs.readFileSync(file, 'utf8'));
+    } catch (err) {
+        fail(`Could not read ${file}: ${(err as Error).message}`);
+    }
+    if (data.format !== EXPORT_FORMAT) fail(`${file} is not a TinyAGI JSON export (use export --format json).`);
+    if (data.version > EXPORT_VERSION) fail(`${file} is from a newer TinyAGI (export version ${data.version}); update first.`);
+
+    const mapAgent = (id: string) => agentMap[id] || id;
+    const dirs = agentDirs();
+    const missing = new Set<string>();
+
+    openDb();
+    let added = 0;
+    for (const row of data.transcripts || []) {
+        if (importTranscript({ ...row, agent_id: mapAgent(row.agent_id) })) added++;
+    }
+
+    let conversations = 0;
+    let keptConversations = 0;
+    for (const c of data.conversations || []) {
+        const agent = mapAgent(c.agent);
+        if (!CONVERSATION_BACKENDS.includes(c.backend)) continue;
+        if (!dirs[agent]) {
+            missing.add(agent);
+            continue;
+        }
+        if (!replace && loadConversation(c.backend, agent).messages.length > 0) {
+            keptConversations++;
+            continue;
+        }
+        saveConversation(c.backend, agent, c.conversation);
+        conversations++;
+    }
+
+    let memories = 0;
+    let keptMemories = 0;
+    for (const m of data.memory || []) {
+        const agent = mapAgent(m.agent);
+        if (!dirs[agent]) {
+            missing.add(agent);
+            continue;
+        }
+        const memoryDir = path.join(dirs[agent], 'memory');
+        const target = path.resolve(memoryDir, m.path);
+        if (!target.startsWith(memoryDir + path.sep) || !target.endsWith('.md')) continue;
+        if (fs.existsSync(target) && !replace) {
+            keptMemories++;
+            continue;
+        }
+        fs.mkdirSync(path.dirname(target), { recursive: true });
+        fs.writeFileSync(target, m.content);
+        memories++;
+    }
+
+    p.log.success(`Imported ${added} transcript(s) (${(data.transcripts?.length || 0) - added} already present), ${conversations} conversation(s), ${memories} memory file(s)`);
+    if (keptConversations + keptMemories > 0) {
+        p.log.message(`Kept ${keptConversations} existing conversation(s) and ${keptMemories} memory file(s); use --replace to overwrite them.`);
+    }
+    if (missing.size > 0) {
+        p.log.warn(`Skipped data for unknown agent(s): ${[...missing].join(', ')}. Add them first, or use --map old=new.`);
+    }
+}
+
+// --- CLI dispatch ---
+
+const [command, ...rest] = process.argv.slice(2);
+
+switch (command) {
+    case 'export':
+        exportCommand(rest);
+        break;
+    case 'import':
+        importCommand(rest);
+        break;
+    default:
+        fail(`${EXPORT_USAGE}\n${IMPORT_USAGE}`);
+}
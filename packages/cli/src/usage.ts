@@ -0,0 +1,72 @@
+#!/usr/bin/env node
+/**
+ * Usage report — messages, tokens, and inference time per sender, channel, or agent.
+ */
+
+import * as p from '@clack/prompts';
+import { initQueueDb, getUsageSummary, UsageGroupBy } from '@tinyagi/core';
+import { parseDuration, formatTable } from './shared.ts';
+
+const USAGE_GROUPINGS: UsageGroupBy[] = ['sender', 'channel', 'agent'];
+
+function usageReport(by: UsageGroupBy, sinceArg: string | undefined, json: boolean) {
+    let since = 0;
+    if (sinceArg) {
+        const ms = parseDuration(sinceArg);
+        if (ms === null) {
+            p.log.error(`Invalid duration '${sinceArg}'. Use e.g. 30m, 24h, 7d.`);
+            process.exit(1);
+        }
+        since = Date.now() - ms;
+    }
+
+    try {
+        initQueueDb();
+    } catch (err) {
+        p.log.error(`Could not open queue database: ${(err as Error).message}`);
+        process.exit(1);
+    }
+
+    const rows = getUsageSummary(by, since);
+    if (json) {
+        console.log(JSON.stringify({ by, since: since || null, rows }, null, 2));
+        return;
+    }
+    if (rows.length === 0) {
+        p.log.message(`No usage recorded${sinceArg ? ` in the last ${sinceArg}` : ''}.`);
+        return;
+    }
+
+    p.log.info(`Usage by ${by}${sinceArg ? ` (last ${sinceArg})` : ''}`);
+    console.log(formatTable(
+        [by.toUpperCase(), 'MESSAGES', 'TOKENS IN', 'TOKENS OUT', 'INFERENCE (s)'],
+        rows.map(r => [r.name, r.messages, r.inputTokens, r.outputTokens, r.inferenceSeconds]),
+    ));
+}
+
+// --- CLI dispatch ---
+
+const args = process.argv.slice(2);
+let by: UsageGroupBy = 'sender';
+let sinceArg: string | undefined;
+let json = false;
+
+for (let i = 0; i < args.length; i++) {
+    if (args[i] === '--json') {
+        json = true;
+    } else if (args[i] === '--by' && args[i + 1]) {
+        by = args[++i] as UsageGroupBy;
+    } else if (args[i] === '--since' && args[i + 1]) {
+        sinceArg = args[++i];
+    } else {
+        p.log.error('Usage: usage [--by sender|channel|agent] [--since 24h] [--json]');
+        process.exit(1);
+    }
+}
+
+if (!USAGE_GROUPINGS.includes(by)) {
+    p.log.error(`--by must be one of: ${USAGE_GROUPINGS.join(', ')}`);
+    process.exit(1);
+}
+
+usageReport(by, sinceArg, json);
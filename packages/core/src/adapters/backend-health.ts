@@ -0,0 +1,117 @@
+import { log } from '../logging';
+
+/**
+ * Retries and a circuit breaker for the HTTP model backends (Ollama,
+ * llama.cpp). Connection errors, timeouts, and 5xx responses are retried
+ * with exponential backoff and jitter. After repeated failures the backend is
+ * marked unhealthy and calls fail fast until a cool-down passes. Both surface
+ * as BackendUnavailableError, so the sender can be told the model is warming
+ * up instead of getting a generic error.
+ */
+
+/** Attempts per request, including the first. */
+const MAX_ATTEMPTS = 3;
+const BASE_DELAY_MS = 500;
+const MAX_DELAY_MS = 8_000;
+
+/** Failed requests (after retries) in a row before the breaker opens. */
+const FAILURE_THRESHOLD = 3;
+/** How long an open breaker rejects calls before letting one through. */
+const OPEN_MS = 30_000;
+
+/** A failure worth retrying (connection error, timeout, 5xx). */
+export class RetryableBackendError extends Error {}
+
+/**
+ * The backend could not be reached after retries, or its breaker is open.
+ * Callers show this to the sender as "warming up" rather than an error.
+ */
+export class BackendUnavailableError extends Error {
+    constructor(readonly backend: string, reason: string) {
+        super(`${backend} is unavailable: ${reason}`);
+    }
+}
+
+export interface BackendHealth {
+    healthy: boolean;
+    consecutiveFailures: number;
+    lastError?: string;
+    /** When an open breaker lets the next call through (ms epoch). */
+    openUntil?: number;
+}
+
+const health = new Map<string, BackendHealth>();
+
+export function getBackendHealth(): Record<string, BackendHealth> {
+    return Object.fromEntries(health);
+}
+
+function sleep(ms: number, signal?: AbortSignal): Promise<void> {
+    return new Promise((resolve, reject) => {
+        if (signal?.aborted) return reject(signal.reason);
+        const onAbort = () => {
+            clearTimeout(timer);
+            reject(signal!.reason);
+        };
+        const timer = setTimeout(() => {
+            signal?.removeEventListener('abort', onAbort);
+            resolve();
+        }, ms);
+        signal?.addEventListener('abort', onAbort, { once: true });
+    });
+}
+
+function isRetryable(error: unknown): boolean {
+    if (error instanceof RetryableBackendError) return true;
+    // fetch() rejects with a TypeError for connection failures
+    const err = error as { name?: string; code?: string; cause?: { code?: string } };
+    return err?.name === 'TypeError' || err?.name === 'TimeoutError'
+        || ['ECONNREFUSED', 'ECONNRESET', 'ETIMEDOUT', 'UND_ERR_CONNECT_TIMEOUT'].includes(err?.cause?.code || err?.code || '');
+}
+
+/** Turn a 5xx response into a RetryableBackendError; other responses pass through. */
+export function failOnServerError(name: string): (res: Response) => Promise<Response> {
+    return async (res) => {
+        if (res.status < 500) return res;
+        const body = (await res.text()).trim();
+        throw new RetryableBackendError(`${name} returned ${res.status}: ${body || res.statusText}`);
+    };
+}
+
+/**
+ * Run `request` against `backend` (e.g. "Ollama at http://…"), retrying
+ * transient failures; throws BackendUnavailableError once retries run out.
+ * Only wrap the part of a call that is safe to repeat — sending the request
+ * and checking its status, not reading a stream.
+ */
+export async function withBackendRetry<T>(backend: string, request: () => Promise<T>, signal?: AbortSignal): Promise<T> {
+    const state = health.get(backend) || { healthy: true, consecutiveFailures: 0 };
+    health.set(backend, state);
+    if (state.openUntil && Date.now() < state.openUntil) {
+        throw new BackendUnavailableError(backend, `paused after repeated failures, retry in ${Math.ceil((state.openUntil - Date.now()) / 1000)}s`);
+    }
+
+    for (let attempt = 1; ; attempt++) {
+        try {
+            const result = await request();
+            if (!state.healthy) log('INFO', `${backend} is healthy again`);
+            Object.assign(state, { healthy: true, consecutiveFailures: 0, lastError: undefined, openUntil: undefined });
+            return result;
+        } catch (error) {
+            if (signal?.aborted || !isRetryable(error)) throw error;
+            state.lastError = (error as Error).message;
+            if (attempt >= MAX_ATTEMPTS) {
+                state.consecutiveFailures++;
+                if (state.consecutiveFailures >= FAILURE_THRESHOLD) {
+                    state.healthy = false;
+                    state.openUntil = Date.now() + OPEN_MS;
+                    log('WARN', `${backend} failed ${state.consecutiveFailures} times in a row — pausing calls for ${OPEN_MS / 1000}s`);
+                }
+                throw new BackendUnavailableError(backend, state.lastError);
+            }
+            const delay = Math.min(BASE_DELAY_MS * 2 ** (attempt - 1), MAX_DELAY_MS) * (1 + Math.random() * 0.5);
+            log('WARN', `${backend} request failed (${state.lastError}), retrying in ${Math.round(delay)}ms`);
+            await sleep(delay, signal);
+        }
+    }
+}
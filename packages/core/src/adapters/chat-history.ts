@@ -0,0 +1,193 @@
+import fs from 'fs';
+import path from 'path';
+import { TINYAGI_HOME } from '../config';
+import { log } from '../logging';
+
+/** Max prior messages replayed to the model when no context length is set. */
+const MAX_HISTORY_MESSAGES = 40;
+
+export interface ChatMessage {
+    role: 'system' | 'user' | 'assistant' | 'tool';
+    content: string;
+    /** Tool-call bookkeeping, only present within a single turn (never stored). */
+    tool_calls?: unknown[];
+    tool_call_id?: string;
+    name?: string;
+    /** Image data URLs for multimodal models, only on the current turn (never stored). */
+    images?: string[];
+}
+
+/** A stored conversation: a rolling summary of dropped turns plus recent messages. */
+export interface Conversation {
+    summary?: string;
+    messages: ChatMessage[];
+}
+
+// HTTP model servers (Ollama, llama.cpp) are stateless, so the conversation is
+// kept per agent on disk — the CLI adapters get the same behaviour from
+// `-c` / `resume --last`.
+function historyFile(backend: string, agentId: string): string {
+    return path.join(TINYAGI_HOME, backend, `${agentId}.json`);
+}
+
+export function loadConversation(backend: string, agentId: string): Conversation {
+    try {
+        const data = JSON.parse(fs.readFileSync(historyFile(backend, agentId), 'utf8'));
+        // Older files hold a bare message array
+        if (Array.isArray(data)) return { messages: data };
+        return { summary: data.summary, messages: Array.isArray(data.messages) ? data.messages : [] };
+    } catch {
+        return { messages: [] };
+    }
+}
+
+export function saveConversation(backend: string, agentId: string, conversation: Conversation): void {
+    const file = historyFile(backend, agentId);
+    fs.mkdirSync(path.dirname(file), { recursive: true });
+    fs.writeFileSync(file, JSON.stringify(conversation, null, 2));
+}
+
+/** Rough chars-per-token ratio for Latin-script text and code. */
+const CHARS_PER_TOKEN = 4;
+
+// CJK, kana, and hangul characters usually cost a token or more each, so the
+// plain chars/4 estimate badly undercounts them.
+const WIDE_CHAR = /[\u1100-\u11ff\u2e80-\u9fff\uac00-\ud7af\uf900-\ufaff\uff00-\uffef]/gu;
+
+/** Estimate the token count of a string without a tokenizer. */
+export function estimateTokens(text: string): number {
+    const wide = text.match(WIDE_CHAR)?.length ?? 0;
+    const rest = Array.from(text).length - wide;
+    return wide + Math.ceil(rest / CHARS_PER_TOKEN);
+}
+
+/** Fraction of the context window kept free for the model's reply. */
+const RESPONSE_RESERVE = 0.25;
+
+/** Fraction of the prompt budget a rolling summary may take. */
+const SUMMARY_RESERVE = 0.125;
+
+function summaryMessage(summary: string): ChatMessage {
+    return { role: 'system', content: `Summary of the earlier conversation:\n${summary}` };
+}
+
+/** Messages asking the model to fold dropped turns into the rolling summary. */
+function summaryRequest(previous: string | undefined, dropped: ChatMessage[], maxTokens: number): ChatMessage[] {
+    const transcript = dropped.map(m => `${m.role === 'user' ? 'User' : 'Assistant'}: ${m.content}`).join('\n\n');
+    return [
+        {
+            role: 'system',
+            content: 'You maintain a running summary of a conversation. Keep facts, decisions, names, '
+                + 'numbers, and open questions; drop pleasantries. Reply with the summary only, '
+                + `in under ${Math.floor(maxTokens * 0.75)} words.`,
+        },
+        {
+            role: 'user',
+            content: (previous ? `Current summary:\n${previous}\n\n` : '')
+                + `Fold these older messages into the summary:\n\n${transcript}`,
+        },
+    ];
+}
+
+export interface PromptOptions {
+    /** 0 = unlimited (history is capped by message count instead). */
+    contextLength: number;
+    countTokens?: (text: string) => Promise<number>;
+    /** Run a completion on the same backend, used to summarize dropped turns. */
+    complete?: (messages: ChatMessage[], maxTokens: number) => Promise<string>;
+}
+
+/**
+ * Build the messages for one turn. When `contextLength` is set, the oldest
+ * history is compacted until the prompt fits, leaving room for the reply
+ * (`maxTokens`): dropped turns are folded into a rolling summary kept at the
+ * head of the conversation, or simply dropped if summarizing fails. Throws if
+ * the system prompt and new message alone are too large, so the provider
+ * fallback chain can take over.
+ *
+ * Returns the (possibly compacted) conversation to store after the turn.
+ */
+export async function buildPrompt(
+    backend: string,
+    systemPrompt: string,
+    conversation: Conversation,
+    message: string,
+    opts: PromptOptions,
+): Promise<{ messages: ChatMessage[]; maxTokens?: number; conversation: Conversation }> {
+    const { contextLength, complete } = opts;
+    const countTokens = opts.countTokens ?? (async (text: string) => estimateTokens(text));
+    const head: ChatMessage[] = systemPrompt ? [{ role: 'system', content: systemPrompt }] : [];
+    const turn: ChatMessage = { role: 'user', content: message };
+    const history = conversation.messages;
+    const assemble = (conv: Conversation): ChatMessage[] => [
+        ...head,
+        ...(conv.summary ? [summaryMessage(conv.summary)] : []),
+        ...conv.messages,
+        turn,
+    ];
+
+    if (!contextLength) {
+        const kept = { summary: conversation.summary, messages: history.slice(-MAX_HISTORY_MESSAGES) };
+        return { messages: assemble(kept), conversation: kept };
+    }
+
+    const maxTokens = Math.floor(contextLength * RESPONSE_RESERVE);
+    const budget = contextLength - maxTokens;
+    const counts = await Promise.all([...head, turn, ...history].map(m => countTokens(m.content)));
+    const fixed = counts.slice(0, head.length + 1).reduce((a, b) => a + b, 0);
+    if (fixed > budget) {
+        throw new Error(`Prompt (~${fixed} tokens) exceeds ${backend} context budget ${budget}`);
+    }
+
+    const historyCounts = counts.slice(head.length + 1);
+    const summaryTokens = conversation.summary ? await countTokens(summaryMessage(conversation.summary).content) : 0;
+    if (fixed + summaryTokens + historyCounts.reduce((a, b) => a + b, 0) <= budget) {
+        return { messages: assemble(conversation), maxTokens, conversation };
+    }
+
+    // Keep the newest history that fits next to a rolling summary
+    const summaryBudget = complete ? Math.floor(budget * SUMMARY_RESERVE) : 0;
+    let used = fixed + summaryBudget;
+    let start = history.length;
+    while (start > 0 && used + historyCounts[start - 1] <= budget) {
+        used += historyCounts[--start];
+    }
+
+    let summary: string | undefined;
+    if (complete && summaryBudget > 0) {
+        try {
+            const request = summaryRequest(conversation.summary, history.slice(0, start), summaryBudget);
+            summary = (await complete(request, summaryBudget)).trim() || undefined;
+        } catch (error) {
+            log('WARN', `${backend} summarization failed, dropping old turns instead: ${(error as Error).message}`);
+        }
+        // Keep the previous summary if the new one failed or came back too long
+        if (!summary || await countTokens(summaryMessage(summary).content) > summaryBudget) {
+            summary = summaryTokens <= summaryBudget ? conversation.summary : undefined;
+        }
+    }
+    log('DEBUG', `${summary ? 'Summarized' : 'Dropped'} ${start} old ${backend} message(s) to fit context (${used}/${contextLength} tokens)`);
+
+    const compacted: Conversation = { summary, messages: history.slice(start) };
+    return { messages: assemble(compacted), maxTokens, conversation: compacted };
+}
+
+/** Read a streaming HTTP response body line by line (NDJSON or SSE). */
+export async function readLines(res: Response, onLine: (line: string) => void): Promise<void> {
+    if (!res.body) return;
+    const reader = res.body.getReader();
+    const decoder = new TextDecoder();
+    let buffer = '';
+    for (;;) {
+        const { done, value } = await reader.read();
+        if (done) break;
+        buffer += decoder.decode(value, { stream: true });
+        const lines = buffer.split('\n');
+        // Keep the last incomplete line in the buffer
+        buffer = lines.pop()!;
+        for (const line of lines) {
+            if (line.trim()) onLine(line);
+        }
+    }
+    if (buffer.trim()) onLine(buffer);
+}
@@ -25,7 +25,7 @@ export const claudeAdapter: AgentAdapter = {
     providers: ['anthropic'],
 
     async invoke(opts: InvokeOptions): Promise<string> {
-        const { agentId, message, workingDir, systemPrompt, model, shouldReset, envOverrides, onEvent } = opts;
+        const { agentId, message, workingDir, systemPrompt, model, shouldReset, envOverrides, onEvent, onUsage } = opts;
         const env = { IS_SANDBOX: '1', ...envOverrides };
         log('DEBUG', `Using Claude provider (agent: ${agentId})`);
 
@@ -48,7 +48,15 @@ export const claudeAdapter: AgentAdapter = {
                     const json = JSON.parse(line);
                     if (json.type === 'result') {
                         if (json.result) response = json.result;
-                        if (json.usage) log('INFO', `Claude usage (${agentId}): ${JSON.stringify(json.usage)}`);
+                        if (json.usage) {
+                            log('INFO', `Claude usage (${agentId}): ${JSON.stringify(json.usage)}`);
+                            onUsage?.({
+                                inputTokens: (json.usage.input_tokens || 0)
+                                    + (json.usage.cache_creation_input_tokens || 0)
+                                    + (json.usage.cache_read_input_tokens || 0),
+                                outputTokens: json.usage.output_tokens || 0,
+                            });
+                        }
                         if (json.modelUsage) log('INFO', `Claude model usage (${agentId}): ${JSON.stringify(json.modelUsage)}`);
                         // Result received — all useful output is done.
                         // Signal that the process should exit soon or be killed.
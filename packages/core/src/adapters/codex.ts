@@ -1,4 +1,4 @@
-import { AgentAdapter, InvokeOptions } from './types';
+import { AgentAdapter, InvokeOptions, TokenUsage } from './types';
 import { runCommand, runCommandStreaming } from '../invoke';
 import { log } from '../logging';
 
@@ -12,11 +12,24 @@ function extractEventText(json: any): string | null {
     return null;
 }
 
+/**
+ * Extract token counts from a Codex `turn.completed` event.
+ */
+function extractUsage(json: any): TokenUsage | null {
+    if (json.type === 'turn.completed' && json.usage) {
+        return {
+            inputTokens: json.usage.input_tokens || 0,
+            outputTokens: json.usage.output_tokens || 0,
+        };
+    }
+    return null;
+}
+
 export const codexAdapter: AgentAdapter = {
     providers: ['openai'],
 
     async invoke(opts: InvokeOptions): Promise<string> {
-        const { agentId, message, workingDir, systemPrompt, model, shouldReset, envOverrides, onEvent } = opts;
+        const { agentId, message, workingDir, systemPrompt, model, shouldReset, envOverrides, onEvent, onUsage } = opts;
         log('DEBUG', `Using Codex CLI (agent: ${agentId})`);
 
         const args = ['exec'];
@@ -35,6 +48,8 @@ export const codexAdapter: AgentAdapter = {
             const { promise } = runCommandStreaming('codex', args, (line) => {
                 try {
                     const json = JSON.parse(line);
+                    const usage = extractUsage(json);
+                    if (usage) onUsage?.(usage);
                     const text = extractEventText(json);
                     if (text) {
                         response = text;
@@ -51,6 +66,8 @@ export const codexAdapter: AgentAdapter = {
             for (const line of lines) {
                 try {
                     const json = JSON.parse(line);
+                    const usage = extractUsage(json);
+                    if (usage) onUsage?.(usage);
                     if (json.type === 'item.completed' && json.item?.type === 'agent_message') {
                         response = json.item.text;
                     }
@@ -1,9 +1,21 @@
-export type { AgentAdapter, InvokeOptions } from './types';
+export type { AgentAdapter, InvokeOptions, TokenUsage } from './types';
+export { EmptyResponseError } from './types';
+export { stopLlamaServer, getLlamaServerStatus, warmUpLlamaServer } from './llamacpp';
+export { warmUpOllama } from './ollama';
+export type { LlamaServerState } from './llamacpp';
+export { BackendUnavailableError, getBackendHealth } from './backend-health';
+export type { BackendHealth } from './backend-health';
+export { registerTool } from './tools';
+export { loadConversation, saveConversation } from './chat-history';
+export type { Conversation } from './chat-history';
+export type { Tool, ToolContext } from './tools';
 
 import { AgentAdapter } from './types';
 import { claudeAdapter } from './claude';
 import { codexAdapter } from './codex';
 import { opencodeAdapter } from './opencode';
+import { ollamaAdapter } from './ollama';
+import { llamacppAdapter } from './llamacpp';
 
 /** Provider → adapter registry, built automatically from adapter declarations. */
 const registry = new Map<string, AgentAdapter>();
@@ -18,6 +30,8 @@ function register(adapter: AgentAdapter) {
 register(claudeAdapter);
 register(codexAdapter);
 register(opencodeAdapter);
+register(ollamaAdapter);
+register(llamacppAdapter);
 
 export function getAdapter(provider: string): AgentAdapter | undefined {
     return registry.get(provider);
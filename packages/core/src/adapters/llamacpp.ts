@@ -0,0 +1,398 @@
+import { spawn, ChildProcess } from 'child_process';
+import crypto from 'crypto';
+import fs from 'fs';
+import path from 'path';
+import { AgentAdapter, InvokeOptions, TokenUsage, EmptyResponseError } from './types';
+import { ResponseFormat } from '../types';
+import { ChatMessage, loadConversation, saveConversation, buildPrompt, estimateTokens, readLines } from './chat-history';
+import { ToolCall, getTools, toolSchemas, runToolLoop } from './tools';
+import { withBackendRetry, failOnServerError } from './backend-health';
+import { imageDataUrls } from './vision';
+import { getSettings, LOG_FILE } from '../config';
+import { log } from '../logging';
+
+export const LLAMACPP_DEFAULT_PORT = 8080;
+
+/** How long to wait for a launched llama-server to load its model. */
+const STARTUP_TIMEOUT_MS = 120_000;
+
+/** A /health probe slower than this counts as unhealthy (a hung server). */
+const HEALTH_TIMEOUT_MS = 5_000;
+
+const SERVER_LOG_FILE = path.join(path.dirname(LOG_FILE), 'llama-server.log');
+
+// ── Managed llama-server ────────────────────────────────────────────────────
+// When models.llamacpp.model_path is set, the queue processor launches
+// llama-server itself on first use and stops it on shutdown. If it crashes
+// after starting, it is restarted with exponential backoff.
+
+/** Restart delays double from 1s up to this cap. */
+const MAX_RESTART_DELAY_MS = 60_000;
+
+/** A server that stays up this long resets the backoff. */
+const STABLE_UPTIME_MS = 5 * 60_000;
+
+/** Lines of server output kept in memory for crash reports. */
+const OUTPUT_TAIL_LINES = 20;
+
+export type LlamaServerState = 'stopped' | 'starting' | 'healthy' | 'restarting' | 'failed';
+
+let serverProcess: ChildProcess | null = null;
+let serverStarting: Promise<void> | null = null;
+let serverState: LlamaServerState = 'stopped';
+let stopping = false;
+let restartTimer: ReturnType<typeof setTimeout> | null = null;
+let restarts = 0;
+let consecutiveCrashes = 0;
+let lastExit: { code: number | null; signal: string | null; at: number } | undefined;
+
+/** State of the managed llama-server, for /api/status. */
+export function getLlamaServerStatus() {
+    return {
+        managed: !!getSettings().models?.llamacpp?.model_path,
+        state: serverState,
+        healthy: serverState === 'healthy',
+        pid: serverProcess?.pid,
+        restarts,
+        lastExit,
+    };
+}
+
+async function isHealthy(baseUrl: string): Promise<boolean> {
+    try {
+        // /health returns 503 while the model is still loading
+        const res = await fetch(`${baseUrl}/health`, { signal: AbortSignal.timeout(HEALTH_TIMEOUT_MS) });
+        return res.ok;
+    } catch {
+        return false;
+    }
+}
+
+function launchServer(modelPath: string, port: number, baseUrl: string): Promise<void> {
+    // The model, and so the tokenizer, may differ from the last launch
+    tokenCounts.clear();
+    const cfg = getSettings().models?.llamacpp || {};
+    const binary = cfg.server_binary || 'llama-server';
+    const args = ['-m', modelPath, '--host', '127.0.0.1', '--port', String(port)];
+    if (cfg.context_length) args.push('-c', String(cfg.context_length));
+    if (cfg.mmproj_path) args.push('--mmproj', cfg.mmproj_path);
+    if (cfg.extra_args) args.push(...cfg.extra_args);
+
+    if (!fs.existsSync(modelPath)) {
+        serverState = 'failed';
+        return Promise.reject(new Error(`GGUF model not found: ${modelPath}`));
+    }
+
+    log('INFO', `Launching ${binary} (model: ${modelPath}, port: ${port})`);
+    fs.mkdirSync(path.dirname(SERVER_LOG_FILE), { recursive: true });
+    const logStream = fs.createWriteStream(SERVER_LOG_FILE, { flags: 'a' });
+
+    stopping = false;
+    serverState = 'starting';
+    const child = spawn(binary, args, { stdio: ['ignore', 'pipe', 'pipe'] });
+    const startedAt = Date.now();
+    const tail: string[] = [];
+    const capture = (chunk: Buffer) => {
+        logStream.write(chunk);
+        tail.push(...chunk.toString().split('\n').filter(line => line.trim()));
+        tail.splice(0, Math.max(0, tail.length - OUTPUT_TAIL_LINES));
+    };
+    child.stdout!.on('data', capture);
+    child.stderr!.on('data', capture);
+    serverProcess = child;
+
+    return new Promise((resolve, reject) => {
+        let done = false;
+        const finish = (error?: Error) => {
+            if (done) return;
+            done = true;
+            clearInterval(poll);
+            clearTimeout(timeout);
+            if (error) {
+                if (serverState === 'starting') serverState = 'failed';
+                reject(error);
+            } else {
+                serverState = 'healthy';
+                resolve();
+            }
+        };
+
+        child.on('error', (error) => finish(new Error(`Failed to launch ${binary}: ${error.message}`)));
+        child.on('exit', (code, signal) => {
+            logStream.end();
+            if (serverProcess === child) serverProcess = null;
+            lastExit = { code, signal, at: Date.now() };
+            if (stopping) {
+                serverState = 'stopped';
+                return;
+            }
+
+            const wasHealthy = serverState === 'healthy';
+            log(wasHealthy ? 'WARN' : 'ERROR', `${binary} exited with code ${code}${signal ? ` (${signal})` : ''} (see ${SERVER_LOG_FILE})`);
+            if (tail.length > 0) log('WARN', `Last ${binary} output:\n${tail.join('\n')}`);
+            finish(new Error(`${binary} exited with code ${code} before becoming healthy (see ${SERVER_LOG_FILE})`));
+
+            // Crashes before the first successful start are reported to the caller
+            // instead; the next message will try to launch it again.
+            if (wasHealthy) scheduleRestart(modelPath, port, baseUrl, Date.now() - startedAt);
+        });
+
+        const poll = setInterval(async () => {
+            if (await isHealthy(baseUrl)) {
+                log('INFO', `llama-server is healthy at ${baseUrl}`);
+                finish();
+            }
+        }, 500);
+        const timeout = setTimeout(() => {
+            finish(new Error(`llama-server did not become healthy within ${STARTUP_TIMEOUT_MS / 1000}s`));
+            stopLlamaServer();
+        }, STARTUP_TIMEOUT_MS);
+    });
+}
+
+function scheduleRestart(modelPath: string, port: number, baseUrl: string, uptimeMs: number): void {
+    consecutiveCrashes = uptimeMs >= STABLE_UPTIME_MS ? 1 : consecutiveCrashes + 1;
+    const delay = Math.min(1000 * 2 ** (consecutiveCrashes - 1), MAX_RESTART_DELAY_MS);
+    serverState = 'restarting';
+    log('WARN', `Restarting llama-server in ${delay / 1000}s (crash ${consecutiveCrashes} in a row)`);
+
+    restartTimer = setTimeout(() => {
+        restartTimer = null;
+        restarts++;
+        serverStarting = launchServer(modelPath, port, baseUrl)
+            .catch((error) => {
+                log('ERROR', `llama-server restart failed: ${error.message}`);
+                if (!stopping) scheduleRestart(modelPath, port, baseUrl, 0);
+            })
+            .finally(() => { serverStarting = null; });
+    }, delay);
+}
+
+/**
+ * Resolve the llama-server base URL, launching the server first if it is
+ * configured with a model path and not already answering health checks.
+ */
+async function ensureServer(onStatus?: (status: string) => void): Promise<string> {
+    const cfg = getSettings().models?.llamacpp || {};
+    const port = cfg.port || LLAMACPP_DEFAULT_PORT;
+    const baseUrl = (cfg.base_url || `http://127.0.0.1:${port}`).replace(/\/+$/, '');
+
+    if (await isHealthy(baseUrl)) return baseUrl;
+    if (!cfg.model_path) {
+        throw new Error(`llama-server is not reachable at ${baseUrl} (set models.llamacpp.model_path to have TinyAGI launch it)`);
+    }
+
+    if (restartTimer) {
+        // A message is waiting: restart now instead of after the backoff
+        clearTimeout(restartTimer);
+        restartTimer = null;
+        restarts++;
+    }
+    onStatus?.(`Loading ${path.basename(cfg.model_path)} into llama-server`);
+    if (!serverStarting) {
+        serverStarting = launchServer(cfg.model_path, port, baseUrl).finally(() => { serverStarting = null; });
+    }
+    await serverStarting;
+    if (serverState !== 'healthy') {
+        throw new Error(`llama-server is ${serverState} (see ${SERVER_LOG_FILE})`);
+    }
+    return baseUrl;
+}
+
+/** Tokenizer counts kept per server and text, so stored history is tokenized once. */
+const TOKEN_CACHE_SIZE = 2000;
+const tokenCounts = new Map<string, number>();
+
+/**
+ * Count tokens with the loaded model's own tokenizer, falling back to an
+ * estimate. Counts are cached: buildPrompt counts the whole history every
+ * turn, and only the new messages should cost a /tokenize request.
+ */
+async function countTokens(baseUrl: string, text: string): Promise<number> {
+    const key = `${baseUrl}|${crypto.createHash('sha1').update(text).digest('hex')}`;
+    const cached = tokenCounts.get(key);
+    if (cached !== undefined) return cached;
+    try {
+        const res = await fetch(`${baseUrl}/tokenize`, {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify({ content: text }),
+            signal: AbortSignal.timeout(10_000),
+        });
+        if (res.ok) {
+            const json: any = await res.json();
+            if (Array.isArray(json.tokens)) {
+                if (tokenCounts.size >= TOKEN_CACHE_SIZE) tokenCounts.delete(tokenCounts.keys().next().value!);
+                tokenCounts.set(key, json.tokens.length);
+                return json.tokens.length;
+            }
+        }
+    } catch {
+        // fall through to the estimate
+    }
+    return estimateTokens(text);
+}
+
+/** Stop the llama-server launched by this process, if any, without restarting it. */
+export function stopLlamaServer(): void {
+    stopping = true;
+    if (restartTimer) {
+        clearTimeout(restartTimer);
+        restartTimer = null;
+    }
+    serverState = 'stopped';
+    if (!serverProcess) return;
+    try { serverProcess.kill('SIGTERM'); } catch { /* already dead */ }
+    serverProcess = null;
+}
+
+/** One chat completions call, streamed through `onDelta` when given. */
+async function chat(
+    baseUrl: string,
+    model: string,
+    messages: ChatMessage[],
+    opts: {
+        maxTokens?: number; tools?: object[]; responseFormat?: ResponseFormat;
+        onDelta?: (delta: string) => void; signal?: AbortSignal;
+    } = {},
+): Promise<{ text: string; message: ChatMessage; calls: ToolCall[]; usage?: TokenUsage }> {
+    const { maxTokens, tools, responseFormat, onDelta, signal } = opts;
+    // llama-server turns a JSON schema into a grammar itself, or takes GBNF directly
+    const constraint = responseFormat?.type === 'json_object' ? { response_format: { type: 'json_object' } }
+        : responseFormat?.type === 'json_schema' ? { json_schema: responseFormat.schema }
+        : responseFormat?.type === 'grammar' ? { grammar: responseFormat.grammar }
+        : {};
+    // llama-server speaks the OpenAI chat completions API
+    const res = await withBackendRetry(`llama-server at ${baseUrl}`, () => fetch(`${baseUrl}/v1/chat/completions`, {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify({
+            ...(model ? { model } : {}),
+            ...(maxTokens ? { max_tokens: maxTokens } : {}),
+            // Images go in an OpenAI-style content array
+            messages: messages.map(m => m.images ? {
+                ...m,
+                images: undefined,
+                content: [{ type: 'text', text: m.content }, ...m.images.map(url => ({ type: 'image_url', image_url: { url } }))],
+            } : m),
+            ...(tools?.length ? { tools } : {}),
+            ...constraint,
+            stream: !!onDelta,
+            ...(onDelta ? { stream_options: { include_usage: true } } : {}),
+        }),
+        signal,
+    }).then(failOnServerError('llama-server')), signal);
+    if (!res.ok) {
+        const body = (await res.text()).trim();
+        throw new Error(`llama-server returned ${res.status}: ${body || res.statusText}`);
+    }
+
+    // Streaming responses are SSE `data:` lines ending with `data: [DONE]`;
+    // with include_usage the last chunk carries the token counts.
+    let text = '';
+    let usage: any;
+    let toolCalls: any[] = [];
+    if (onDelta) {
+        await readLines(res, (line) => {
+            if (!line.startsWith('data:')) return;
+            const payload = line.slice('data:'.length).trim();
+            if (payload === '[DONE]') return;
+            let json: any;
+            try { json = JSON.parse(payload); } catch { return; }
+            const delta = json.choices?.[0]?.delta?.content;
+            if (delta) {
+                text += delta;
+                onDelta(delta);
+            }
+            if (json.usage) usage = json.usage;
+        });
+    } else {
+        const json: any = await res.json();
+        text = json.choices?.[0]?.message?.content || '';
+        toolCalls = json.choices?.[0]?.message?.tool_calls || [];
+        usage = json.usage;
+    }
+
+    // OpenAI-style tool calls carry their arguments as a JSON string
+    const calls: ToolCall[] = toolCalls.map((c: any) => {
+        let args = {};
+        try { args = JSON.parse(c.function?.arguments || '{}'); } catch { /* leave empty */ }
+        return { id: c.id, name: c.function?.name, args };
+    });
+
+    return {
+        text: text.trim(),
+        message: { role: 'assistant', content: text, ...(toolCalls.length ? { tool_calls: toolCalls } : {}) },
+        calls,
+        usage: usage ? { inputTokens: usage.prompt_tokens || 0, outputTokens: usage.completion_tokens || 0 } : undefined,
+    };
+}
+
+/**
+ * Start llama-server if needed and run a one-token completion, so weights
+ * are paged in before the first real message.
+ */
+export async function warmUpLlamaServer(): Promise<void> {
+    const baseUrl = await ensureServer();
+    await chat(baseUrl, '', [{ role: 'user', content: 'Hi' }], { maxTokens: 1, signal: AbortSignal.timeout(5 * 60_000) });
+}
+
+export const llamacppAdapter: AgentAdapter = {
+    providers: ['llamacpp'],
+
+    async invoke(opts: InvokeOptions): Promise<string> {
+        const { agentId, message, workingDir, systemPrompt, model, shouldReset, onEvent, onDelta, onUsage, signal } = opts;
+        const baseUrl = await ensureServer(opts.onStatus);
+        log('DEBUG', `Using llama.cpp (agent: ${agentId}, base_url: ${baseUrl})`);
+
+        if (shouldReset) {
+            log('INFO', `Resetting llama.cpp conversation for agent: ${agentId}`);
+        }
+        const stored = shouldReset ? { messages: [] } : loadConversation('llamacpp', agentId);
+
+        const cfg = getSettings().models?.llamacpp || {};
+        const contextLength = cfg.context_length || 0;
+        const { messages, maxTokens, conversation } = await buildPrompt('llama.cpp', systemPrompt, stored, message, {
+            contextLength,
+            countTokens: (text) => countTokens(baseUrl, text),
+            complete: async (request, limit) => {
+                const result = await chat(baseUrl, model, request, { maxTokens: limit, signal });
+                if (result.usage) onUsage?.(result.usage);
+                return result.text;
+            },
+        });
+
+        // Attach referenced images when the server has a multimodal projector
+        const images = cfg.mmproj_path || cfg.vision ? imageDataUrls(message) : [];
+        if (images.length > 0) {
+            messages[messages.length - 1] = { ...messages[messages.length - 1], images };
+        }
+
+        // Tool rounds are not streamed; the final answer is sent as one delta.
+        // llama-server needs --jinja for tool calls, and rejects grammars
+        // alongside tools, so output constraints only apply without tools.
+        const tools = getTools(opts.tools);
+        const toolCtx = { agentId, workingDir, agentDir: opts.agentDir ?? workingDir, approve: opts.approve, onStatus: opts.onStatus };
+        const response = await runToolLoop(messages, tools, toolCtx, async (convo) => {
+            const result = await chat(baseUrl, model, convo, {
+                maxTokens,
+                tools: toolSchemas(tools),
+                responseFormat: tools.length ? undefined : opts.responseFormat,
+                onDelta: tools.length ? undefined : onDelta,
+                signal,
+            });
+            if (result.usage) onUsage?.(result.usage);
+            return result;
+        });
+        if (tools.length && response) onDelta?.(response);
+
+        if (!response) throw new EmptyResponseError('llama.cpp');
+
+        saveConversation('llamacpp', agentId, {
+            summary: conversation.summary,
+            messages: [...conversation.messages, { role: 'user', content: message }, { role: 'assistant', content: response }],
+        });
+        onEvent?.(response);
+        return response;
+    },
+};
@@ -0,0 +1,203 @@
+import { AgentAdapter, InvokeOptions, TokenUsage, EmptyResponseError } from './types';
+import { ResponseFormat } from '../types';
+import { ChatMessage, loadConversation, saveConversation, buildPrompt, readLines } from './chat-history';
+import { ToolCall, getTools, toolSchemas, runToolLoop } from './tools';
+import { withBackendRetry, failOnServerError, BackendUnavailableError } from './backend-health';
+import { imageDataUrls, base64FromDataUrl } from './vision';
+import { log } from '../logging';
+
+export const OLLAMA_DEFAULT_BASE_URL = 'http://localhost:11434';
+export const OLLAMA_DEFAULT_MODEL = 'llama3.2';
+
+/** One /api/chat call, streamed through `onDelta` when given. */
+async function chat(
+    baseUrl: string,
+    model: string,
+    messages: ChatMessage[],
+    opts: {
+        contextLength?: number; maxTokens?: number; keepAlive?: string; tools?: object[]; responseFormat?: ResponseFormat;
+        onDelta?: (delta: string) => void; signal?: AbortSignal;
+    } = {},
+): Promise<{ text: string; message: ChatMessage; calls: ToolCall[]; usage?: TokenUsage }> {
+    const { contextLength, maxTokens, keepAlive, tools, responseFormat, onDelta, signal } = opts;
+    // Ollama takes "json" or a JSON schema; it has no GBNF grammar support
+    const format = responseFormat?.type === 'json_object' ? 'json'
+        : responseFormat?.type === 'json_schema' ? responseFormat.schema
+        : undefined;
+    let res: Response;
+    try {
+        res = await withBackendRetry(`Ollama at ${baseUrl}`, () => fetch(`${baseUrl}/api/chat`, {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify({
+                model,
+                messages: messages.map(m => m.images ? { ...m, images: m.images.map(base64FromDataUrl) } : m),
+                stream: !!onDelta,
+                ...(tools?.length ? { tools } : {}),
+                ...(format ? { format } : {}),
+                ...(keepAlive ? { keep_alive: keepAlive } : {}),
+                ...(contextLength ? { options: { num_ctx: contextLength, num_predict: maxTokens } } : {}),
+            }),
+            signal,
+        }).then(failOnServerError('Ollama')), signal);
+    } catch (error) {
+        if (signal?.aborted || error instanceof BackendUnavailableError) throw error;
+        throw new Error(`Could not reach Ollama at ${baseUrl}: ${(error as Error).message}`);
+    }
+    if (!res.ok) {
+        const body = (await res.text()).trim();
+        throw new Error(`Ollama returned ${res.status}: ${body || res.statusText}`);
+    }
+
+    // Streaming responses are NDJSON chunks; the last one (done: true)
+    // carries the token counts.
+    let text = '';
+    let final: any = {};
+    let toolCalls: any[] = [];
+    if (onDelta) {
+        await readLines(res, (line) => {
+            let json: any;
+            try { json = JSON.parse(line); } catch { return; }
+            if (json.error) throw new Error(`Ollama stream error: ${json.error}`);
+            const delta = json.message?.content;
+            if (delta) {
+                text += delta;
+                onDelta(delta);
+            }
+            if (json.message?.tool_calls) toolCalls.push(...json.message.tool_calls);
+            if (json.done) final = json;
+        });
+    } else {
+        final = await res.json();
+        text = final.message?.content || '';
+        toolCalls = final.message?.tool_calls || [];
+    }
+
+    const usage = final.prompt_eval_count !== undefined || final.eval_count !== undefined
+        ? { inputTokens: final.prompt_eval_count || 0, outputTokens: final.eval_count || 0 }
+        : undefined;
+    const calls: ToolCall[] = toolCalls.map((c: any) => ({
+        name: c.function?.name,
+        args: c.function?.arguments || {},
+    }));
+    return {
+        text: text.trim(),
+        message: { role: 'assistant', content: text, ...(toolCalls.length ? { tool_calls: toolCalls } : {}) },
+        calls,
+        usage,
+    };
+}
+
+const visionCache = new Map<string, boolean>();
+
+/** Whether Ollama reports the model as vision-capable (cached per model). */
+async function supportsVision(baseUrl: string, model: string): Promise<boolean> {
+    const key = `${baseUrl}|${model}`;
+    if (!visionCache.has(key)) {
+        try {
+            const res = await fetch(`${baseUrl}/api/show`, {
+                method: 'POST',
+                headers: { 'Content-Type': 'application/json' },
+                body: JSON.stringify({ model }),
+                signal: AbortSignal.timeout(10_000),
+            });
+            const info: any = res.ok ? await res.json() : {};
+            visionCache.set(key, Array.isArray(info.capabilities) && info.capabilities.includes('vision'));
+        } catch {
+            return false;
+        }
+    }
+    return visionCache.get(key)!;
+}
+
+/**
+ * Load a model into Ollama's memory without generating anything (an empty
+ * prompt), with the same context size real requests use so Ollama does not
+ * reload it for the first message.
+ */
+export async function warmUpOllama(
+    baseUrl: string,
+    model: string,
+    opts: { contextLength?: number; keepAlive?: string } = {},
+): Promise<void> {
+    const res = await fetch(`${baseUrl.replace(/\/+$/, '')}/api/generate`, {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify({
+            model: model || OLLAMA_DEFAULT_MODEL,
+            prompt: '',
+            stream: false,
+            ...(opts.keepAlive ? { keep_alive: opts.keepAlive } : {}),
+            ...(opts.contextLength ? { options: { num_ctx: opts.contextLength } } : {}),
+        }),
+        signal: AbortSignal.timeout(5 * 60_000),
+    });
+    if (!res.ok) {
+        throw new Error(`Ollama returned ${res.status}: ${(await res.text()).trim() || res.statusText}`);
+    }
+}
+
+export const ollamaAdapter: AgentAdapter = {
+    providers: ['ollama'],
+
+    async invoke(opts: InvokeOptions): Promise<string> {
+        const { agentId, message, workingDir, systemPrompt, model, shouldReset, envOverrides, onEvent, onDelta, onUsage, signal } = opts;
+        const baseUrl = (envOverrides.OLLAMA_HOST || process.env.OLLAMA_HOST || OLLAMA_DEFAULT_BASE_URL).replace(/\/+$/, '');
+        const modelName = model || OLLAMA_DEFAULT_MODEL;
+        log('DEBUG', `Using Ollama (agent: ${agentId}, model: ${modelName}, base_url: ${baseUrl})`);
+
+        if (shouldReset) {
+            log('INFO', `Resetting Ollama conversation for agent: ${agentId}`);
+        }
+        const stored = shouldReset ? { messages: [] } : loadConversation('ollama', agentId);
+
+        // Ollama silently truncates prompts that overflow num_ctx, so compact
+        // history to fit first (and fail if even that is not enough).
+        const contextLength = Number(envOverrides.OLLAMA_CONTEXT_LENGTH) || 0;
+        const keepAlive = envOverrides.OLLAMA_KEEP_ALIVE;
+        const { messages, maxTokens, conversation } = await buildPrompt('Ollama', systemPrompt, stored, message, {
+            contextLength,
+            complete: async (request, limit) => {
+                const result = await chat(baseUrl, modelName, request, { contextLength, maxTokens: limit, keepAlive, signal });
+                if (result.usage) onUsage?.(result.usage);
+                return result.text;
+            },
+        });
+
+        // Attach referenced images when the model can see them
+        const images = imageDataUrls(message);
+        if (images.length > 0) {
+            if (await supportsVision(baseUrl, modelName)) {
+                messages[messages.length - 1] = { ...messages[messages.length - 1], images };
+            } else {
+                log('DEBUG', `Ollama model ${modelName} has no vision capability — sending image references as text`);
+            }
+        }
+
+        // Tool rounds are not streamed; the final answer is sent as one delta.
+        // Output constraints would block tool calls, so they only apply without tools.
+        const tools = getTools(opts.tools);
+        const toolCtx = { agentId, workingDir, agentDir: opts.agentDir ?? workingDir, approve: opts.approve, onStatus: opts.onStatus };
+        const response = await runToolLoop(messages, tools, toolCtx, async (convo) => {
+            const result = await chat(baseUrl, modelName, convo, {
+                contextLength, maxTokens, keepAlive,
+                tools: toolSchemas(tools),
+                responseFormat: tools.length ? undefined : opts.responseFormat,
+                onDelta: tools.length ? undefined : onDelta,
+                signal,
+            });
+            if (result.usage) onUsage?.(result.usage);
+            return result;
+        });
+        if (tools.length && response) onDelta?.(response);
+
+        if (!response) throw new EmptyResponseError('Ollama');
+
+        saveConversation('ollama', agentId, {
+            summary: conversation.summary,
+            messages: [...conversation.messages, { role: 'user', content: message }, { role: 'assistant', content: response }],
+        });
+        onEvent?.(response);
+        return response;
+    },
+};
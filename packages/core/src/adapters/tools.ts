@@ -0,0 +1,446 @@
+import { execFile } from 'child_process';
+import dns from 'dns';
+import fs from 'fs';
+import http from 'http';
+import https from 'https';
+import net from 'net';
+import path from 'path';
+import { ChatMessage } from './chat-history';
+import { getSettings } from '../config';
+import { saveMemory, deleteMemory } from '../memory';
+import { log } from '../logging';
+
+/**
+ * Tool calling for the HTTP model backends (Ollama, llama.cpp). The CLI
+ * harnesses bring their own tools; these give local models a small, opt-in
+ * set enabled per agent with `tools: [...]`.
+ */
+
+export interface ToolContext {
+    agentId: string;
+    workingDir: string;
+    agentDir: string;
+    approve?: (question: string) => Promise<boolean>;
+    onStatus?: (status: string) => void;
+}
+
+export interface Tool {
+    name: string;
+    description: string;
+    /** JSON schema for the arguments object. */
+    parameters: Record<string, unknown>;
+    run(args: Record<string, any>, ctx: ToolContext): Promise<string>;
+}
+
+export interface ToolCall {
+    id?: string;
+    name: string;
+    args: Record<string, any>;
+}
+
+/** Max model → tool → model round trips per message. */
+const MAX_TOOL_ROUNDS = 5;
+
+/** Tool output is cut to this many characters before going back to the model. */
+const MAX_TOOL_OUTPUT = 8000;
+
+const tools = new Map<string, Tool>();
+
+export function registerTool(tool: Tool): void {
+    tools.set(tool.name, tool);
+}
+
+/** Resolve enabled tool names, skipping (and logging) unknown ones. */
+export function getTools(names: string[] = []): Tool[] {
+    const resolved: Tool[] = [];
+    for (const name of names) {
+        const tool = tools.get(name);
+        if (tool) resolved.push(tool);
+        else log('WARN', `Unknown tool '${name}' — skipping`);
+    }
+    return resolved;
+}
+
+/** Tool declarations in the OpenAI/Ollama `tools` request format. */
+export function toolSchemas(list: Tool[]): object[] {
+    return list.map(t => ({
+        type: 'function',
+        function: { name: t.name, description: t.description, parameters: t.parameters },
+    }));
+}
+
+/**
+ * Call the model, run any tools it asks for, feed the results back, and
+ * repeat until it answers in plain text (or MAX_TOOL_ROUNDS is reached).
+ * `step` makes one model call and returns the assistant message to append.
+ */
+export async function runToolLoop(
+    messages: ChatMessage[],
+    list: Tool[],
+    ctx: ToolContext,
+    step: (messages: ChatMessage[]) => Promise<{ text: string; message: ChatMessage; calls: ToolCall[] }>,
+): Promise<string> {
+    const convo = [...messages];
+    for (let round = 0; round < MAX_TOOL_ROUNDS; round++) {
+        const { text, message, calls } = await step(convo);
+        if (calls.length === 0) return text;
+
+        convo.push(message);
+        for (const call of calls) {
+            const tool = list.find(t => t.name === call.name);
+            ctx.onStatus?.(`Calling tool ${call.name}`);
+            let output: string;
+            if (!tool) {
+                output = `Error: unknown tool '${call.name}'`;
+            } else {
+                try {
+                    output = await tool.run(call.args, ctx);
+                } catch (error) {
+                    output = `Error: ${(error as Error).message}`;
+                }
+            }
+            log('INFO', `Tool ${call.name} (agent: ${ctx.agentId}) → ${output.length} chars`);
+            convo.push({
+                role: 'tool',
+                content: output.slice(0, MAX_TOOL_OUTPUT),
+                tool_call_id: call.id,
+                name: call.name,
+            });
+        }
+    }
+    throw new Error(`Model kept calling tools after ${MAX_TOOL_ROUNDS} rounds`);
+}
+
+// ── Built-in tools ──────────────────────────────────────────────────────────
+
+registerTool({
+    name: 'current_time',
+    description: 'Get the current date and time, optionally in a given IANA time zone.',
+    parameters: {
+        type: 'object',
+        properties: { timezone: { type: 'string', description: 'e.g. Europe/Berlin (default: server time zone)' } },
+    },
+    async run(args) {
+        const now = new Date();
+        const formatted = now.toLocaleString('en-US', {
+            timeZone: args.timezone || undefined,
+            dateStyle: 'full',
+            timeStyle: 'long',
+        });
+        return `${formatted} (ISO ${now.toISOString()})`;
+    },
+});
+
+registerTool({
+    name: 'calculator',
+    description: 'Evaluate an arithmetic expression. Supports + - * / % ^, parentheses, pi, e, and sqrt abs round floor ceil ln log exp sin cos tan.',
+    parameters: {
+        type: 'object',
+        properties: { expression: { type: 'string' } },
+        required: ['expression'],
+    },
+    async run(args) {
+        return String(evaluate(String(args.expression ?? '')));
+    },
+});
+
+/** web_fetch stops reading after this many bytes of the body. */
+const FETCH_MAX_BYTES = 1024 * 1024;
+const FETCH_MAX_REDIRECTS = 5;
+const FETCH_TIMEOUT_MS = 15_000;
+
+/**
+ * Loopback, private, link-local (cloud metadata), and other non-public
+ * ranges. web_fetch must not reach them, e.g. the TinyAGI API itself.
+ */
+const BLOCKED_ADDRESSES = new net.BlockList();
+for (const [network, prefix] of [
+    ['0.0.0.0', 8], ['10.0.0.0', 8], ['100.64.0.0', 10], ['127.0.0.0', 8], ['169.254.0.0', 16],
+    ['172.16.0.0', 12], ['192.168.0.0', 16], ['224.0.0.0', 4], ['240.0.0.0', 4],
+] as const) {
+    BLOCKED_ADDRESSES.addSubnet(network, prefix, 'ipv4');
+}
+for (const [network, prefix] of [['::', 128], ['::1', 128], ['fc00::', 7], ['fe80::', 10], ['ff00::', 8]] as const) {
+    BLOCKED_ADDRESSES.addSubnet(network, prefix, 'ipv6');
+}
+
+function isBlockedAddress(address: string): boolean {
+    // IPv4-mapped IPv6 (::ffff:127.0.0.1) is checked as the IPv4 address
+    const mapped = address.match(/^::ffff:(\d+\.\d+\.\d+\.\d+)$/i);
+    if (mapped) return BLOCKED_ADDRESSES.check(mapped[1], 'ipv4');
+    return BLOCKED_ADDRESSES.check(address, net.isIPv6(address) ? 'ipv6' : 'ipv4');
+}
+
+/** dns.lookup that refuses names resolving to a blocked address; used for every connection. */
+const publicLookup: net.LookupFunction = (hostname, options, callback) => {
+    dns.lookup(hostname, { ...options, all: true }, (err, addresses) => {
+        if (err) return callback(err, '', 0);
+        const blocked = addresses.find(a => isBlockedAddress(a.address));
+        if (blocked) return callback(new Error(`${hostname} resolves to a private address (${blocked.address})`), '', 0);
+        if (options.all) return (callback as any)(null, addresses);
+        callback(null, addresses[0].address, addresses[0].family);
+    });
+};
+
+interface FetchResult {
+    status: number;
+    type: string;
+    body: string;
+    truncated: boolean;
+}
+
+/**
+ * GET a public http(s) URL. Every hop of a redirect is checked again, and
+ * the address is checked at connect time, so DNS cannot be swapped between
+ * the check and the request.
+ */
+function fetchPublic(url: URL, redirectsLeft = FETCH_MAX_REDIRECTS): Promise<FetchResult> {
+    if (url.protocol !== 'http:' && url.protocol !== 'https:') {
+        return Promise.reject(new Error('only http and https URLs are allowed'));
+    }
+    // Literal IPs are connected to without a lookup
+    const host = url.hostname.replace(/^\[|\]$/g, '');
+    if (net.isIP(host) && isBlockedAddress(host)) {
+        return Promise.reject(new Error(`${host} is a private address`));
+    }
+
+    return new Promise((resolve, reject) => {
+        const client = url.protocol === 'https:' ? https : http;
+        const req = client.get(url, { lookup: publicLookup, timeout: FETCH_TIMEOUT_MS }, (res) => {
+            const status = res.statusCode || 0;
+            if (status >= 300 && status < 400 && res.headers.location) {
+                res.resume();
+                if (redirectsLeft === 0) return reject(new Error('too many redirects'));
+                return resolve(fetchPublic(new URL(res.headers.location, url), redirectsLeft - 1));
+            }
+
+            const chunks: Buffer[] = [];
+            let size = 0;
+            const finish = (truncated: boolean) => resolve({
+                status,
+                type: String(res.headers['content-type'] || ''),
+                body: Buffer.concat(chunks).subarray(0, FETCH_MAX_BYTES).toString('utf8'),
+                truncated,
+            });
+            res.on('data', (chunk: Buffer) => {
+                chunks.push(chunk);
+                size += chunk.length;
+                if (size >= FETCH_MAX_BYTES) {
+                    res.destroy();
+                    finish(true);
+                }
+            });
+            res.on('end', () => finish(false));
+            res.on('error', reject);
+        });
+        req.on('timeout', () => req.destroy(new Error(`timed out after ${FETCH_TIMEOUT_MS / 1000}s`)));
+        req.on('error', reject);
+    });
+}
+
+registerTool({
+    name: 'web_fetch',
+    description: 'Fetch a public web page or API over HTTP(S) and return its text content. '
+        + 'Local and private network addresses are not reachable.',
+    parameters: {
+        type: 'object',
+        properties: { url: { type: 'string' } },
+        required: ['url'],
+    },
+    async run(args) {
+        const res = await fetchPublic(new URL(String(args.url)));
+        const text = res.type.includes('html') ? htmlToText(res.body) : res.body;
+        return `HTTP ${res.status}${res.truncated ? ` (first ${FETCH_MAX_BYTES / 1024} KB)` : ''}\n\n${text}`;
+    },
+});
+
+registerTool({
+    name: 'read_file',
+    description: "Read a text file from the agent's working directory.",
+    parameters: {
+        type: 'object',
+        properties: { path: { type: 'string', description: 'Path relative to the working directory' } },
+        required: ['path'],
+    },
+    async run(args, ctx) {
+        const root = fs.realpathSync(ctx.workingDir);
+        const target = fs.realpathSync(path.resolve(root, String(args.path)));
+        const rel = path.relative(root, target);
+        if (rel.startsWith('..') || path.isAbsolute(rel)) {
+            throw new Error('path is outside the working directory');
+        }
+        return fs.readFileSync(target, 'utf8');
+    },
+});
+
+registerTool({
+    name: 'shell',
+    description: "Run one command (no pipes or redirects) in the agent's working directory. "
+        + 'Only allowlisted programs can run, and the user may be asked to approve it first.',
+    parameters: {
+        type: 'object',
+        properties: { command: { type: 'string', description: 'e.g. git status' } },
+        required: ['command'],
+    },
+    async run(args, ctx) {
+        const cfg = getSettings().tools?.shell || {};
+        const command = String(args.command ?? '').trim();
+        // No shell is involved, but reject operators so the model gets a clear error
+        if (/[;&|`$<>\n]/.test(command)) {
+            throw new Error('shell operators are not supported; run one command at a time');
+        }
+        const argv = splitArgs(command);
+        if (argv.length === 0) throw new Error('empty command');
+        if (!(cfg.allowlist || []).includes(argv[0])) {
+            throw new Error(`'${argv[0]}' is not in tools.shell.allowlist`);
+        }
+
+        if (cfg.approval !== false) {
+            if (!ctx.approve) throw new Error('this command needs approval and there is no one to ask');
+            if (!await ctx.approve(`Run \`${command}\`?`)) {
+                return 'The user declined to run this command.';
+            }
+        }
+
+        const timeout = (cfg.timeout || 30) * 1000;
+        log('INFO', `Shell tool (agent: ${ctx.agentId}): ${command}`);
+        return new Promise((resolve) => {
+            execFile(argv[0], argv.slice(1), { cwd: ctx.workingDir, timeout, maxBuffer: 1024 * 1024 }, (error, stdout, stderr) => {
+                const status = !error ? 'exit 0'
+                    : error.killed ? `killed after ${timeout / 1000}s`
+                    : `exit ${error.code ?? 'error'}: ${error.message}`;
+                resolve(`[${status}]\n${stdout}${stderr ? `\n[stderr]\n${stderr}` : ''}`);
+            });
+        });
+    },
+});
+
+registerTool({
+    name: 'save_memory',
+    description: 'Save a lasting fact about the user, their setup, or their preferences '
+        + '(e.g. "home server IP is 10.0.0.5"). Saved memories appear in your system prompt in every later conversation. '
+        + 'Saving under an existing name replaces that memory.',
+    parameters: {
+        type: 'object',
+        properties: {
+            name: { type: 'string', description: 'Short identifier, e.g. home-server' },
+            summary: { type: 'string', description: 'The fact itself, in one line' },
+            category: { type: 'string', description: 'Optional folder, e.g. people or preferences' },
+        },
+        required: ['name', 'summary'],
+    },
+    async run(args, ctx) {
+        const file = saveMemory(ctx.agentDir, {
+            name: String(args.name ?? ''),
+            summary: String(args.summary ?? ''),
+            category: args.category ? String(args.category) : undefined,
+        });
+        return `Saved memory/${file}`;
+    },
+});
+
+registerTool({
+    name: 'forget_memory',
+    description: 'Delete a saved memory by name, e.g. when the user says a fact is no longer true.',
+    parameters: {
+        type: 'object',
+        properties: { name: { type: 'string' } },
+        required: ['name'],
+    },
+    async run(args, ctx) {
+        const name = String(args.name ?? '');
+        return deleteMemory(ctx.agentDir, name) ? `Deleted memory '${name}'` : `No memory named '${name}'`;
+    },
+});
+
+/** Split a command line into argv, honouring single and double quotes. */
+function splitArgs(command: string): string[] {
+    const argv: string[] = [];
+    const re = /"([^"]*)"|'([^']*)'|(\S+)/g;
+    let match: RegExpExecArray | null;
+    while ((match = re.exec(command))) {
+        argv.push(match[1] ?? match[2] ?? match[3]);
+    }
+    return argv;
+}
+
+function htmlToText(html: string): string {
+    return html
+        .replace(/<(script|style|noscript)[\s\S]*?<\/\1>/gi, '')
+        .replace(/<[^>]+>/g, ' ')
+        .replace(/&nbsp;/g, ' ').replace(/&amp;/g, '&').replace(/&lt;/g, '<').replace(/&gt;/g, '>')
+        .replace(/[ \t]+/g, ' ')
+        .replace(/\s*\n\s*/g, '\n')
+        .trim();
+}
+
+// ── Calculator ──────────────────────────────────────────────────────────────
+// Small recursive-descent parser so expressions never reach eval().
+
+const FUNCTIONS: Record<string, (x: number) => number> = {
+    sqrt: Math.sqrt, abs: Math.abs, round: Math.round, floor: Math.floor, ceil: Math.ceil,
+    ln: Math.log, log: Math.log10, exp: Math.exp, sin: Math.sin, cos: Math.cos, tan: Math.tan,
+};
+const CONSTANTS: Record<string, number> = { pi: Math.PI, e: Math.E };
+
+function hasOwn(obj: object, key: string): boolean {
+    return Object.prototype.hasOwnProperty.call(obj, key);
+}
+
+function evaluate(expression: string): number {
+    const tokens = expression.match(/\d+(?:\.\d+)?(?:e[+-]?\d+)?|[a-z]+|[-+*/%^()]|\S/gi) || [];
+    let pos = 0;
+    const peek = () => tokens[pos];
+    const next = () => tokens[pos++];
+
+    function expr(): number {
+        let value = term();
+        while (peek() === '+' || peek() === '-') {
+            value = next() === '+' ? value + term() : value - term();
+        }
+        return value;
+    }
+    function term(): number {
+        let value = power();
+        while (peek() === '*' || peek() === '/' || peek() === '%') {
+            const op = next();
+            const rhs = power();
+            value = op === '*' ? value * rhs : op === '/' ? value / rhs : value % rhs;
+        }
+        return value;
+    }
+    function power(): number {
+        const base = unary();
+        if (peek() !== '^') return base;
+        next();
+        return base ** power();
+    }
+    function unary(): number {
+        if (peek() === '-') { next(); return -unary(); }
+        if (peek() === '+') { next(); return unary(); }
+        return primary();
+    }
+    function primary(): number {
+        const token = next();
+        if (token === undefined) throw new Error('unexpected end of expression');
+        if (token === '(') {
+            const value = expr();
+            if (next() !== ')') throw new Error("expected ')'");
+            return value;
+        }
+        if (/^\d/.test(token)) return parseFloat(token);
+        const name = token.toLowerCase();
+        if (hasOwn(CONSTANTS, name)) return CONSTANTS[name];
+        if (hasOwn(FUNCTIONS, name)) {
+            if (next() !== '(') throw new Error(`expected '(' after ${name}`);
+            const arg = expr();
+            if (next() !== ')') throw new Error("expected ')'");
+            return FUNCTIONS[name](arg);
+        }
+        throw new Error(`unexpected '${token}'`);
+    }
+
+    const result = expr();
+    if (pos < tokens.length) throw new Error(`unexpected '${peek()}'`);
+    return result;
+}
@@ -1,3 +1,5 @@
+import type { ResponseFormat } from '../types';
+
 /**
  * Adapter interface — each CLI backend implements this to handle agent invocation.
  */
@@ -5,11 +7,32 @@ export interface InvokeOptions {
     agentId: string;
     message: string;
     workingDir: string;
+    /** The agent's own directory (holds memory/); may differ from workingDir. */
+    agentDir?: string;
     systemPrompt: string;
     model: string;
     shouldReset: boolean;
     envOverrides: Record<string, string>;
     onEvent?: (text: string) => void;
+    /** Called with incremental text as tokens arrive, for adapters that can stream them. */
+    onDelta?: (delta: string) => void;
+    /** Built-in tools the model may call (HTTP backends only). */
+    tools?: string[];
+    /** Ask the sender a yes/no question through their channel (absent when no one can answer). */
+    approve?: (question: string) => Promise<boolean>;
+    /** Short human-readable progress notes (model loading, tool calls). */
+    onStatus?: (status: string) => void;
+    /** Constrain the reply to JSON or a grammar, where the backend supports it. */
+    responseFormat?: ResponseFormat;
+    /** Aborted when the message times out or is cancelled. */
+    signal?: AbortSignal;
+    /** Called with token counts when the CLI reports them. */
+    onUsage?: (usage: TokenUsage) => void;
+}
+
+export interface TokenUsage {
+    inputTokens: number;
+    outputTokens: number;
 }
 
 export interface AgentAdapter {
@@ -18,3 +41,13 @@ export interface AgentAdapter {
     /** Execute the agent and return the response text. */
     invoke(options: InvokeOptions): Promise<string>;
 }
+
+/**
+ * The model finished without any text. Thrown so a fallback provider gets a
+ * turn; the sender is told only once the whole chain has come back empty.
+ */
+export class EmptyResponseError extends Error {
+    constructor(readonly provider: string) {
+        super(`${provider} returned an empty response`);
+    }
+}
@@ -0,0 +1,38 @@
+import fs from 'fs';
+import path from 'path';
+import { log } from '../logging';
+
+/**
+ * Image input for multimodal local models. Image `[file: …]` references in
+ * the message are read and attached to the user turn as data URLs; each
+ * backend converts them to its own wire format.
+ */
+
+const IMAGE_TYPES: Record<string, string> = {
+    '.jpg': 'image/jpeg', '.jpeg': 'image/jpeg', '.png': 'image/png', '.webp': 'image/webp', '.gif': 'image/gif',
+};
+
+/** Larger images are skipped rather than sent to the model. */
+const MAX_IMAGE_BYTES = 10 * 1024 * 1024;
+
+/** Data URLs for the image files referenced in a message. */
+export function imageDataUrls(message: string): string[] {
+    const urls: string[] = [];
+    for (const match of message.matchAll(/\[file:\s*([^\]]+)\]/g)) {
+        const file = match[1].trim();
+        const mime = IMAGE_TYPES[path.extname(file).toLowerCase()];
+        if (!mime || !fs.existsSync(file)) continue;
+        const size = fs.statSync(file).size;
+        if (size > MAX_IMAGE_BYTES) {
+            log('WARN', `Not sending ${path.basename(file)} to the model: ${size} bytes is over the ${MAX_IMAGE_BYTES} byte limit`);
+            continue;
+        }
+        urls.push(`data:${mime};base64,${fs.readFileSync(file).toString('base64')}`);
+    }
+    return urls;
+}
+
+/** Strip the `data:…;base64,` prefix (Ollama takes bare base64). */
+export function base64FromDataUrl(url: string): string {
+    return url.slice(url.indexOf(',') + 1);
+}
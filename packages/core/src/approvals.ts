@@ -0,0 +1,56 @@
+/**
+ * Pending yes/no approvals (e.g. "run `git status`?") waiting on a reply from
+ * the user who sent the message being processed. The API intercepts that
+ * user's next message and resolves the approval instead of queueing it —
+ * queueing would deadlock, since the agent is busy waiting for the answer.
+ */
+
+const APPROVAL_TIMEOUT_MS = 5 * 60 * 1000;
+
+const YES = /^\s*(y|yes|ok|okay|approve|approved|sure|run it|go|go ahead)\s*[.!]*\s*$/i;
+
+const pending = new Map<string, (approved: boolean) => void>();
+
+function approvalKey(channel: string, senderKey: string): string {
+    return `${channel}:${senderKey}`;
+}
+
+/**
+ * Wait for the sender's next message on `channel`. Resolves true for an
+ * affirmative reply, false for anything else, after 5 minutes, or as soon
+ * as `signal` aborts (timeout or /stop of the message that asked).
+ */
+export function awaitApproval(
+    channel: string,
+    senderKey: string,
+    signal?: AbortSignal,
+    timeoutMs = APPROVAL_TIMEOUT_MS,
+): Promise<boolean> {
+    const key = approvalKey(channel, senderKey);
+    pending.get(key)?.(false); // only one question per sender at a time
+    if (signal?.aborted) return Promise.resolve(false);
+
+    return new Promise((resolve) => {
+        const timer = setTimeout(() => settle(false), timeoutMs);
+        const onAbort = () => settle(false);
+        function settle(approved: boolean) {
+            clearTimeout(timer);
+            signal?.removeEventListener('abort', onAbort);
+            if (pending.get(key) === settle) pending.delete(key);
+            resolve(approved);
+        }
+        signal?.addEventListener('abort', onAbort, { once: true });
+        pending.set(key, settle);
+    });
+}
+
+/**
+ * Deliver an incoming message to a pending approval. Returns true if the
+ * message was consumed as the answer (and must not be queued).
+ */
+export function resolveApproval(channel: string, senderKey: string, text: string): boolean {
+    const settle = pending.get(approvalKey(channel, senderKey));
+    if (!settle) return false;
+    settle(YES.test(text));
+    return true;
+}
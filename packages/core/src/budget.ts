@@ -0,0 +1,52 @@
+import { getSettings } from './config';
+import { log, emitEvent } from './logging';
+import { enqueueResponse, getUsageTotals } from './queues';
+
+/**
+ * Daily usage budget: after each message, today's tokens and inference
+ * seconds are totalled from the usage table and compared with
+ * `monitoring.budget`. The first time a limit is passed each day, an alert
+ * goes to the `notify` channel (like a heartbeat reply) and a
+ * `budget:exceeded` event is emitted.
+ */
+
+/** Limits already alerted on, keyed by limit name → local date. */
+const alerted = new Map<string, string>();
+
+function startOfToday(): number {
+    const d = new Date();
+    d.setHours(0, 0, 0, 0);
+    return d.getTime();
+}
+
+/** Check today's usage against the configured budget and alert once per limit per day. */
+export function checkBudget(): void {
+    const budget = getSettings().monitoring?.budget;
+    if (!budget || (!budget.daily_tokens && !budget.daily_inference_seconds)) return;
+
+    const today = new Date().toDateString();
+    const totals = getUsageTotals(startOfToday());
+    const limits = [
+        { name: 'tokens', used: totals.tokens, limit: budget.daily_tokens, unit: 'tokens' },
+        { name: 'inference_seconds', used: Math.round(totals.inferenceSeconds), limit: budget.daily_inference_seconds, unit: 'seconds of inference' },
+    ];
+
+    for (const { name, used, limit, unit } of limits) {
+        if (!limit || used < limit || alerted.get(name) === today) continue;
+        alerted.set(name, today);
+
+        const text = `Usage budget exceeded: ${used.toLocaleString()} ${unit} today (limit ${limit.toLocaleString()}).`;
+        log('WARN', text);
+        emitEvent('budget:exceeded', { limit: name, used, budget: limit });
+        if (budget.notify?.channel) {
+            enqueueResponse({
+                channel: budget.notify.channel,
+                sender: budget.notify.sender,
+                senderId: budget.notify.sender_id,
+                message: text,
+                originalMessage: '',
+                messageId: `budget_${name}_${Date.now()}`,
+            });
+        }
+    }
+}
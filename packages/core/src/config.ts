@@ -37,7 +37,10 @@ export function getSettings(): Settings {
             }
         }
 
-        // Auto-detect provider if not specified
+        // Auto-detect provider if not specified. Local backends (ollama,
+        // llamacpp) need an explicit models.provider: their blocks also carry
+        // base_url for RAG embeddings and moderation, which must not move an
+        // existing Anthropic setup onto a local model.
         if (!settings?.models?.provider) {
             if (settings?.models?.openai) {
                 if (!settings.models) settings.models = {};
@@ -68,6 +71,12 @@ export function getDefaultAgentFromModels(settings: Settings): AgentConfig {
         model = settings?.models?.openai?.model || 'gpt-5.3-codex';
     } else if (provider === 'opencode') {
         model = settings?.models?.opencode?.model || 'sonnet';
+    } else if (provider === 'ollama') {
+        model = settings?.models?.ollama?.model || 'llama3.2';
+    } else if (provider === 'llamacpp') {
+        // llama-server serves whichever GGUF it was started with
+        const modelPath = settings?.models?.llamacpp?.model_path;
+        model = modelPath ? path.basename(modelPath, '.gguf') : 'default';
     } else {
         model = settings?.models?.anthropic?.model || 'sonnet';
     }
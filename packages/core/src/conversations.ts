@@ -0,0 +1,116 @@
+import fs from 'fs';
+import path from 'path';
+import { TINYAGI_HOME } from './config';
+import { log } from './logging';
+import { Conversation, loadConversation, saveConversation } from './adapters/chat-history';
+
+/**
+ * Conversation branching for agents whose history TinyAGI stores itself
+ * (Ollama, llama.cpp): `/undo` drops the last exchange, `/branch <name>`
+ * forks the conversation, and `/switch <name>` moves between saved
+ * branches. The active branch stays in the adapter's usual history file;
+ * the others are kept alongside it in `<agent>.branches.json`.
+ */
+
+const STORED_HISTORY_PROVIDERS = ['ollama', 'llamacpp'];
+const DEFAULT_BRANCH = 'main';
+const BRANCH_NAME = /^[\w.-]{1,40}$/;
+
+interface BranchIndex {
+    active: string;
+    branches: Record<string, Conversation>;
+}
+
+function branchFile(backend: string, agentId: string): string {
+    return path.join(TINYAGI_HOME, backend, `${agentId}.branches.json`);
+}
+
+function loadBranches(backend: string, agentId: string): BranchIndex {
+    try {
+        const data = JSON.parse(fs.readFileSync(branchFile(backend, agentId), 'utf8'));
+        return { active: data.active || DEFAULT_BRANCH, branches: data.branches || {} };
+    } catch {
+        return { active: DEFAULT_BRANCH, branches: {} };
+    }
+}
+
+function saveBranches(backend: string, agentId: string, index: BranchIndex): void {
+    const file = branchFile(backend, agentId);
+    fs.mkdirSync(path.dirname(file), { recursive: true });
+    fs.writeFileSync(file, JSON.stringify(index, null, 2));
+}
+
+function preview(text: string): string {
+    const flat = text.replace(/\s+/g, ' ').trim();
+    return flat.length > 60 ? `${flat.slice(0, 59)}…` : flat;
+}
+
+function listBranches(index: BranchIndex): string {
+    const names = new Set([index.active, ...Object.keys(index.branches)]);
+    return [...names].sort().map(name => `${name === index.active ? '* ' : '  '}${name}`).join('\n');
+}
+
+/** Remove the last user message and everything after it. */
+function undo(backend: string, agentId: string): string {
+    const conversation = loadConversation(backend, agentId);
+    const lastUser = conversation.messages.map(m => m.role).lastIndexOf('user');
+    if (lastUser === -1) return 'Nothing to undo.';
+    const removed = conversation.messages[lastUser];
+    saveConversation(backend, agentId, { summary: conversation.summary, messages: conversation.messages.slice(0, lastUser) });
+    return `Removed the last exchange ("${preview(removed.content)}").`;
+}
+
+function branch(backend: string, agentId: string, name: string): string {
+    const index = loadBranches(backend, agentId);
+    if (name === index.active || index.branches[name]) {
+        return `Branch '${name}' already exists. Use /switch ${name} to go to it.`;
+    }
+    const current = loadConversation(backend, agentId);
+    index.branches[index.active] = current;
+    index.branches[name] = current;
+    index.active = name;
+    saveBranches(backend, agentId, index);
+    return `Created branch '${name}' from the current conversation and switched to it.`;
+}
+
+function switchBranch(backend: string, agentId: string, name: string): string {
+    const index = loadBranches(backend, agentId);
+    if (name === index.active) return `Already on branch '${name}'.`;
+    const target = index.branches[name];
+    if (!target) return `No branch named '${name}'. Branches:\n${listBranches(index)}`;
+    index.branches[index.active] = loadConversation(backend, agentId);
+    saveConversation(backend, agentId, target);
+    index.active = name;
+    saveBranches(backend, agentId, index);
+    return `Switched to branch '${name}' (${target.messages.length} messages).`;
+}
+
+/**
+ * Handle `/undo`, `/branch [name]`, or `/switch <name>` for an agent. Returns
+ * the reply to send, or null when the message is not one of these commands.
+ * Callers must run this in the agent's queue so it never races a turn.
+ */
+export function handleConversationCommand(message: string, agentId: string, provider: string | undefined): string | null {
+    const match = message.trim().match(/^[!/](undo|branch|switch)(?:\s+(\S+))?\s*$/i);
+    if (!match) return null;
+    const command = match[1].toLowerCase();
+    const name = match[2];
+
+    const backend = provider || 'anthropic';
+    if (!STORED_HISTORY_PROVIDERS.includes(backend)) {
+        return `/${command} works with agents on Ollama or llama.cpp; @${agentId} uses ${backend}, which keeps its own history.`;
+    }
+    if (name && !BRANCH_NAME.test(name)) {
+        return 'Branch names may use letters, digits, dot, dash and underscore (up to 40 characters).';
+    }
+
+    log('INFO', `Conversation command /${command}${name ? ` ${name}` : ''} for @${agentId}`);
+    switch (command) {
+        case 'undo':
+            return undo(backend, agentId);
+        case 'branch':
+            return name ? branch(backend, agentId, name) : `Branches for @${agentId}:\n${listBranches(loadBranches(backend, agentId))}`;
+        default:
+            return name ? switchBranch(backend, agentId, name) : 'Usage: /switch <branch>';
+    }
+}
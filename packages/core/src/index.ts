@@ -19,3 +19,13 @@ export * from './queues';
 export * from './memory';
 export * from './ids';
 export * from './schedules';
+export * from './approvals';
+export * from './rag';
+export * from './models';
+export * from './verbose';
+export * from './structured';
+export * from './moderation';
+export * from './warmup';
+export * from './stt';
+export * from './budget';
+export * from './conversations';
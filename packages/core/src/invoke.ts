@@ -1,11 +1,17 @@
 import { spawn, ChildProcess } from 'child_process';
 import fs from 'fs';
 import path from 'path';
-import { AgentConfig, CustomProvider, TeamConfig } from './types';
+import { AgentConfig, AgentFallback, CustomProvider, ResponseFormat, TeamConfig } from './types';
 import { SCRIPT_DIR, resolveModel, getSettings } from './config';
 import { log } from './logging';
 import { ensureAgentDirectory, buildSystemPrompt } from './agent';
-import { getAdapter } from './adapters';
+import { getAdapter, InvokeOptions, TokenUsage } from './adapters';
+import { retrieveContext } from './rag';
+import { loadMemoryIndex, saveMemory } from './memory';
+import { formatInstructions, parseStructured } from './structured';
+
+/** Extra attempts when a structured reply does not validate. */
+const MAX_FORMAT_RETRIES = 2;
 
 // ── Active process tracking ─────────────────────────────────────────────────
 // Tracks the active child process per agent for manual session management.
@@ -23,6 +29,82 @@ export function killAgentProcess(agentId: string): boolean {
     return true;
 }
 
+// ── In-flight invocations ───────────────────────────────────────────────────
+// Each message being processed gets an AbortController so it can be timed out
+// or cancelled (`/stop`, the kill API). Aborting kills the agent's CLI process
+// and aborts HTTP requests to local model servers.
+
+interface InFlight {
+    controller: AbortController;
+    agentId: string;
+    channel: string;
+    sender: string;
+    senderKey: string;
+    startedAt: number;
+    /** Approximate tokens generated so far (streaming providers only). */
+    tokens: number;
+    /** Latest progress note, as sent in `agent:status`. */
+    status?: string;
+}
+
+/** A message being processed, as shown by `/api/status` and `tinyagi status`. */
+export interface InFlightInfo {
+    messageId: string;
+    agentId: string;
+    channel: string;
+    sender: string;
+    elapsedMs: number;
+    tokens: number;
+    status?: string;
+}
+
+const inFlight = new Map<string, InFlight>();
+
+/** Register a message as in flight; returns its controller. */
+export function trackInvocation(messageId: string, info: Pick<InFlight, 'agentId' | 'channel' | 'sender' | 'senderKey'>): AbortController {
+    const controller = new AbortController();
+    inFlight.set(messageId, { ...info, controller, startedAt: Date.now(), tokens: 0 });
+    return controller;
+}
+
+/** Record progress for an in-flight message. */
+export function updateInvocation(messageId: string, progress: { tokens?: number; status?: string }): void {
+    const entry = inFlight.get(messageId);
+    if (entry) Object.assign(entry, progress);
+}
+
+export function untrackInvocation(messageId: string): void {
+    inFlight.delete(messageId);
+}
+
+/** Messages currently being processed, oldest first. */
+export function getInFlightInvocations(): InFlightInfo[] {
+    const now = Date.now();
+    return [...inFlight.entries()]
+        .map(([messageId, e]) => ({
+            messageId, agentId: e.agentId, channel: e.channel, sender: e.sender,
+            elapsedMs: now - e.startedAt, tokens: e.tokens, status: e.status,
+        }))
+        .sort((a, b) => b.elapsedMs - a.elapsedMs);
+}
+
+/**
+ * Abort in-flight messages matching every given field. `reason` becomes the
+ * signal's reason ('cancelled' or 'timeout'). Returns how many were aborted.
+ */
+export function cancelInvocations(match: { agentId?: string; channel?: string; senderKey?: string }, reason = 'cancelled'): number {
+    let count = 0;
+    for (const entry of inFlight.values()) {
+        if (match.agentId && entry.agentId !== match.agentId) continue;
+        if (match.channel && entry.channel !== match.channel) continue;
+        if (match.senderKey && entry.senderKey !== match.senderKey) continue;
+        if (entry.controller.signal.aborted) continue;
+        entry.controller.abort(reason);
+        count++;
+    }
+    return count;
+}
+
 export async function runCommand(command: string, args: string[], cwd?: string, envOverrides?: Record<string, string>): Promise<string> {
     return new Promise((resolve, reject) => {
         const env = { ...process.env, ...envOverrides };
@@ -171,12 +253,37 @@ export function runCommandStreaming(
     };
 }
 
+/** Optional callbacks and controls for invokeAgent. */
+export interface InvokeAgentOptions {
+    /**
+     * Intermediate text events as they arrive (verbose/streaming mode),
+     * tagged with the provider and model that produced them.
+     */
+    onEvent?: (text: string, provider: string, model?: string) => void;
+    /** Token counts, for adapters whose CLI reports them. */
+    onUsage?: (usage: TokenUsage) => void;
+    /** Token-level deltas (Ollama, llama.cpp); onEvent still gets the full text. */
+    onDelta?: (delta: string) => void;
+    /** Lets tools ask the sender a yes/no question before acting. */
+    approve?: (question: string) => Promise<boolean>;
+    /** Aborting stops the current provider and skips the fallbacks. */
+    signal?: AbortSignal;
+    /** Short progress notes (fallbacks, model loading, tools). */
+    onStatus?: (status: string) => void;
+    /**
+     * The reply is validated (see structured.ts), retried up to
+     * MAX_FORMAT_RETRIES times, and returned as compact JSON.
+     */
+    responseFormat?: ResponseFormat;
+}
+
 /**
  * Invoke a single agent with a message. Resolves the provider,
  * delegates to the matching adapter, and returns the raw response text.
  *
- * When `onEvent` is provided, streams intermediate text events as they arrive
- * from the CLI subprocess (verbose/streaming mode).
+ * If the agent's provider fails (including a local model rejecting a prompt
+ * larger than its context), each entry of `agent.fallback` — or
+ * `models.fallback` when the agent has none — is tried in order.
  */
 export async function invokeAgent(
     agent: AgentConfig,
@@ -186,8 +293,10 @@ export async function invokeAgent(
     _shouldReset: boolean,
     agents: Record<string, AgentConfig> = {},
     teams: Record<string, TeamConfig> = {},
-    onEvent?: (text: string) => void,
+    opts: InvokeAgentOptions = {},
 ): Promise<string> {
+    const { onEvent, onUsage, onDelta, approve, signal, onStatus, responseFormat } = opts;
+
     // Ensure agent directory exists with config files
     const agentDir = path.join(workspacePath, agentId);
     const isNewAgent = !fs.existsSync(agentDir);
@@ -198,8 +307,10 @@ export async function invokeAgent(
         shouldReset = true;
     }
 
-    // Build system prompt in-memory (built-in instructions + teammates + memory + user customization)
-    const systemPrompt = buildSystemPrompt(agentId, agentDir, agents, teams, agent.system_prompt, agent.prompt_file);
+    // Build system prompt in-memory (built-in instructions + teammates + memory + user customization
+    // + documents retrieved for this message when RAG is enabled)
+    const systemPrompt = buildSystemPrompt(agentId, agentDir, agents, teams, agent.system_prompt, agent.prompt_file)
+        + await retrieveContext(message);
 
     // Resolve working directory
     const workingDir = agent.working_directory
@@ -208,7 +319,191 @@ export async function invokeAgent(
             : path.join(workspacePath, agent.working_directory))
         : agentDir;
 
-    const rawProvider = agent.provider || 'anthropic';
+    const chain: AgentFallback[] = [
+        { provider: agent.provider || 'anthropic', model: agent.model },
+        ...(agent.fallback ?? getSettings().models?.fallback ?? []),
+    ];
+
+    // CLI adapters run as tracked child processes; HTTP adapters get the signal
+    const onAbort = () => killAgentProcess(agentId);
+    signal?.addEventListener('abort', onAbort, { once: true });
+
+    const runChain = async (text: string, reset: boolean): Promise<string> => {
+        let lastError: Error | undefined;
+        for (let i = 0; i < chain.length; i++) {
+            const { provider, model } = chain[i];
+            // While a fallback remains, hold this attempt's output back: text
+            // already sent to the sender cannot be taken back if it fails
+            const held: string[] = [];
+            const heldDeltas: string[] = [];
+            const hold = i < chain.length - 1;
+            const flushDeltas = () => heldDeltas.splice(0).forEach(d => onDelta!(d));
+            try {
+                const response = await invokeProvider(provider, model || '', {
+                    agentId, message: text, workingDir, agentDir, systemPrompt, shouldReset: reset,
+                    tools: agent.tools,
+                    onEvent: onEvent && ((t) => hold ? held.push(t) : onEvent(t, provider, model)),
+                    onDelta: onDelta && ((d) => hold ? heldDeltas.push(d) : onDelta(d)),
+                    onUsage,
+                    approve,
+                    signal,
+                    onStatus,
+                    responseFormat,
+                });
+                flushDeltas();
+                for (const t of held) onEvent!(t, provider, model);
+                return response;
+            } catch (error) {
+                lastError = error as Error;
+                // Cut short, not failed: what it had so far is still the partial answer
+                if (signal?.aborted) {
+                    flushDeltas();
+                    break;
+                }
+                if (i < chain.length - 1) {
+                    log('WARN', `Provider '${provider}' failed for agent ${agentId}: ${lastError.message} — falling back to '${chain[i + 1].provider}'`);
+                    onStatus?.(`${provider} failed, trying ${chain[i + 1].provider}`);
+                }
+            }
+        }
+        throw lastError;
+    };
+
+    try {
+        if (!responseFormat) return await runChain(message, shouldReset);
+
+        // Structured output: validate the reply and, if it does not parse or
+        // match the schema, tell the model what was wrong and ask again.
+        let response = await runChain(message + formatInstructions(responseFormat), shouldReset);
+        for (let attempt = 0; ; attempt++) {
+            try {
+                return JSON.stringify(parseStructured(response, responseFormat));
+            } catch (error) {
+                if (responseFormat.type === 'grammar') return response;
+                if (attempt >= MAX_FORMAT_RETRIES) {
+                    throw new Error(`Reply was not valid structured output after ${attempt + 1} attempts: ${(error as Error).message}`);
+                }
+                log('WARN', `Structured reply from agent ${agentId} ${(error as Error).message} — retrying`);
+                onStatus?.('Reply was not valid JSON, asking again');
+                response = await runChain(
+                    `Your last reply ${(error as Error).message}.${formatInstructions(responseFormat)}`,
+                    false,
+                );
+            }
+        }
+    } finally {
+        signal?.removeEventListener('abort', onAbort);
+    }
+}
+
+const DEFAULT_REFINE_PROMPT = 'You review draft answers. Check the draft for mistakes, missing steps, and unclear '
+    + 'wording, then reply with the improved final answer only, written to the person who asked. '
+    + 'Do not mention the draft or the review. If the draft is already good, repeat it unchanged.';
+
+/**
+ * Second pass of draft-and-refine mode: give the question and the agent's
+ * draft to `refiner` (default: the agent's own provider and model) and
+ * return its improved answer. Runs as a fresh conversation in its own
+ * directory so CLI session continuation (`-c`, `resume --last`) for the
+ * agent is unaffected.
+ */
+export async function refineResponse(
+    agent: AgentConfig,
+    agentId: string,
+    question: string,
+    draft: string,
+    workspacePath: string,
+    opts: { refiner?: AgentFallback; prompt?: string; onUsage?: (usage: TokenUsage) => void; signal?: AbortSignal } = {},
+): Promise<string> {
+    const { refiner, signal } = opts;
+    const refineId = `${agentId}-refine`;
+    const agentDir = path.join(workspacePath, agentId);
+    const workingDir = path.join(agentDir, '.refine');
+    fs.mkdirSync(workingDir, { recursive: true });
+
+    const onAbort = () => killAgentProcess(refineId);
+    signal?.addEventListener('abort', onAbort, { once: true });
+    try {
+        return await invokeProvider(
+            refiner?.provider || agent.provider || 'anthropic',
+            refiner?.provider ? refiner.model || '' : agent.model || '',
+            {
+                agentId: refineId, workingDir, agentDir,
+                message: `Question:\n${question}\n\nDraft answer:\n${draft}`,
+                systemPrompt: opts.prompt || DEFAULT_REFINE_PROMPT,
+                shouldReset: true,
+                onUsage: opts.onUsage,
+                signal,
+            },
+        );
+    } finally {
+        signal?.removeEventListener('abort', onAbort);
+    }
+}
+
+const EXTRACT_MEMORY_PROMPT = 'You maintain long-term memory about the user. From the exchange below, pick out '
+    + 'lasting facts about the user, their setup, or their preferences (e.g. "home server IP is 10.0.0.5") that '
+    + 'would help in later conversations. Ignore small talk, one-off requests, and anything already remembered '
+    + 'unchanged. To update a remembered fact, reuse its name. Reply with JSON only: '
+    + '{"memories": [{"name": "short-identifier", "summary": "the fact in one line"}]}, or {"memories": []}.';
+
+/**
+ * Automatic memory extraction (memory.auto_extract): ask `extractor`
+ * (default: the agent's own provider and model) for lasting facts in one
+ * exchange and save them with saveMemory. Runs as a fresh conversation in
+ * its own directory, like refineResponse. Returns the saved file paths.
+ */
+export async function extractMemories(
+    agent: AgentConfig,
+    agentId: string,
+    question: string,
+    answer: string,
+    workspacePath: string,
+    opts: { extractor?: AgentFallback; onUsage?: (usage: TokenUsage) => void } = {},
+): Promise<string[]> {
+    const { extractor } = opts;
+    const extractId = `${agentId}-memory`;
+    const agentDir = path.join(workspacePath, agentId);
+    const workingDir = path.join(agentDir, '.memory-extract');
+    fs.mkdirSync(workingDir, { recursive: true });
+
+    const known = loadMemoryIndex(agentDir);
+    const reply = await invokeProvider(
+        extractor?.provider || agent.provider || 'anthropic',
+        extractor?.provider ? extractor.model || '' : agent.model || '',
+        {
+            agentId: extractId, workingDir, agentDir,
+            message: `${known ? `Already remembered:\n${known}\n\n` : ''}User:\n${question}\n\nAssistant:\n${answer}`,
+            systemPrompt: EXTRACT_MEMORY_PROMPT,
+            shouldReset: true,
+            onUsage: opts.onUsage,
+        },
+    );
+
+    const json = reply.match(/\{[\s\S]*\}/);
+    if (!json) return [];
+    let memories: unknown;
+    try {
+        memories = JSON.parse(json[0]).memories;
+    } catch {
+        return [];
+    }
+    if (!Array.isArray(memories)) return [];
+    return memories
+        .filter((m): m is { name: string; summary: string } => typeof m?.name === 'string' && typeof m?.summary === 'string' && !!m.summary.trim())
+        .map(m => saveMemory(agentDir, { name: m.name, summary: m.summary }));
+}
+
+/**
+ * Run one provider: resolve custom providers and stored credentials into
+ * env overrides, resolve the model alias, and call the adapter.
+ */
+async function invokeProvider(
+    rawProvider: string,
+    agentModel: string,
+    opts: Omit<InvokeOptions, 'model' | 'envOverrides'>,
+): Promise<string> {
+    const { agentId } = opts;
 
     // Resolve custom provider if using "custom:<id>" prefix
     let provider = rawProvider;
@@ -249,14 +544,19 @@ export async function invokeAgent(
             envOverrides.ANTHROPIC_API_KEY = settings.models.anthropic.api_key;
         } else if (provider === 'openai' && settings.models?.openai?.api_key) {
             envOverrides.OPENAI_API_KEY = settings.models.openai.api_key;
+        } else if (provider === 'ollama') {
+            const ollama = settings.models?.ollama;
+            if (ollama?.base_url) envOverrides.OLLAMA_HOST = ollama.base_url;
+            if (ollama?.context_length) envOverrides.OLLAMA_CONTEXT_LENGTH = String(ollama.context_length);
+            if (ollama?.keep_alive) envOverrides.OLLAMA_KEEP_ALIVE = ollama.keep_alive;
         }
     }
 
     // Resolve model — custom providers use their own model, otherwise resolve via aliases
-    const effectiveModel = agent.model || customProvider?.model || '';
+    const effectiveModel = agentModel || customProvider?.model || '';
     const model = customProvider
         ? effectiveModel
-        : resolveModel(effectiveModel, provider as 'anthropic' | 'openai' | 'opencode');
+        : resolveModel(effectiveModel, provider);
 
     // Look up the adapter
     const adapter = getAdapter(provider);
@@ -264,14 +564,5 @@ export async function invokeAgent(
         throw new Error(`No adapter registered for provider '${provider}'`);
     }
 
-    return adapter.invoke({
-        agentId,
-        message,
-        workingDir,
-        systemPrompt,
-        model,
-        shouldReset,
-        envOverrides,
-        onEvent,
-    });
+    return adapter.invoke({ ...opts, model, envOverrides });
 }
@@ -1,7 +1,17 @@
 import fs from 'fs';
 import { LOG_FILE } from './config';
 
+const LOG_LEVELS: Record<string, number> = { TRACE: 5, DEBUG: 10, INFO: 20, WARN: 30, ERROR: 40 };
+
+/**
+ * TINYAGI_LOG_LEVEL (trace, debug, info, warn, error) drops lower levels.
+ * The default, debug, writes everything except the per-event trace.
+ */
+const minLevel = LOG_LEVELS[(process.env.TINYAGI_LOG_LEVEL || 'debug').toUpperCase()] ?? LOG_LEVELS.DEBUG;
+
 export function log(level: string, message: string): void {
+    // Levels outside the scale (e.g. custom tags) are always written
+    if ((LOG_LEVELS[level] ?? Infinity) < minLevel) return;
     const timestamp = new Date().toISOString();
     const logMessage = `[${timestamp}] [${level}] ${message}\n`;
     console.log(logMessage.trim());
@@ -25,6 +35,10 @@ export function onEvent(listener: EventListener): void {
  * Emit a structured event — dispatched to in-memory listeners (e.g. SSE broadcast, plugins).
  */
 export function emitEvent(type: string, data: Record<string, unknown>): void {
+    // Token deltas would flood the log
+    if (minLevel <= LOG_LEVELS.TRACE && type !== 'agent:delta') {
+        log('TRACE', `Event ${type} ${JSON.stringify(data).slice(0, 500)}`);
+    }
     for (const listener of eventListeners) {
         try { listener(type, data); } catch { /* never break the queue processor */ }
     }
@@ -129,3 +129,43 @@ export function loadMemoryIndex(agentDir: string): string {
     const tree = formatMemoryTree(root);
     return tree;
 }
+
+function slug(s: string): string {
+    return s.toLowerCase().replace(/[^a-z0-9]+/g, '-').replace(/^-+|-+$/g, '');
+}
+
+function memoryFile(agentDir: string, name: string, category?: string): string {
+    const file = `${slug(name) || 'memory'}.md`;
+    const folder = category ? category.split('/').map(slug).filter(Boolean) : [];
+    return path.join(agentDir, 'memory', ...folder, file);
+}
+
+/**
+ * Write (or overwrite) a memory file in the same format the memory skill
+ * uses, so it shows up in the index. For agents that cannot write files
+ * themselves (the local model backends). Returns the path relative to memory/.
+ */
+export function saveMemory(agentDir: string, entry: { name: string; summary: string; body?: string; category?: string }): string {
+    const file = memoryFile(agentDir, entry.name, entry.category);
+    const name = path.basename(file, '.md');
+    const summary = entry.summary.replace(/\s+/g, ' ').trim();
+    fs.mkdirSync(path.dirname(file), { recursive: true });
+    fs.writeFileSync(file, `---\nname: ${name}\nsummary: ${summary}\n---\n\n${(entry.body || summary).trim()}\n`);
+    return path.relative(path.join(agentDir, 'memory'), file);
+}
+
+/**
+ * Delete the memory whose frontmatter name matches `name`, as given or as
+ * saveMemory stores it ("Home Server" → home-server). Returns false if
+ * there is no such memory.
+ */
+export function deleteMemory(agentDir: string, name: string): boolean {
+    const names = new Set([name, slug(name)]);
+    const find = (folder: MemoryFolder): MemoryEntry | undefined =>
+        folder.entries.find(e => names.has(e.name)) ?? folder.subfolders.map(find).find(Boolean);
+    const memoryDir = path.join(agentDir, 'memory');
+    const entry = find(scanMemoryDir(memoryDir, ''));
+    if (!entry) return false;
+    fs.unlinkSync(path.join(memoryDir, entry.filePath));
+    return true;
+}
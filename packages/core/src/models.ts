@@ -0,0 +1,296 @@
+import crypto from 'crypto';
+import fs from 'fs';
+import os from 'os';
+import path from 'path';
+import { Readable } from 'stream';
+import { pipeline } from 'stream/promises';
+import { TINYAGI_HOME, getSettings, getAgents } from './config';
+import { Settings, AgentFallback } from './types';
+import { readLines } from './adapters/chat-history';
+import { OLLAMA_DEFAULT_MODEL } from './adapters/ollama';
+import bundledCatalog from './model-catalog.json';
+
+/**
+ * Local models: downloads (GGUF files for llama.cpp from a URL or Hugging
+ * Face, and `ollama pull` through the Ollama API), the catalog of suggested
+ * models, and what is installed.
+ */
+
+export const MODELS_DIR = path.join(TINYAGI_HOME, 'models');
+const CATALOG_CACHE = path.join(MODELS_DIR, 'catalog.json');
+
+export interface DownloadProgress {
+    /** What is happening, e.g. "downloading" or "verifying". */
+    status: string;
+    completed: number;
+    /** 0 when the size is unknown. */
+    total: number;
+}
+
+export type ProgressCallback = (progress: DownloadProgress) => void;
+
+/** Resolve `hf:owner/repo/path/file.gguf` to a download URL; other specs pass through. */
+export function resolveModelUrl(spec: string): string {
+    const hf = spec.match(/^hf:([^/]+\/[^/]+)\/(.+)$/);
+    if (hf) return `https://huggingface.co/${hf[1]}/resolve/main/${hf[2]}`;
+    if (/^https?:\/\//.test(spec)) return spec;
+    throw new Error(`Not a model URL: ${spec} (use https://… or hf:owner/repo/file.gguf)`);
+}
+
+/**
+ * Hugging Face serves the SHA-256 of LFS files in the `x-linked-etag` header
+ * of the (pre-redirect) resolve response.
+ */
+async function huggingFaceSha256(url: string): Promise<string | undefined> {
+    if (!url.startsWith('https://huggingface.co/')) return undefined;
+    try {
+        const res = await fetch(url, { method: 'HEAD', redirect: 'manual' });
+        const etag = (res.headers.get('x-linked-etag') || '').replace(/"/g, '');
+        return /^[0-9a-f]{64}$/i.test(etag) ? etag.toLowerCase() : undefined;
+    } catch {
+        return undefined;
+    }
+}
+
+async function sha256File(file: string, onProgress?: ProgressCallback): Promise<string> {
+    const hash = crypto.createHash('sha256');
+    const total = fs.statSync(file).size;
+    let completed = 0;
+    for await (const chunk of fs.createReadStream(file)) {
+        hash.update(chunk as Buffer);
+        completed += (chunk as Buffer).length;
+        onProgress?.({ status: 'verifying', completed, total });
+    }
+    return hash.digest('hex');
+}
+
+/**
+ * Download a model file into MODELS_DIR (or `dest`). An interrupted download
+ * leaves a `.part` file that the next call resumes with a Range request. The
+ * file is checked against `sha256` (or the checksum Hugging Face publishes)
+ * before it is moved into place. Returns the final path.
+ */
+export async function downloadModel(
+    spec: string,
+    opts: { sha256?: string; dest?: string; onProgress?: ProgressCallback } = {},
+): Promise<string> {
+    const url = resolveModelUrl(spec);
+    const dest = opts.dest || path.join(MODELS_DIR, decodeURIComponent(path.basename(new URL(url).pathname)));
+    const part = dest + '.part';
+    fs.mkdirSync(path.dirname(dest), { recursive: true });
+
+    const expected = opts.sha256?.toLowerCase() || await huggingFaceSha256(url);
+    const offset = fs.existsSync(part) ? fs.statSync(part).size : 0;
+    const res = await fetch(url, { headers: offset ? { Range: `bytes=${offset}-` } : {} });
+    if (res.status === 416) {
+        // The .part file is already complete
+    } else if (!res.ok || !res.body) {
+        throw new Error(`Download failed (${res.status} ${res.statusText}): ${url}`);
+    } else {
+        // 200 means the server ignored the Range header: start over
+        const resumed = res.status === 206;
+        const completedStart = resumed ? offset : 0;
+        const total = Number(res.headers.get('content-length') || 0);
+        let completed = completedStart;
+        const body = Readable.fromWeb(res.body as any);
+        body.on('data', (chunk: Buffer) => {
+            completed += chunk.length;
+            opts.onProgress?.({ status: 'downloading', completed, total: total ? total + completedStart : 0 });
+        });
+        await pipeline(body, fs.createWriteStream(part, { flags: resumed ? 'a' : 'w' }));
+    }
+
+    if (expected) {
+        const actual = await sha256File(part, opts.onProgress);
+        if (actual !== expected) {
+            fs.unlinkSync(part);
+            throw new Error(`Checksum mismatch for ${path.basename(dest)}: expected ${expected}, got ${actual}`);
+        }
+    }
+    fs.renameSync(part, dest);
+    return dest;
+}
+
+function ollamaBaseUrl(): string {
+    const settings = getSettings();
+    return (settings.models?.ollama?.base_url || process.env.OLLAMA_HOST || 'http://localhost:11434').replace(/\/+$/, '');
+}
+
+/** Pull a model into Ollama via its API (same as `ollama pull`). */
+export async function pullOllamaModel(name: string, onProgress?: ProgressCallback): Promise<void> {
+    const baseUrl = ollamaBaseUrl();
+    let res: Response;
+    try {
+        res = await fetch(`${baseUrl}/api/pull`, {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify({ model: name, stream: true }),
+        });
+    } catch (error) {
+        throw new Error(`Could not reach Ollama at ${baseUrl}: ${(error as Error).message}`);
+    }
+    if (!res.ok) {
+        throw new Error(`Ollama returned ${res.status}: ${(await res.text()).trim() || res.statusText}`);
+    }
+    await readLines(res, (line) => {
+        let json: any;
+        try { json = JSON.parse(line); } catch { return; }
+        if (json.error) throw new Error(`Ollama pull failed: ${json.error}`);
+        onProgress?.({ status: json.status || '', completed: json.completed || 0, total: json.total || 0 });
+    });
+}
+
+// ── Catalog ─────────────────────────────────────────────────────────────────
+
+export interface CatalogModel {
+    id: string;
+    label: string;
+    /** Defaults to 'chat'. */
+    kind?: 'chat' | 'embedding';
+    /** Ollama tag, e.g. `qwen3:8b`. */
+    ollama?: string;
+    /** GGUF download spec for llama.cpp (URL or `hf:owner/repo/file`). */
+    gguf?: string;
+    quantization?: string;
+    size_gb?: number;
+    ram_gb?: number;
+    license?: string;
+    /** Whether the model handles tool calling well. */
+    tools?: boolean;
+}
+
+/** The refreshed catalog if one was fetched, otherwise the bundled one. */
+export function getModelCatalog(): CatalogModel[] {
+    try {
+        const cached = JSON.parse(fs.readFileSync(CATALOG_CACHE, 'utf8'));
+        if (Array.isArray(cached) && cached.length > 0) return cached;
+    } catch {
+        // Fall back to the bundled catalog
+    }
+    return bundledCatalog as CatalogModel[];
+}
+
+/** Look up a catalog entry by id or Ollama tag. */
+export function findCatalogModel(name: string): CatalogModel | undefined {
+    return getModelCatalog().find(m => m.id === name || m.ollama === name);
+}
+
+/** Fetch `models.catalog_url` and cache it; returns the number of entries. */
+export async function refreshModelCatalog(): Promise<number> {
+    const url = getSettings().models?.catalog_url;
+    if (!url) throw new Error('models.catalog_url is not set');
+    const res = await fetch(url, { signal: AbortSignal.timeout(15_000) });
+    if (!res.ok) throw new Error(`Catalog fetch failed (${res.status} ${res.statusText}): ${url}`);
+    const data = await res.json();
+    if (!Array.isArray(data) || !data.every((m: any) => typeof m?.id === 'string' && typeof m?.label === 'string')) {
+        throw new Error('Catalog must be a JSON array of { id, label, ... } entries');
+    }
+    fs.mkdirSync(MODELS_DIR, { recursive: true });
+    fs.writeFileSync(CATALOG_CACHE, JSON.stringify(data, null, 2));
+    return data.length;
+}
+
+// ── Installed models ────────────────────────────────────────────────────────
+
+export interface InstalledModel {
+    backend: 'ollama' | 'llamacpp';
+    /** Ollama tag or GGUF file name. */
+    name: string;
+    path?: string;
+    bytes: number;
+}
+
+/** GGUF files in MODELS_DIR plus the configured llama.cpp model_path. */
+export function getInstalledGgufModels(): InstalledModel[] {
+    const files = new Set<string>();
+    if (fs.existsSync(MODELS_DIR)) {
+        for (const name of fs.readdirSync(MODELS_DIR)) {
+            if (name.endsWith('.gguf')) files.add(path.join(MODELS_DIR, name));
+        }
+    }
+    const configured = getSettings().models?.llamacpp?.model_path;
+    if (configured && fs.existsSync(configured)) files.add(path.resolve(configured));
+
+    return [...files].map(file => ({
+        backend: 'llamacpp' as const,
+        name: path.basename(file),
+        path: file,
+        bytes: fs.statSync(file).size,
+    }));
+}
+
+/** Models the Ollama server has pulled; throws if Ollama is not reachable. */
+export async function getInstalledOllamaModels(): Promise<InstalledModel[]> {
+    const baseUrl = ollamaBaseUrl();
+    const res = await fetch(`${baseUrl}/api/tags`, { signal: AbortSignal.timeout(5_000) });
+    if (!res.ok) throw new Error(`Ollama returned ${res.status}`);
+    const json: any = await res.json();
+    return (json.models || []).map((m: any) => ({ backend: 'ollama' as const, name: m.name, bytes: m.size || 0 }));
+}
+
+/** Remove a model from Ollama (same as `ollama rm`). */
+export async function deleteOllamaModel(name: string): Promise<void> {
+    const baseUrl = ollamaBaseUrl();
+    const res = await fetch(`${baseUrl}/api/delete`, {
+        method: 'DELETE',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify({ model: name }),
+    });
+    if (!res.ok) throw new Error(`Ollama returned ${res.status}: ${(await res.text()).trim() || res.statusText}`);
+}
+
+/**
+ * Where Ollama keeps its blobs, if the server is local: OLLAMA_MODELS or
+ * ~/.ollama/models. Null for a remote server.
+ */
+export function getOllamaModelsDir(): string | null {
+    const host = new URL(ollamaBaseUrl()).hostname;
+    if (!['localhost', '127.0.0.1', '::1', '[::1]'].includes(host)) return null;
+    return process.env.OLLAMA_MODELS || path.join(os.homedir(), '.ollama', 'models');
+}
+
+/** Ollama tags without a tag are `:latest`. */
+function normalizeTag(name: string): string {
+    return name.includes(':') ? name : `${name}:latest`;
+}
+
+/**
+ * Local models the settings refer to: agent and fallback models, routing and
+ * refine overrides, the memory extractor, RAG embeddings, the moderation
+ * classifier, and the llama.cpp GGUF files. Anything else installed can be pruned.
+ */
+export function getModelsInUse(settings: Settings): { ollama: Set<string>; files: Set<string> } {
+    const ollama = new Set<string>();
+    const files = new Set<string>();
+    const defaultOllama = settings.models?.ollama?.model;
+    const addRef = (ref?: AgentFallback | { provider?: string; model?: string }) => {
+        if (ref?.provider !== 'ollama') return;
+        ollama.add(normalizeTag(ref.model || defaultOllama || OLLAMA_DEFAULT_MODEL));
+    };
+
+    if (defaultOllama) ollama.add(normalizeTag(defaultOllama));
+    // getAgents: with no agents configured, the default agent from models.provider
+    for (const agent of Object.values(getAgents(settings))) {
+        addRef(agent);
+        agent.fallback?.forEach(addRef);
+    }
+    settings.models?.fallback?.forEach(addRef);
+    Object.values(settings.routing?.channels ?? {}).forEach(addRef);
+    Object.values(settings.routing?.tags ?? {}).forEach(addRef);
+    Object.values(settings.refine?.channels ?? {}).forEach(addRef);
+    if (settings.memory?.auto_extract) addRef(settings.memory);
+    if (settings.rag?.enabled) ollama.add(normalizeTag(settings.rag.embed_model || 'nomic-embed-text'));
+    if (settings.moderation?.classifier?.model) ollama.add(normalizeTag(settings.moderation.classifier.model));
+
+    for (const file of [settings.models?.llamacpp?.model_path, settings.models?.llamacpp?.mmproj_path]) {
+        if (file) files.add(path.resolve(file));
+    }
+    return { ollama, files };
+}
+
+/** Whether an installed model is referenced by the settings. */
+export function isModelInUse(model: InstalledModel, inUse: ReturnType<typeof getModelsInUse>): boolean {
+    return model.backend === 'ollama'
+        ? inUse.ollama.has(normalizeTag(model.name))
+        : !!model.path && inUse.files.has(path.resolve(model.path));
+}
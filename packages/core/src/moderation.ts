@@ -0,0 +1,129 @@
+import { getSettings } from './config';
+import { log, emitEvent } from './logging';
+import { recordModeration } from './queues';
+import { ModerationRule } from './types';
+
+/**
+ * Moderation: incoming messages and agent replies are checked against the
+ * keyword / regex rules in `moderation.rules` and, optionally, a local
+ * safety classifier (e.g. llama-guard3 on Ollama). A match either blocks the
+ * text or redacts the matching part; every match is written to the
+ * moderation_log table.
+ */
+
+export type ModerationDirection = 'incoming' | 'outgoing';
+
+export interface ModerationContext {
+    channel: string;
+    sender: string;
+    messageId: string;
+    /** The user's message, given to the classifier alongside a reply. */
+    originalMessage?: string;
+}
+
+export interface ModerationResult {
+    text: string;
+    blocked: boolean;
+}
+
+const DEFAULT_BLOCKED_REPLY = 'Sorry, I can\'t help with that.';
+const REDACTED = '[redacted]';
+
+function appliesTo(applyTo: ModerationRule['apply_to'], direction: ModerationDirection): boolean {
+    return !applyTo || applyTo === 'both' || applyTo === direction;
+}
+
+function escapeRegex(text: string): string {
+    return text.replace(/[.*+?^${}()|[\]\\]/g, '\\$&');
+}
+
+/** One case-insensitive regex per rule; keywords match whole words. */
+function ruleRegex(rule: ModerationRule): RegExp | null {
+    const parts: string[] = [];
+    if (rule.pattern) parts.push(`(?:${rule.pattern})`);
+    for (const keyword of rule.keywords || []) {
+        if (keyword.trim()) parts.push(`\\b${escapeRegex(keyword.trim())}\\b`);
+    }
+    if (parts.length === 0) return null;
+    try {
+        return new RegExp(parts.join('|'), 'gi');
+    } catch (error) {
+        log('WARN', `Moderation rule '${rule.name || rule.pattern}' has an invalid pattern: ${(error as Error).message}`);
+        return null;
+    }
+}
+
+/** What the sender sees instead of a blocked message or reply. */
+export function blockedReply(): string {
+    return getSettings().moderation?.blocked_reply || DEFAULT_BLOCKED_REPLY;
+}
+
+/**
+ * Ask a llama-guard style model (through Ollama) whether the text is safe.
+ * Returns the violated categories, or null when it is safe. Errors let the
+ * text through so a stopped classifier does not take the agents down.
+ */
+async function classify(text: string, direction: ModerationDirection, ctx: ModerationContext): Promise<string | null> {
+    const settings = getSettings();
+    const cfg = settings.moderation?.classifier;
+    if (!cfg?.model || !appliesTo(cfg.apply_to, direction)) return null;
+
+    const baseUrl = (cfg.base_url || settings.models?.ollama?.base_url || process.env.OLLAMA_HOST || 'http://localhost:11434').replace(/\/+$/, '');
+    const messages = direction === 'outgoing' && ctx.originalMessage
+        ? [{ role: 'user', content: ctx.originalMessage }, { role: 'assistant', content: text }]
+        : [{ role: 'user', content: text }];
+    try {
+        const res = await fetch(`${baseUrl}/api/chat`, {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify({ model: cfg.model, messages, stream: false }),
+            signal: AbortSignal.timeout(30_000),
+        });
+        if (!res.ok) throw new Error(`Ollama returned ${res.status}`);
+        const verdict = String((await res.json() as any).message?.content || '').trim();
+        // llama-guard answers "safe", or "unsafe" followed by category codes
+        if (!/^unsafe\b/i.test(verdict)) return null;
+        return verdict.replace(/^unsafe\s*/i, '').replace(/\s+/g, ' ') || 'unsafe';
+    } catch (error) {
+        log('WARN', `Moderation classifier failed, letting ${direction} message ${ctx.messageId} through: ${(error as Error).message}`);
+        return null;
+    }
+}
+
+/**
+ * Check text against the moderation policy. Returns the (possibly
+ * redacted) text and whether it must be blocked. With no `moderation`
+ * settings this returns the text unchanged.
+ */
+export async function moderate(text: string, direction: ModerationDirection, ctx: ModerationContext): Promise<ModerationResult> {
+    const cfg = getSettings().moderation;
+    if (!cfg || cfg.enabled === false) return { text, blocked: false };
+
+    const audit = (action: 'block' | 'redact', rule: string) => {
+        log('WARN', `Moderation ${action} (${direction}, rule: ${rule}) on message ${ctx.messageId} from ${ctx.channel}/${ctx.sender}`);
+        recordModeration({ ...ctx, direction, action, rule, content: text });
+        emitEvent('moderation:filtered', { messageId: ctx.messageId, channel: ctx.channel, direction, action, rule });
+    };
+
+    let result = text;
+    for (const rule of cfg.rules || []) {
+        if (!appliesTo(rule.apply_to, direction)) continue;
+        const regex = ruleRegex(rule);
+        if (!regex || !regex.test(result)) continue;
+        const name = rule.name || rule.pattern || (rule.keywords || []).join(',');
+        if (rule.action === 'redact') {
+            audit('redact', name);
+            result = result.replace(regex, REDACTED);
+        } else {
+            audit('block', name);
+            return { text: blockedReply(), blocked: true };
+        }
+    }
+
+    const categories = await classify(result, direction, ctx);
+    if (categories) {
+        audit('block', `classifier: ${categories}`);
+        return { text: blockedReply(), blocked: true };
+    }
+    return { text: result, blocked: false };
+}
@@ -28,6 +28,7 @@ export function initQueueDb(): void {
             channel TEXT NOT NULL, sender TEXT NOT NULL, sender_id TEXT,
             message TEXT NOT NULL, agent TEXT,
             from_agent TEXT,
+            response_format TEXT,
             status TEXT NOT NULL DEFAULT 'pending',
             retry_count INTEGER NOT NULL DEFAULT 0, last_error TEXT,
             created_at INTEGER NOT NULL, updated_at INTEGER NOT NULL
@@ -54,12 +55,71 @@ export function initQueueDb(): void {
             sender TEXT NOT NULL,
             message_id TEXT NOT NULL,
             content TEXT NOT NULL,
+            provider TEXT,
+            created_at INTEGER NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS usage (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            message_id TEXT NOT NULL,
+            agent_id TEXT NOT NULL,
+            channel TEXT NOT NULL,
+            sender TEXT NOT NULL,
+            provider TEXT, model TEXT,
+            input_tokens INTEGER NOT NULL DEFAULT 0,
+            output_tokens INTEGER NOT NULL DEFAULT 0,
+            duration_ms INTEGER NOT NULL DEFAULT 0,
+            created_at INTEGER NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS rag_chunks (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            source TEXT NOT NULL,
+            chunk_index INTEGER NOT NULL,
+            content TEXT NOT NULL,
+            embedding BLOB NOT NULL,
+            mtime INTEGER NOT NULL,
+            created_at INTEGER NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS transcripts (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            message_id TEXT NOT NULL,
+            agent_id TEXT NOT NULL,
+            channel TEXT NOT NULL,
+            sender TEXT NOT NULL,
+            provider TEXT, model TEXT,
+            prompt TEXT NOT NULL,
+            response TEXT NOT NULL,
+            input_tokens INTEGER NOT NULL DEFAULT 0,
+            output_tokens INTEGER NOT NULL DEFAULT 0,
+            duration_ms INTEGER NOT NULL DEFAULT 0,
+            created_at INTEGER NOT NULL
+        );
+        CREATE VIRTUAL TABLE IF NOT EXISTS transcripts_fts USING fts5(
+            prompt, response, content='transcripts', content_rowid='id'
+        );
+        CREATE TRIGGER IF NOT EXISTS transcripts_ai AFTER INSERT ON transcripts BEGIN
+            INSERT INTO transcripts_fts(rowid, prompt, response) VALUES (new.id, new.prompt, new.response);
+        END;
+        CREATE TRIGGER IF NOT EXISTS transcripts_ad AFTER DELETE ON transcripts BEGIN
+            INSERT INTO transcripts_fts(transcripts_fts, rowid, prompt, response) VALUES ('delete', old.id, old.prompt, old.response);
+        END;
+        CREATE TABLE IF NOT EXISTS moderation_log (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            message_id TEXT NOT NULL,
+            channel TEXT NOT NULL,
+            sender TEXT NOT NULL,
+            direction TEXT NOT NULL,
+            action TEXT NOT NULL,
+            rule TEXT NOT NULL,
+            content TEXT NOT NULL,
             created_at INTEGER NOT NULL
         );
         CREATE INDEX IF NOT EXISTS idx_msg_status ON messages(status, agent, created_at);
         CREATE INDEX IF NOT EXISTS idx_resp_channel ON responses(channel, status);
         CREATE INDEX IF NOT EXISTS idx_chat_team ON chat_messages(team_id, id);
         CREATE INDEX IF NOT EXISTS idx_agent_messages_agent ON agent_messages(agent_id, created_at);
+        CREATE INDEX IF NOT EXISTS idx_usage_created ON usage(created_at);
+        CREATE INDEX IF NOT EXISTS idx_rag_source ON rag_chunks(source);
+        CREATE INDEX IF NOT EXISTS idx_transcripts_created ON transcripts(created_at);
     `);
 
     // Migrations for existing databases
@@ -74,6 +134,13 @@ export function initQueueDb(): void {
     if (msgCols.some(c => c.name === 'conversation_id')) {
         db.exec('ALTER TABLE messages DROP COLUMN conversation_id');
     }
+    if (!msgCols.some(c => c.name === 'response_format')) {
+        db.exec('ALTER TABLE messages ADD COLUMN response_format TEXT');
+    }
+    const agentMsgCols = db.prepare("PRAGMA table_info(agent_messages)").all() as { name: string }[];
+    if (!agentMsgCols.some(c => c.name === 'provider')) {
+        db.exec('ALTER TABLE agent_messages ADD COLUMN provider TEXT');
+    }
 }
 
 function getDb(): Database.Database {
@@ -87,10 +154,11 @@ export function enqueueMessage(data: MessageJobData): number | null {
     const now = Date.now();
     try {
         const r = getDb().prepare(
-            `INSERT INTO messages (message_id,channel,sender,sender_id,message,agent,from_agent,status,created_at,updated_at)
-             VALUES (?,?,?,?,?,?,?,'pending',?,?)`
+            `INSERT INTO messages (message_id,channel,sender,sender_id,message,agent,from_agent,response_format,status,created_at,updated_at)
+             VALUES (?,?,?,?,?,?,?,?,'pending',?,?)`
         ).run(data.messageId, data.channel, data.sender, data.senderId ?? null, data.message,
-            data.agent ?? null, data.fromAgent ?? null, now, now);
+            data.agent ?? null, data.fromAgent ?? null,
+            data.responseFormat ? JSON.stringify(data.responseFormat) : null, now, now);
         queueEvents.emit('message:enqueued', { id: r.lastInsertRowid, agent: data.agent });
         return r.lastInsertRowid as number;
     } catch (err: any) {
@@ -129,13 +197,20 @@ export function completeMessage(rowId: number): void {
     getDb().prepare(`UPDATE messages SET status='completed',updated_at=? WHERE id=?`).run(Date.now(), rowId);
 }
 
-export function failMessage(rowId: number, error: string): void {
+export function failMessage(rowId: number, error: string): 'pending' | 'dead' | undefined {
     const d = getDb();
     const msg = d.prepare('SELECT retry_count FROM messages WHERE id=?').get(rowId) as { retry_count: number } | undefined;
-    if (!msg) return;
+    if (!msg) return undefined;
     const newStatus = msg.retry_count + 1 >= MAX_RETRIES ? 'dead' : 'pending';
     d.prepare(`UPDATE messages SET status=?,retry_count=?,last_error=?,updated_at=? WHERE id=?`)
         .run(newStatus, msg.retry_count + 1, error, Date.now(), rowId);
+    return newStatus;
+}
+
+/** Move a message straight to the dead-letter queue (no retries), e.g. after a timeout. */
+export function deadLetterMessage(rowId: number, error: string): void {
+    getDb().prepare(`UPDATE messages SET status='dead',last_error=?,updated_at=? WHERE id=?`)
+        .run(error, Date.now(), rowId);
 }
 
 export function getProcessingMessages(): any[] {
@@ -204,10 +279,32 @@ export function deleteDeadMessage(rowId: number): boolean {
     return getDb().prepare(`DELETE FROM messages WHERE id=? AND status='dead'`).run(rowId).changes > 0;
 }
 
+/** Messages waiting or in progress, oldest first. */
+export function getQueuedMessages(): any[] {
+    return getDb().prepare(`SELECT * FROM messages WHERE status IN ('pending','queued','processing') ORDER BY created_at`).all();
+}
+
+/** Look a message up by row id or message_id. */
+export function getQueueMessage(id: string): any | undefined {
+    const d = getDb();
+    if (/^\d+$/.test(id)) return d.prepare(`SELECT * FROM messages WHERE id=?`).get(Number(id));
+    return d.prepare(`SELECT * FROM messages WHERE message_id=?`).get(id);
+}
+
+/** Delete pending (or dead) messages created before `olderThanMs` ago. */
+export function purgeMessages(status: 'pending' | 'dead', olderThanMs = 0): number {
+    return getDb().prepare(`DELETE FROM messages WHERE status=? AND created_at<=?`).run(status, Date.now() - olderThanMs).changes;
+}
+
 export function pruneAckedResponses(olderThanMs = 86400000): number {
     return getDb().prepare(`DELETE FROM responses WHERE status='acked' AND acked_at<?`).run(Date.now() - olderThanMs).changes;
 }
 
+/** Delete responses on `channel`, delivered or not, created before `olderThanMs` ago. */
+export function pruneChannelResponses(channel: string, olderThanMs: number): number {
+    return getDb().prepare(`DELETE FROM responses WHERE channel=? AND created_at<?`).run(channel, Date.now() - olderThanMs).changes;
+}
+
 export function pruneCompletedMessages(olderThanMs = 86400000): number {
     return getDb().prepare(`DELETE FROM messages WHERE status='completed' AND updated_at<?`).run(Date.now() - olderThanMs).changes;
 }
@@ -217,10 +314,11 @@ export function pruneCompletedMessages(olderThanMs = 86400000): number {
 export function insertAgentMessage(data: {
     agentId: string; role: 'user' | 'assistant';
     channel: string; sender: string; messageId: string; content: string;
+    provider?: string;
 }): number {
     return getDb().prepare(
-        `INSERT INTO agent_messages (agent_id,role,channel,sender,message_id,content,created_at) VALUES (?,?,?,?,?,?,?)`
-    ).run(data.agentId, data.role, data.channel, data.sender, data.messageId, data.content, Date.now()).lastInsertRowid as number;
+        `INSERT INTO agent_messages (agent_id,role,channel,sender,message_id,content,provider,created_at) VALUES (?,?,?,?,?,?,?,?)`
+    ).run(data.agentId, data.role, data.channel, data.sender, data.messageId, data.content, data.provider ?? null, Date.now()).lastInsertRowid as number;
 }
 
 export function getAgentMessages(agentId: string, limit = 100): any[] {
@@ -235,6 +333,230 @@ export function getAllAgentMessages(limit = 100): any[] {
     ).all(limit);
 }
 
+// ── Usage accounting ────────────────────────────────────────────────────────
+
+export type UsageGroupBy = 'sender' | 'channel' | 'agent';
+
+const USAGE_GROUP_COLUMNS: Record<UsageGroupBy, string> = {
+    sender: "channel || ':' || sender",
+    channel: 'channel',
+    agent: 'agent_id',
+};
+
+export function recordUsage(data: {
+    messageId: string; agentId: string; channel: string; sender: string;
+    provider?: string; model?: string;
+    inputTokens?: number; outputTokens?: number; durationMs: number;
+}): number {
+    return getDb().prepare(
+        `INSERT INTO usage (message_id,agent_id,channel,sender,provider,model,input_tokens,output_tokens,duration_ms,created_at)
+         VALUES (?,?,?,?,?,?,?,?,?,?)`
+    ).run(data.messageId, data.agentId, data.channel, data.sender, data.provider ?? null, data.model ?? null,
+        data.inputTokens ?? 0, data.outputTokens ?? 0, Math.round(data.durationMs), Date.now()).lastInsertRowid as number;
+}
+
+export interface UsageSummaryRow {
+    name: string;
+    messages: number;
+    inputTokens: number;
+    outputTokens: number;
+    inferenceSeconds: number;
+}
+
+export function getUsageSummary(groupBy: UsageGroupBy = 'sender', since = 0): UsageSummaryRow[] {
+    const column = USAGE_GROUP_COLUMNS[groupBy];
+    if (!column) throw new Error(`Unknown usage grouping: ${groupBy}`);
+    return getDb().prepare(
+        `SELECT ${column} as name,
+                COUNT(*) as messages,
+                SUM(input_tokens) as inputTokens,
+                SUM(output_tokens) as outputTokens,
+                ROUND(SUM(duration_ms) / 1000.0, 1) as inferenceSeconds
+         FROM usage WHERE created_at>=? GROUP BY name ORDER BY inferenceSeconds DESC`
+    ).all(since) as UsageSummaryRow[];
+}
+
+/** The most recent invocation, for the last-inference line in status. */
+export function getLastUsage(): { agentId: string; provider: string | null; model: string | null; durationMs: number; at: number } | undefined {
+    return getDb().prepare(
+        `SELECT agent_id as agentId, provider, model, duration_ms as durationMs, created_at as at
+         FROM usage ORDER BY id DESC LIMIT 1`
+    ).get() as { agentId: string; provider: string | null; model: string | null; durationMs: number; at: number } | undefined;
+}
+
+/** Tokens and inference time for all usage since `since` (ms epoch). */
+export function getUsageTotals(since: number): { tokens: number; inferenceSeconds: number } {
+    return getDb().prepare(
+        `SELECT COALESCE(SUM(input_tokens + output_tokens), 0) as tokens,
+                COALESCE(SUM(duration_ms), 0) / 1000.0 as inferenceSeconds
+         FROM usage WHERE created_at>=?`
+    ).get(since) as { tokens: number; inferenceSeconds: number };
+}
+
+// ── Transcripts ─────────────────────────────────────────────────────────────
+// Every prompt / response pair, full-text indexed (FTS5) for search.
+
+export interface TranscriptRow {
+    id: number;
+    message_id: string;
+    agent_id: string;
+    channel: string;
+    sender: string;
+    provider: string | null;
+    model: string | null;
+    prompt: string;
+    response: string;
+    input_tokens: number;
+    output_tokens: number;
+    duration_ms: number;
+    created_at: number;
+    /** Search results only: matching excerpt with hits wrapped in [ ]. */
+    snippet?: string;
+}
+
+export interface TranscriptFilter {
+    messageId?: string;
+    agentId?: string;
+    channel?: string;
+    since?: number;
+    limit?: number;
+    offset?: number;
+}
+
+export function recordTranscript(data: {
+    messageId: string; agentId: string; channel: string; sender: string;
+    provider?: string; model?: string; prompt: string; response: string;
+    inputTokens?: number; outputTokens?: number; durationMs: number;
+}): number {
+    return getDb().prepare(
+        `INSERT INTO transcripts (message_id,agent_id,channel,sender,provider,model,prompt,response,input_tokens,output_tokens,duration_ms,created_at)
+         VALUES (?,?,?,?,?,?,?,?,?,?,?,?)`
+    ).run(data.messageId, data.agentId, data.channel, data.sender, data.provider ?? null, data.model ?? null,
+        data.prompt, data.response, data.inputTokens ?? 0, data.outputTokens ?? 0,
+        Math.round(data.durationMs), Date.now()).lastInsertRowid as number;
+}
+
+/**
+ * Insert a transcript exported from another instance, keeping its timestamp.
+ * Returns false if the same exchange is already recorded.
+ */
+export function importTranscript(row: Omit<TranscriptRow, 'id' | 'snippet'>): boolean {
+    return getDb().prepare(
+        `INSERT INTO transcripts (message_id,agent_id,channel,sender,provider,model,prompt,response,input_tokens,output_tokens,duration_ms,created_at)
+         SELECT ?,?,?,?,?,?,?,?,?,?,?,?
+         WHERE NOT EXISTS (SELECT 1 FROM transcripts WHERE message_id=? AND agent_id=? AND created_at=?)`
+    ).run(row.message_id, row.agent_id, row.channel, row.sender, row.provider, row.model, row.prompt, row.response,
+        row.input_tokens, row.output_tokens, row.duration_ms, row.created_at,
+        row.message_id, row.agent_id, row.created_at).changes > 0;
+}
+
+function transcriptWhere(filter: TranscriptFilter): { sql: string; params: unknown[] } {
+    const clauses: string[] = [];
+    const params: unknown[] = [];
+    if (filter.messageId) { clauses.push('t.message_id=?'); params.push(filter.messageId); }
+    if (filter.agentId) { clauses.push('t.agent_id=?'); params.push(filter.agentId); }
+    if (filter.channel) { clauses.push('t.channel=?'); params.push(filter.channel); }
+    if (filter.since) { clauses.push('t.created_at>=?'); params.push(filter.since); }
+    return { sql: clauses.map(c => ` AND ${c}`).join(''), params };
+}
+
+/** Newest transcripts first. A negative `limit` returns all of them. */
+export function listTranscripts(filter: TranscriptFilter = {}): TranscriptRow[] {
+    const where = transcriptWhere(filter);
+    return getDb().prepare(
+        `SELECT t.* FROM transcripts t WHERE 1=1${where.sql} ORDER BY t.id DESC LIMIT ? OFFSET ?`
+    ).all(...where.params, filter.limit ?? 20, filter.offset ?? 0) as TranscriptRow[];
+}
+
+/**
+ * Full-text search over prompts and responses, best matches first. Each word
+ * of `query` is matched literally (FTS operators are not interpreted).
+ */
+export function searchTranscripts(query: string, filter: TranscriptFilter = {}): TranscriptRow[] {
+    const terms = query.split(/\s+/).filter(Boolean).map(t => `"${t.replace(/"/g, '""')}"`);
+    if (terms.length === 0) return [];
+    const where = transcriptWhere(filter);
+    return getDb().prepare(
+        `SELECT t.*, snippet(transcripts_fts, -1, '[', ']', '…', 16) as snippet
+         FROM transcripts_fts JOIN transcripts t ON t.id = transcripts_fts.rowid
+         WHERE transcripts_fts MATCH ?${where.sql}
+         ORDER BY rank LIMIT ? OFFSET ?`
+    ).all(terms.join(' '), ...where.params, filter.limit ?? 20, filter.offset ?? 0) as TranscriptRow[];
+}
+
+// ── Moderation log ──────────────────────────────────────────────────────────
+
+export interface ModerationLogRow {
+    id: number;
+    message_id: string;
+    channel: string;
+    sender: string;
+    direction: string;
+    action: string;
+    rule: string;
+    content: string;
+    created_at: number;
+}
+
+export function recordModeration(data: {
+    messageId: string; channel: string; sender: string;
+    direction: string; action: string; rule: string; content: string;
+}): number {
+    return getDb().prepare(
+        `INSERT INTO moderation_log (message_id,channel,sender,direction,action,rule,content,created_at)
+         VALUES (?,?,?,?,?,?,?,?)`
+    ).run(data.messageId, data.channel, data.sender, data.direction, data.action, data.rule, data.content, Date.now()).lastInsertRowid as number;
+}
+
+export function getModerationLog(limit = 100): ModerationLogRow[] {
+    return getDb().prepare(`SELECT * FROM moderation_log ORDER BY id DESC LIMIT ?`).all(limit) as ModerationLogRow[];
+}
+
+// ── RAG chunks ──────────────────────────────────────────────────────────────
+
+export interface RagChunk {
+    source: string;
+    content: string;
+    embedding: Float32Array;
+}
+
+/** Replace all chunks for a source (file path or note id) in one transaction. */
+export function replaceRagSource(source: string, mtime: number, chunks: { content: string; embedding: number[] }[]): void {
+    const d = getDb();
+    const insert = d.prepare(
+        `INSERT INTO rag_chunks (source,chunk_index,content,embedding,mtime,created_at) VALUES (?,?,?,?,?,?)`
+    );
+    d.transaction(() => {
+        d.prepare(`DELETE FROM rag_chunks WHERE source=?`).run(source);
+        const now = Date.now();
+        chunks.forEach((c, i) => {
+            insert.run(source, i, c.content, Buffer.from(new Float32Array(c.embedding).buffer), mtime, now);
+        });
+    })();
+}
+
+export function deleteRagSource(source: string): number {
+    return getDb().prepare(`DELETE FROM rag_chunks WHERE source=?`).run(source).changes;
+}
+
+/** Indexed sources with their stored mtime and chunk count. */
+export function getRagSources(): { source: string; mtime: number; chunks: number }[] {
+    return getDb().prepare(
+        `SELECT source, MAX(mtime) as mtime, COUNT(*) as chunks FROM rag_chunks GROUP BY source ORDER BY source`
+    ).all() as { source: string; mtime: number; chunks: number }[];
+}
+
+export function getAllRagChunks(): RagChunk[] {
+    const rows = getDb().prepare(`SELECT source, content, embedding FROM rag_chunks`).all() as
+        { source: string; content: string; embedding: Buffer }[];
+    return rows.map(r => ({
+        source: r.source,
+        content: r.content,
+        // Copy first: the Buffer's offset into its pool may not be 4-byte aligned
+        embedding: new Float32Array(new Uint8Array(r.embedding).buffer),
+    }));
+}
+
 // ── Chat messages ───────────────────────────────────────────────────────────
 
 export function insertChatMessage(teamId: string, fromAgent: string, message: string): number {
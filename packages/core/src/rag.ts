@@ -0,0 +1,230 @@
+import fs from 'fs';
+import path from 'path';
+import { getSettings } from './config';
+import { log } from './logging';
+import { replaceRagSource, deleteRagSource, getRagSources, getAllRagChunks } from './queues';
+
+/**
+ * Local retrieval-augmented generation: documents are split into chunks,
+ * embedded with a local Ollama embedding model, stored in SQLite, and the
+ * closest chunks are added to the agent's system prompt for each message.
+ */
+
+const DEFAULT_EMBED_MODEL = 'nomic-embed-text';
+const DEFAULT_TOP_K = 4;
+const DEFAULT_MIN_SCORE = 0.35;
+const CHUNK_CHARS = 1200;
+const CHUNK_OVERLAP = 200;
+const MAX_FILE_BYTES = 2 * 1024 * 1024;
+
+const TEXT_EXTENSIONS = new Set([
+    '.md', '.markdown', '.txt', '.rst', '.org', '.csv', '.json', '.yaml', '.yml', '.toml',
+    '.html', '.htm', '.ts', '.tsx', '.js', '.jsx', '.mjs', '.py', '.rs', '.go', '.java',
+    '.c', '.h', '.cpp', '.hpp', '.rb', '.sh', '.sql',
+]);
+
+function ragSettings() {
+    const settings = getSettings();
+    const rag = settings.rag || {};
+    return {
+        enabled: !!rag.enabled,
+        baseUrl: (rag.base_url || settings.models?.ollama?.base_url || process.env.OLLAMA_HOST || 'http://localhost:11434').replace(/\/+$/, ''),
+        model: rag.embed_model || DEFAULT_EMBED_MODEL,
+        topK: rag.top_k || DEFAULT_TOP_K,
+        minScore: rag.min_score ?? DEFAULT_MIN_SCORE,
+        watchDir: rag.watch_dir,
+    };
+}
+
+/** Embed texts with Ollama; `model` defaults to `rag.embed_model`. */
+export async function embedWithUsage(texts: string[], model?: string): Promise<{ model: string; embeddings: number[][]; promptTokens: number }> {
+    const cfg = ragSettings();
+    const modelName = model || cfg.model;
+    let res: Response;
+    try {
+        res = await fetch(`${cfg.baseUrl}/api/embed`, {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify({ model: modelName, input: texts }),
+        });
+    } catch (error) {
+        throw new Error(`Could not reach Ollama at ${cfg.baseUrl}: ${(error as Error).message}`);
+    }
+    if (!res.ok) {
+        throw new Error(`Embedding request failed (${res.status}): ${(await res.text()).trim() || res.statusText}`);
+    }
+    const json: any = await res.json();
+    return { model: modelName, embeddings: json.embeddings, promptTokens: json.prompt_eval_count || 0 };
+}
+
+/** Embed texts with the configured Ollama embedding model. */
+export async function embed(texts: string[]): Promise<number[][]> {
+    return (await embedWithUsage(texts)).embeddings;
+}
+
+/** Split text into overlapping chunks, preferring paragraph boundaries. */
+export function chunkText(text: string): string[] {
+    const chunks: string[] = [];
+    let start = 0;
+    while (start < text.length) {
+        let end = Math.min(start + CHUNK_CHARS, text.length);
+        if (end < text.length) {
+            const para = text.lastIndexOf('\n\n', end);
+            if (para > start + CHUNK_CHARS / 2) end = para;
+        }
+        const chunk = text.slice(start, end).trim();
+        if (chunk) chunks.push(chunk);
+        if (end >= text.length) break;
+        start = Math.max(end - CHUNK_OVERLAP, start + 1);
+    }
+    return chunks;
+}
+
+async function indexText(source: string, text: string, mtime: number): Promise<number> {
+    const chunks = chunkText(text);
+    if (chunks.length === 0) {
+        deleteRagSource(source);
+        return 0;
+    }
+    const embeddings = await embed(chunks);
+    replaceRagSource(source, mtime, chunks.map((content, i) => ({ content, embedding: embeddings[i] })));
+    return chunks.length;
+}
+
+function listFiles(target: string): string[] {
+    const stat = fs.statSync(target);
+    if (stat.isFile()) return [target];
+    const files: string[] = [];
+    for (const entry of fs.readdirSync(target, { withFileTypes: true })) {
+        if (entry.name.startsWith('.') || entry.name === 'node_modules') continue;
+        const full = path.join(target, entry.name);
+        if (entry.isDirectory()) files.push(...listFiles(full));
+        else if (TEXT_EXTENSIONS.has(path.extname(entry.name).toLowerCase())) files.push(full);
+    }
+    return files;
+}
+
+export interface IngestResult {
+    indexed: number;   // files (re-)embedded
+    unchanged: number; // files skipped because their mtime matched
+    removed: number;   // previously indexed files that no longer exist
+    chunks: number;
+}
+
+/**
+ * Index a file or directory. Files whose mtime matches the stored one are
+ * skipped; indexed files under `target` that were deleted are removed.
+ */
+export async function ingestPath(target: string): Promise<IngestResult> {
+    const root = path.resolve(target);
+    const result: IngestResult = { indexed: 0, unchanged: 0, removed: 0, chunks: 0 };
+    const known = new Map(getRagSources().map(s => [s.source, s.mtime]));
+
+    const files = listFiles(root);
+    for (const file of files) {
+        const stat = fs.statSync(file);
+        if (stat.size > MAX_FILE_BYTES) {
+            log('WARN', `RAG: skipping ${file} (${Math.round(stat.size / 1024)} KB is too large)`);
+            continue;
+        }
+        const mtime = Math.floor(stat.mtimeMs);
+        if (known.get(file) === mtime) {
+            result.unchanged++;
+            continue;
+        }
+        result.chunks += await indexText(file, fs.readFileSync(file, 'utf8'), mtime);
+        result.indexed++;
+    }
+
+    const present = new Set(files);
+    for (const source of known.keys()) {
+        if ((source === root || source.startsWith(root + path.sep)) && !present.has(source)) {
+            deleteRagSource(source);
+            result.removed++;
+        }
+    }
+
+    log('INFO', `RAG: ingested ${root} — ${result.indexed} indexed, ${result.unchanged} unchanged, ${result.removed} removed`);
+    return result;
+}
+
+/** Store a free-text note (from `/remember`) as its own source; fails when RAG is off. */
+export async function rememberNote(text: string, author: string): Promise<void> {
+    // Stored notes are only ever read back through retrieval
+    if (!ragSettings().enabled) throw new Error('the knowledge base is off. Set rag.enabled to true to use /remember.');
+    const now = Date.now();
+    await indexText(`note:${now}`, `Note from ${author} (${new Date(now).toISOString()}):\n${text}`, now);
+}
+
+function cosine(a: Float32Array, b: number[]): number {
+    let dot = 0, na = 0, nb = 0;
+    for (let i = 0; i < a.length; i++) {
+        dot += a[i] * b[i];
+        na += a[i] * a[i];
+        nb += b[i] * b[i];
+    }
+    return na && nb ? dot / Math.sqrt(na * nb) : 0;
+}
+
+/** Top-k stored chunks most similar to `query`. */
+export async function searchChunks(query: string, topK?: number): Promise<{ source: string; content: string; score: number }[]> {
+    const cfg = ragSettings();
+    const chunks = getAllRagChunks();
+    if (chunks.length === 0) return [];
+    const [q] = await embed([query]);
+    return chunks
+        .map(c => ({ source: c.source, content: c.content, score: cosine(c.embedding, q) }))
+        .filter(c => c.score >= cfg.minScore)
+        .sort((a, b) => b.score - a.score)
+        .slice(0, topK ?? cfg.topK);
+}
+
+/**
+ * Build the system-prompt block of documents relevant to `message`, or ''
+ * when RAG is disabled, nothing matches, or the embedding server is down.
+ */
+export async function retrieveContext(message: string): Promise<string> {
+    if (!ragSettings().enabled) return '';
+    try {
+        const hits = await searchChunks(message);
+        if (hits.length === 0) return '';
+        const blocks = hits.map(h => `[${h.source.startsWith('note:') ? 'note' : path.basename(h.source)}]\n${h.content}`);
+        return '\n\n## Relevant documents\n\nRetrieved from the local knowledge base; use them if they help answer.\n\n'
+            + blocks.join('\n\n---\n\n') + '\n';
+    } catch (error) {
+        log('WARN', `RAG retrieval failed: ${(error as Error).message}`);
+        return '';
+    }
+}
+
+// ── Watched folder ──────────────────────────────────────────────────────────
+
+let watcher: fs.FSWatcher | null = null;
+let reindexTimer: ReturnType<typeof setTimeout> | null = null;
+
+/** Index `rag.watch_dir` now and re-index it whenever files change. */
+export function startRagWatcher(): void {
+    const { enabled, watchDir } = ragSettings();
+    if (!enabled || !watchDir) return;
+    if (!fs.existsSync(watchDir)) {
+        log('WARN', `RAG: watch_dir ${watchDir} does not exist`);
+        return;
+    }
+
+    const reindex = () => {
+        ingestPath(watchDir).catch(err => log('WARN', `RAG: ingest of ${watchDir} failed: ${err.message}`));
+    };
+    reindex();
+    watcher = fs.watch(watchDir, { recursive: true }, () => {
+        // Debounce bursts of change events (editors write several times)
+        if (reindexTimer) clearTimeout(reindexTimer);
+        reindexTimer = setTimeout(reindex, 2000);
+    });
+    log('INFO', `RAG: watching ${watchDir}`);
+}
+
+export function stopRagWatcher(): void {
+    if (reindexTimer) clearTimeout(reindexTimer);
+    watcher?.close();
+    watcher = null;
+}
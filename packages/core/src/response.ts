@@ -1,21 +1,73 @@
 import fs from 'fs';
 import path from 'path';
-import { FILES_DIR } from './config';
+import { FILES_DIR, getSettings } from './config';
 import { log, emitEvent } from './logging';
 import { runOutgoingHooks } from './plugins';
+import { moderate } from './moderation';
 import { enqueueResponse } from './queues';
 
 export const LONG_RESPONSE_THRESHOLD = 4000;
 
+/** Code points that attach to the character before them. */
+const CONTINUATION = /^[\p{M}\u200D\uFE0E\uFE0F\u{1F3FB}-\u{1F3FF}\u{E0020}-\u{E007F}]/u;
+
 /**
- * If a response exceeds the threshold, save full text as a .md file
- * and return a truncated preview with the file attached.
+ * Cut text to at most `max` UTF-16 code units without splitting a character:
+ * surrogate pairs, combining marks, and emoji modifier / ZWJ sequences stay
+ * together.
+ */
+export function truncateGraphemes(text: string, max: number): string {
+    if (text.length <= max) return text;
+    let end = max;
+    while (end > 0) {
+        const code = text.charCodeAt(end);
+        const lowSurrogate = code >= 0xDC00 && code <= 0xDFFF;
+        if (!lowSurrogate && !CONTINUATION.test(text.slice(end, end + 2)) && text.charCodeAt(end - 1) !== 0x200D) break;
+        end--;
+    }
+    return text.slice(0, end);
+}
+
+/**
+ * Split text into chunks of at most `maxLength` for channels with a message
+ * size limit. Prefers newline, then space boundaries; a hard cut never
+ * splits a character (see truncateGraphemes).
+ */
+export function splitMessage(text: string, maxLength: number): string[] {
+    const chunks: string[] = [];
+    let remaining = text;
+
+    while (remaining.length > maxLength) {
+        // Try to split at a newline boundary, then a space
+        let splitIndex = remaining.lastIndexOf('\n', maxLength);
+        if (splitIndex <= 0) splitIndex = remaining.lastIndexOf(' ', maxLength);
+        if (splitIndex <= 0) {
+            // A single character sequence longer than maxLength still has to go somewhere
+            splitIndex = truncateGraphemes(remaining, maxLength).length
+                || (remaining.codePointAt(0)! > 0xFFFF ? 2 : 1);
+        }
+
+        chunks.push(remaining.substring(0, splitIndex));
+        remaining = remaining.substring(splitIndex).replace(/^\n/, '');
+    }
+    if (remaining.length > 0 || chunks.length === 0) chunks.push(remaining);
+
+    return chunks;
+}
+
+/**
+ * Long responses are sent in full by default; channels split them to fit
+ * their message limit. With `processing.long_response: "file"`, responses
+ * over the threshold are saved as a .md file and sent as a preview with the
+ * file attached.
  */
 export function handleLongResponse(
     response: string,
     existingFiles: string[]
 ): { message: string; files: string[] } {
-    if (response.length <= LONG_RESPONSE_THRESHOLD) {
+    const cfg = getSettings().processing || {};
+    const threshold = cfg.long_response_threshold || LONG_RESPONSE_THRESHOLD;
+    if (cfg.long_response !== 'file' || response.length <= threshold) {
         return { message: response, files: existingFiles };
     }
 
@@ -25,10 +77,12 @@ export function handleLongResponse(
     fs.writeFileSync(filePath, response);
     log('INFO', `Long response (${response.length} chars) saved to ${filename}`);
 
-    // Truncate to preview
-    const preview = response.substring(0, LONG_RESPONSE_THRESHOLD) + '\n\n_(Full response attached as file)_';
+    // Preview ends at a line break when there is one reasonably close
+    let preview = truncateGraphemes(response, threshold);
+    const breakAt = preview.lastIndexOf('\n');
+    if (breakAt > threshold * 0.8) preview = preview.slice(0, breakAt);
 
-    return { message: preview, files: [...existingFiles, filePath] };
+    return { message: preview.trimEnd() + '\n\n_(Full response attached as file)_', files: [...existingFiles, filePath] };
 }
 
 /**
@@ -47,7 +101,7 @@ export function collectFiles(response: string, fileSet: Set<string>): void {
  * Shared pipeline for processing and enqueuing a response.
  * Used by both direct responses and streamed team responses.
  *
- * Pipeline: transform? → collectFiles + strip tags → runOutgoingHooks → handleLongResponse → enqueueResponse → emitEvent
+ * Pipeline: transform? → collectFiles + strip tags → moderate → runOutgoingHooks → handleLongResponse → enqueueResponse → emitEvent
  */
 export async function streamResponse(response: string, options: {
     channel: string;
@@ -71,6 +125,12 @@ export async function streamResponse(response: string, options: {
         finalResponse = finalResponse.replace(/\[send_file:\s*[^\]]+\]/g, '').trim();
     }
 
+    const moderated = await moderate(finalResponse, 'outgoing', {
+        channel: options.channel, sender: options.sender, messageId: options.messageId, originalMessage: options.originalMessage,
+    });
+    finalResponse = moderated.text;
+    if (moderated.blocked) outboundFiles.length = 0;
+
     const { text: hookedResponse, metadata } = await runOutgoingHooks(finalResponse, {
         channel: options.channel, sender: options.sender, messageId: options.messageId, originalMessage: options.originalMessage,
     });
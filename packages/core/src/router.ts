@@ -1,5 +1,5 @@
 import path from 'path';
-import { AgentConfig, TeamConfig } from './types';
+import { AgentConfig, AgentFallback, Settings, TeamConfig } from './types';
 import { log } from './logging';
 
 /**
@@ -44,6 +44,28 @@ export function parseAgentRouting(
     return { agentId: 'tinyagi', message: rawMessage };
 }
 
+/**
+ * Pick a provider/model override for a message from `settings.routing`.
+ * A `#tag:` prefix matching `routing.tags` wins (and is stripped from the
+ * message); otherwise `routing.channels[channel]` applies. Returns no route
+ * when neither matches, so the agent's own provider and model are used.
+ */
+export function resolveModelRoute(
+    channel: string,
+    message: string,
+    settings: Settings,
+): { route?: AgentFallback; message: string; reason?: string } {
+    const routing = settings.routing || {};
+    const tagMatch = message.match(/^#([\w-]+):\s*([\s\S]*)$/);
+    if (tagMatch) {
+        const route = routing.tags?.[tagMatch[1].toLowerCase()];
+        if (route) return { route, message: tagMatch[2], reason: `#${tagMatch[1]}` };
+    }
+    const route = routing.channels?.[channel];
+    if (route) return { route, message, reason: `channel ${channel}` };
+    return { message };
+}
+
 /**
  * Find the first team that contains the given agent.
  */
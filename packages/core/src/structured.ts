@@ -0,0 +1,122 @@
+import { ResponseFormat } from './types';
+
+/**
+ * Structured output for API callers: JSON replies (optionally checked against
+ * a JSON schema) or, on llama.cpp, a GBNF grammar. Backends that support it
+ * constrain generation natively; every reply is still parsed and validated
+ * here, and invokeAgent asks again when it does not pass.
+ */
+
+/**
+ * Check a `responseFormat` from an API request. Also accepts OpenAI's
+ * `{ type: 'json_schema', json_schema: { schema } }` shape.
+ */
+export function toResponseFormat(value: any): ResponseFormat {
+    if (!value || typeof value !== 'object') throw new Error('responseFormat must be an object');
+    switch (value.type) {
+        case 'json_object':
+            return { type: 'json_object' };
+        case 'json_schema': {
+            const schema = value.schema ?? value.json_schema?.schema;
+            if (!schema || typeof schema !== 'object' || Array.isArray(schema)) {
+                throw new Error('responseFormat.schema must be a JSON schema object');
+            }
+            return { type: 'json_schema', schema };
+        }
+        case 'grammar':
+            if (typeof value.grammar !== 'string' || !value.grammar.trim()) {
+                throw new Error('responseFormat.grammar must be a GBNF grammar string');
+            }
+            return { type: 'grammar', grammar: value.grammar };
+        default:
+            throw new Error("responseFormat.type must be 'json_object', 'json_schema' or 'grammar'");
+    }
+}
+
+/** Extra instructions appended to the message for JSON formats. */
+export function formatInstructions(format: ResponseFormat): string {
+    if (format.type === 'grammar') return '';
+    let text = '\n\nReply with a single JSON value only — no prose, no code fences.';
+    if (format.type === 'json_schema') {
+        text += ` It must match this JSON schema:\n${JSON.stringify(format.schema)}`;
+    }
+    return text;
+}
+
+/**
+ * Parse a reply as JSON and validate it against the format's schema.
+ * Throws with a message suitable for sending back to the model.
+ */
+export function parseStructured(text: string, format: ResponseFormat): unknown {
+    // Models often wrap JSON in a code fence despite being told not to
+    const fenced = text.trim().match(/^```(?:json)?\s*\n([\s\S]*?)\n?```$/);
+    const body = fenced ? fenced[1] : text.trim();
+    let value: unknown;
+    try {
+        value = JSON.parse(body);
+    } catch (error) {
+        throw new Error(`not valid JSON (${(error as Error).message})`);
+    }
+    if (format.type === 'json_schema') {
+        const problems = validateSchema(value, format.schema, '$');
+        if (problems.length > 0) throw new Error(`does not match the schema: ${problems.slice(0, 5).join('; ')}`);
+    }
+    return value;
+}
+
+function typeOf(value: unknown): string {
+    if (value === null) return 'null';
+    if (Array.isArray(value)) return 'array';
+    if (typeof value === 'number' && Number.isInteger(value)) return 'integer';
+    return typeof value;
+}
+
+/**
+ * Validate the commonly used subset of JSON Schema: type, enum, const,
+ * properties, required, additionalProperties: false, items, and
+ * minItems / maxItems. Other keywords are ignored.
+ */
+export function validateSchema(value: unknown, schema: Record<string, any>, at: string): string[] {
+    const problems: string[] = [];
+    const actual = typeOf(value);
+
+    if (schema.type) {
+        const allowed: string[] = Array.isArray(schema.type) ? schema.type : [schema.type];
+        // An integer is also a number
+        if (!allowed.includes(actual) && !(actual === 'integer' && allowed.includes('number'))) {
+            return [`${at} should be ${allowed.join(' or ')}, got ${actual}`];
+        }
+    }
+    if (schema.enum && !schema.enum.some((v: unknown) => JSON.stringify(v) === JSON.stringify(value))) {
+        problems.push(`${at} should be one of ${JSON.stringify(schema.enum)}`);
+    }
+    if ('const' in schema && JSON.stringify(schema.const) !== JSON.stringify(value)) {
+        problems.push(`${at} should be ${JSON.stringify(schema.const)}`);
+    }
+
+    if (actual === 'object') {
+        const obj = value as Record<string, unknown>;
+        const properties: Record<string, any> = schema.properties || {};
+        for (const key of schema.required || []) {
+            if (!(key in obj)) problems.push(`${at}.${key} is required`);
+        }
+        for (const [key, child] of Object.entries(obj)) {
+            if (Object.prototype.hasOwnProperty.call(properties, key)) {
+                problems.push(...validateSchema(child, properties[key], `${at}.${key}`));
+            } else if (schema.additionalProperties === false) {
+                problems.push(`${at}.${key} is not allowed`);
+            }
+        }
+    }
+
+    if (actual === 'array') {
+        const items = value as unknown[];
+        if (schema.minItems !== undefined && items.length < schema.minItems) problems.push(`${at} needs at least ${schema.minItems} items`);
+        if (schema.maxItems !== undefined && items.length > schema.maxItems) problems.push(`${at} allows at most ${schema.maxItems} items`);
+        if (schema.items) {
+            items.forEach((item, i) => problems.push(...validateSchema(item, schema.items, `${at}[${i}]`)));
+        }
+    }
+
+    return problems;
+}
@@ -0,0 +1,102 @@
+import { execFile } from 'child_process';
+import fs from 'fs';
+import os from 'os';
+import path from 'path';
+import { promisify } from 'util';
+import { getSettings } from './config';
+import { log } from './logging';
+
+/**
+ * Local speech-to-text with whisper.cpp. Audio is converted to 16 kHz mono
+ * WAV with ffmpeg, then transcribed by `whisper-cli` (or a running
+ * `whisper-server` when `stt.base_url` is set). Voice notes attached to
+ * incoming messages are transcribed before the agent sees them.
+ */
+
+const run = promisify(execFile);
+
+const AUDIO_EXTENSIONS = new Set(['.ogg', '.oga', '.opus', '.mp3', '.m4a', '.wav', '.webm', '.flac', '.aac']);
+
+/** Transcriptions longer than this are cut off (whisper-cli default timeout). */
+const TRANSCRIBE_TIMEOUT_MS = 5 * 60_000;
+
+function sttSettings() {
+    const stt = getSettings().stt || {};
+    return {
+        enabled: !!stt.enabled,
+        binary: stt.binary || 'whisper-cli',
+        modelPath: stt.model_path || '',
+        baseUrl: stt.base_url?.replace(/\/+$/, ''),
+        language: stt.language || 'auto',
+        ffmpeg: stt.ffmpeg || 'ffmpeg',
+    };
+}
+
+export function isAudioFile(file: string): boolean {
+    return AUDIO_EXTENSIONS.has(path.extname(file).toLowerCase());
+}
+
+/** Transcribe an audio file and return the text. */
+export async function transcribeFile(file: string): Promise<string> {
+    const cfg = sttSettings();
+    if (!cfg.baseUrl && !cfg.modelPath) {
+        throw new Error('Speech-to-text is not configured (set stt.model_path or stt.base_url)');
+    }
+
+    const tmpDir = fs.mkdtempSync(path.join(os.tmpdir(), 'tinyagi-stt-'));
+    const wav = path.join(tmpDir, 'audio.wav');
+    try {
+        try {
+            await run(cfg.ffmpeg, ['-nostdin', '-loglevel', 'error', '-i', file, '-ar', '16000', '-ac', '1', '-c:a', 'pcm_s16le', wav],
+                { timeout: TRANSCRIBE_TIMEOUT_MS });
+        } catch (error) {
+            throw new Error(`ffmpeg could not convert ${path.basename(file)}: ${(error as Error).message}`);
+        }
+
+        if (cfg.baseUrl) {
+            const form = new FormData();
+            form.append('file', new Blob([fs.readFileSync(wav)], { type: 'audio/wav' }), 'audio.wav');
+            form.append('response_format', 'json');
+            form.append('language', cfg.language);
+            const res = await fetch(`${cfg.baseUrl}/inference`, {
+                method: 'POST', body: form, signal: AbortSignal.timeout(TRANSCRIBE_TIMEOUT_MS),
+            });
+            if (!res.ok) throw new Error(`whisper-server returned ${res.status}: ${(await res.text()).trim() || res.statusText}`);
+            return String((await res.json() as any).text || '').trim();
+        }
+
+        // -nt: no timestamps, -np: only the transcript on stdout
+        const { stdout } = await run(cfg.binary, ['-m', cfg.modelPath, '-f', wav, '-l', cfg.language, '-nt', '-np'], {
+            timeout: TRANSCRIBE_TIMEOUT_MS, maxBuffer: 10 * 1024 * 1024,
+        });
+        return stdout.replace(/\s+/g, ' ').trim();
+    } finally {
+        fs.rmSync(tmpDir, { recursive: true, force: true });
+    }
+}
+
+/**
+ * Add transcripts for audio `[file: …]` references in a message, when
+ * `stt.enabled` is set. The file reference is kept; failures are noted
+ * inline so the agent can tell the sender.
+ */
+export async function transcribeAttachments(message: string): Promise<string> {
+    if (!sttSettings().enabled) return message;
+    const refs = [...message.matchAll(/\[file:\s*([^\]]+)\]/g)].map(m => m[1].trim()).filter(isAudioFile);
+    if (refs.length === 0) return message;
+
+    const notes: string[] = [];
+    for (const file of refs) {
+        const name = path.basename(file);
+        try {
+            const startedAt = Date.now();
+            const text = await transcribeFile(file);
+            log('INFO', `Transcribed ${name} in ${((Date.now() - startedAt) / 1000).toFixed(1)}s (${text.length} chars)`);
+            notes.push(`[Voice message ${name} transcript]: ${text || '(no speech detected)'}`);
+        } catch (error) {
+            log('WARN', `Could not transcribe ${name}: ${(error as Error).message}`);
+            notes.push(`[Voice message ${name} could not be transcribed: ${(error as Error).message}]`);
+        }
+    }
+    return `${notes.join('\n')}\n\n${message}`;
+}
@@ -6,19 +6,36 @@ export interface CustomProvider {
     model?: string;               // model name to pass to the CLI
 }
 
+/** A provider to try when the one before it in the chain fails. */
+export interface AgentFallback {
+    provider: string;       // same values as AgentConfig.provider
+    model?: string;
+}
+
 export interface AgentConfig {
     name: string;
-    provider: string;       // 'anthropic', 'openai', 'opencode', or 'custom:<provider_id>'
+    provider: string;       // 'anthropic', 'openai', 'opencode', 'ollama', 'llamacpp', or 'custom:<provider_id>'
     model: string;           // e.g. 'sonnet', 'opus', 'gpt-5.3-codex'
     working_directory: string;
     system_prompt?: string;
     prompt_file?: string;
+    fallback?: AgentFallback[]; // overrides models.fallback for this agent
+    tools?: string[];           // built-in tools for ollama/llamacpp, e.g. ['current_time', 'calculator']
     heartbeat?: {
         enabled?: boolean;
         interval?: number;
     };
 }
 
+/** A keyword / regex filter for `moderation.rules`. */
+export interface ModerationRule {
+    name?: string;            // shown in the audit log
+    pattern?: string;         // regex, matched case-insensitively
+    keywords?: string[];      // whole-word, case-insensitive
+    action: 'block' | 'redact';
+    apply_to?: 'incoming' | 'outgoing' | 'both'; // default both
+}
+
 export interface TeamConfig {
     name: string;
     agents: string[];
@@ -38,7 +55,7 @@ export interface Settings {
         defaults?: Record<string, { agentId: string }>;
     };
     models?: {
-        provider?: string; // 'anthropic', 'openai', or 'opencode'
+        provider?: string; // 'anthropic', 'openai', 'opencode', 'ollama', or 'llamacpp'
         anthropic?: {
             model?: string;
             api_key?: string;
@@ -51,12 +68,104 @@ export interface Settings {
         opencode?: {
             model?: string;
         };
+        ollama?: {
+            model?: string;
+            base_url?: string; // defaults to http://localhost:11434
+            context_length?: number; // old history is trimmed to fit; larger prompts fail (and fall back)
+            keep_alive?: string;     // how long Ollama keeps the model loaded, e.g. '30m' or '-1m' (forever)
+        };
+        llamacpp?: {
+            base_url?: string;      // existing llama-server; defaults to http://127.0.0.1:<port>
+            model_path?: string;    // GGUF file — when set, llama-server is launched on demand
+            server_binary?: string; // defaults to 'llama-server'
+            port?: number;          // defaults to 8080
+            context_length?: number; // history trimmed to fit, counted with the server's tokenizer
+            mmproj_path?: string;   // multimodal projector GGUF — enables image input (--mmproj)
+            vision?: boolean;       // external server (base_url) already runs with --mmproj
+            extra_args?: string[];
+        };
+        warmup?: {
+            enabled?: boolean;            // load local models at startup instead of on the first message
+            keep_alive_interval?: number; // seconds between pings that keep them loaded (default 240, 0 = off)
+        };
+        fallback?: AgentFallback[]; // tried in order when an agent's provider fails
+        catalog_url?: string; // JSON model catalog fetched by `tinyagi models --refresh`
     };
     agents?: Record<string, AgentConfig>;
     custom_providers?: Record<string, CustomProvider>;
     teams?: Record<string, TeamConfig>;
     monitoring?: {
         heartbeat_interval?: number;
+        budget?: {
+            daily_tokens?: number;            // input + output tokens per day before an alert
+            daily_inference_seconds?: number; // seconds spent waiting on models per day
+            notify?: { channel: string; sender: string; sender_id?: string }; // where alerts are sent
+        };
+        heartbeat_route?: {                   // where heartbeat replies are delivered
+            channel: string;                  // telegram, discord, whatsapp, ntfy, or email
+            sender?: string;
+            sender_id?: string;               // chat channels: the chat/user to message
+            url?: string;                     // ntfy: topic URL, e.g. https://ntfy.sh/my-topic
+            token?: string;                   // ntfy: access token for protected topics
+            to?: string;                      // email: recipient, sent through the local sendmail
+        };
+        heartbeat_retention_hours?: number;   // heartbeat replies are deleted after this (default 24)
+    };
+    routing?: {
+        channels?: Record<string, AgentFallback>; // e.g. { "heartbeat": { "provider": "ollama", "model": "gemma3:1b" } }
+        tags?: Record<string, AgentFallback>;     // "#code: ..." → tags.code
+    };
+    refine?: {
+        // Per channel ('*' for all): a second pass improves the agent's draft before it is sent.
+        // provider/model pick the refiner (default: the agent's own); prompt replaces the built-in instructions.
+        channels?: Record<string, { provider?: string; model?: string; prompt?: string }>;
+    };
+    server?: {
+        port?: number; // HTTP API port, default 3777; TINYAGI_API_PORT takes precedence
+    };
+    processing?: {
+        timeout?: number; // seconds per message before it is cancelled (0/unset = no limit)
+        drain_timeout?: number; // seconds `tinyagi stop` / restart waits for in-flight messages, default 60
+        long_response?: 'chunk' | 'file'; // chunk (default): send everything; file: preview + .md attachment
+        long_response_threshold?: number; // characters before 'file' mode kicks in, default 4000
+    };
+    memory?: {
+        auto_extract?: boolean;  // after each reply, save lasting facts about the user as memories
+        provider?: string;       // extractor, default: the agent's own provider and model
+        model?: string;
+    };
+    rag?: {
+        enabled?: boolean;       // add matching documents to every agent's system prompt
+        watch_dir?: string;      // folder indexed at startup and re-indexed on change
+        embed_model?: string;    // Ollama embedding model, default 'nomic-embed-text'
+        base_url?: string;       // Ollama server, default models.ollama.base_url
+        top_k?: number;          // chunks per message, default 4
+        min_score?: number;      // cosine similarity cut-off, default 0.35
+    };
+    moderation?: {
+        enabled?: boolean;       // default true when this section exists
+        rules?: ModerationRule[];
+        classifier?: {
+            model: string;       // Ollama safety model, e.g. 'llama-guard3:1b'
+            base_url?: string;   // default models.ollama.base_url
+            apply_to?: 'incoming' | 'outgoing' | 'both';
+        };
+        blocked_reply?: string;  // sent instead of blocked text
+    };
+    stt?: {
+        enabled?: boolean;     // transcribe voice notes attached to incoming messages
+        model_path?: string;   // whisper.cpp GGML model, e.g. ggml-base.en.bin
+        binary?: string;       // default 'whisper-cli'
+        base_url?: string;     // use a running whisper-server instead of the binary
+        language?: string;     // default 'auto'
+        ffmpeg?: string;       // default 'ffmpeg'
+    };
+    tools?: {
+        shell?: {
+            allowlist?: string[];  // permitted programs, e.g. ['git', 'ls', 'df']
+            timeout?: number;      // seconds, default 30
+            approval?: boolean;    // ask the sender before each command (default true)
+        };
     };
 }
 
@@ -111,6 +220,12 @@ export interface Schedule {
 }
 
 // Queue job data types
+/** Structured output requested through the API (see structured.ts). */
+export type ResponseFormat =
+    | { type: 'json_object' }
+    | { type: 'json_schema'; schema: Record<string, any> }
+    | { type: 'grammar'; grammar: string }; // GBNF, llama.cpp only
+
 export interface MessageJobData {
     channel: string;
     sender: string;
@@ -119,6 +234,7 @@ export interface MessageJobData {
     messageId: string;
     agent?: string;
     fromAgent?: string;
+    responseFormat?: ResponseFormat;
 }
 
 export interface ResponseJobData {
@@ -0,0 +1,38 @@
+import fs from 'fs';
+import path from 'path';
+import { TINYAGI_HOME } from './config';
+
+/**
+ * Per-conversation verbose mode (`/verbose`): while on, the sender gets short
+ * progress updates — which model is working, tool calls, tokens so far —
+ * instead of silence until the final answer. Kept in a small JSON file so it
+ * survives restarts.
+ */
+
+const VERBOSE_FILE = path.join(TINYAGI_HOME, 'verbose.json');
+
+function load(): Set<string> {
+    try {
+        return new Set(JSON.parse(fs.readFileSync(VERBOSE_FILE, 'utf8')));
+    } catch {
+        return new Set();
+    }
+}
+
+const enabled = load();
+
+function verboseKey(channel: string, senderKey: string): string {
+    return `${channel}:${senderKey}`;
+}
+
+export function isVerbose(channel: string, senderKey: string): boolean {
+    return enabled.has(verboseKey(channel, senderKey));
+}
+
+export function setVerbose(channel: string, senderKey: string, on: boolean): void {
+    const key = verboseKey(channel, senderKey);
+    if (on) enabled.add(key);
+    else enabled.delete(key);
+    fs.mkdirSync(path.dirname(VERBOSE_FILE), { recursive: true });
+    fs.writeFileSync(VERBOSE_FILE, JSON.stringify([...enabled], null, 2));
+}
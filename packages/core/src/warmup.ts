@@ -0,0 +1,80 @@
+import { getSettings, getAgents, resolveModel } from './config';
+import { log, emitEvent } from './logging';
+import { warmUpOllama, warmUpLlamaServer } from './adapters';
+import { OLLAMA_DEFAULT_BASE_URL } from './adapters/ollama';
+
+/**
+ * Warm-up for local models: with `models.warmup.enabled`, every Ollama model
+ * an agent uses is loaded (and llama-server started) when the queue
+ * processor starts, then pinged every `keep_alive_interval` seconds so it
+ * stays in memory. Progress is reported as `model:warmup` events.
+ */
+
+const DEFAULT_KEEP_ALIVE_INTERVAL = 240;
+
+let pingTimer: NodeJS.Timeout | null = null;
+
+interface WarmupTarget {
+    provider: 'ollama' | 'llamacpp';
+    model: string;
+}
+
+/** Distinct local models used by configured agents. */
+function warmupTargets(): WarmupTarget[] {
+    const targets = new Map<string, WarmupTarget>();
+    for (const agent of Object.values(getAgents(getSettings()))) {
+        if (agent.provider === 'ollama') {
+            const model = resolveModel(agent.model, 'ollama');
+            targets.set(`ollama:${model}`, { provider: 'ollama', model });
+        } else if (agent.provider === 'llamacpp') {
+            // One llama-server serves a single model
+            targets.set('llamacpp', { provider: 'llamacpp', model: '' });
+        }
+    }
+    return [...targets.values()];
+}
+
+async function warm(target: WarmupTarget, ping: boolean): Promise<void> {
+    const label = target.provider === 'ollama' ? `ollama/${target.model || 'default'}` : 'llama-server';
+    const startedAt = Date.now();
+    if (!ping) emitEvent('model:warmup', { ...target, status: 'loading' });
+    try {
+        if (target.provider === 'ollama') {
+            const ollama = getSettings().models?.ollama;
+            await warmUpOllama(ollama?.base_url || process.env.OLLAMA_HOST || OLLAMA_DEFAULT_BASE_URL, target.model, {
+                contextLength: ollama?.context_length,
+                keepAlive: ollama?.keep_alive,
+            });
+        } else {
+            await warmUpLlamaServer();
+        }
+        const durationMs = Date.now() - startedAt;
+        if (!ping) {
+            log('INFO', `Warm-up: ${label} ready in ${(durationMs / 1000).toFixed(1)}s`);
+            emitEvent('model:warmup', { ...target, status: 'ready', durationMs });
+        }
+    } catch (error) {
+        log('WARN', `Warm-up: ${label} ${ping ? 'keep-alive ping' : 'load'} failed: ${(error as Error).message}`);
+        emitEvent('model:warmup', { ...target, status: 'failed', error: (error as Error).message });
+    }
+}
+
+export function startWarmup(): void {
+    const cfg = getSettings().models?.warmup;
+    if (!cfg?.enabled) return;
+    const targets = warmupTargets();
+    if (targets.length === 0) return;
+
+    const run = (ping: boolean) => Promise.all(targets.map(t => warm(t, ping)));
+    run(false);
+
+    const interval = cfg.keep_alive_interval ?? DEFAULT_KEEP_ALIVE_INTERVAL;
+    if (interval > 0) {
+        pingTimer = setInterval(() => run(true), interval * 1000);
+    }
+}
+
+export function stopWarmup(): void {
+    if (pingTimer) clearInterval(pingTimer);
+    pingTimer = null;
+}
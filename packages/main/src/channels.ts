@@ -18,7 +18,24 @@ const TOKEN_ENV_KEYS: Record<string, string> = {
     telegram: 'TELEGRAM_BOT_TOKEN',
 };
 
+/** Tail of stderr kept per channel, to explain why it exited. */
+const STDERR_TAIL_LINES = 5;
+
+export interface ChannelState {
+    running: boolean;
+    /** The client reported it is logged in (see channels/src/startup.ts). */
+    ready: boolean;
+    pid?: number;
+    startedAt?: number;
+    /** Why the channel is not running: missing token, the reason it reported, or its last stderr line on exit. */
+    error?: string;
+    exitCode?: number | null;
+}
+
 const children = new Map<string, ChildProcess>();
+const startedAt = new Map<string, number>();
+const failures = new Map<string, { error: string; exitCode?: number | null }>();
+const ready = new Set<string>();
 
 function getChannelToken(channelId: string): string | undefined {
     // Check environment first (Docker / manual override)
@@ -40,38 +57,7 @@ export function startChannels(): void {
         return;
     }
 
-    for (const channelId of enabled) {
-        const script = CHANNEL_SCRIPTS[channelId];
-        if (!script) {
-            log('WARN', `Unknown channel: ${channelId}`);
-            continue;
-        }
-
-        const envKey = TOKEN_ENV_KEYS[channelId];
-        const token = getChannelToken(channelId);
-
-        // WhatsApp doesn't need a token (uses QR code auth)
-        if (envKey && !token) {
-            log('WARN', `${channelId} enabled but ${envKey} not set, skipping`);
-            continue;
-        }
-
-        const scriptPath = path.join(SCRIPT_DIR, 'packages', 'channels', 'dist', script);
-        const env: Record<string, string> = { ...process.env as Record<string, string> };
-        if (envKey && token) {
-            env[envKey] = token;
-        }
-
-        log('INFO', `Starting ${channelId} channel...`);
-        const child = fork(scriptPath, [], { env, stdio: 'inherit' });
-
-        child.on('exit', (code) => {
-            log('INFO', `Channel ${channelId} exited (code ${code})`);
-            children.delete(channelId);
-        });
-
-        children.set(channelId, child);
-    }
+    for (const channelId of enabled) startChannel(channelId);
 
     log('INFO', `Started ${children.size} channel(s): ${[...children.keys()].join(', ')}`);
 }
@@ -80,8 +66,9 @@ export function stopChannel(channelId: string): boolean {
     const child = children.get(channelId);
     if (!child) return false;
     log('INFO', `Stopping ${channelId} channel...`);
-    child.kill('SIGTERM');
     children.delete(channelId);
+    ready.delete(channelId);
+    child.kill('SIGTERM');
     return true;
 }
 
@@ -94,14 +81,17 @@ export function startChannel(channelId: string): boolean {
     const script = CHANNEL_SCRIPTS[channelId];
     if (!script) {
         log('WARN', `Unknown channel: ${channelId}`);
+        failures.set(channelId, { error: 'unknown channel' });
         return false;
     }
 
     const envKey = TOKEN_ENV_KEYS[channelId];
     const token = getChannelToken(channelId);
 
+    // WhatsApp doesn't need a token (uses QR code auth)
     if (envKey && !token) {
-        log('WARN', `${channelId} requires ${envKey} but it is not set`);
+        log('WARN', `${channelId} enabled but ${envKey} not set, skipping`);
+        failures.set(channelId, { error: `${envKey} not set` });
         return false;
     }
 
@@ -112,14 +102,45 @@ export function startChannel(channelId: string): boolean {
     }
 
     log('INFO', `Starting ${channelId} channel...`);
-    const child = fork(scriptPath, [], { env, stdio: 'inherit' });
+    // stderr is piped (and passed through) so a failed start can be explained
+    const child = fork(scriptPath, [], { env, stdio: ['inherit', 'inherit', 'pipe', 'ipc'] });
+    const stderrTail: string[] = [];
+    child.stderr?.on('data', (chunk: Buffer) => {
+        process.stderr.write(chunk);
+        stderrTail.push(...chunk.toString().split('\n').map(l => l.trim()).filter(Boolean));
+        stderrTail.splice(0, Math.max(0, stderrTail.length - STDERR_TAIL_LINES));
+    });
+
+    // The client reports when it is logged in, or why it could not log in
+    let reportedError: string | undefined;
+    child.on('message', (message: any) => {
+        if (children.get(channelId) !== child) return;
+        if (message.type === 'ready') {
+            ready.add(channelId);
+            log('INFO', `Channel ${channelId} ready`);
+        } else if (message.type === 'failed') {
+            reportedError = message.error;
+            log('ERROR', `Channel ${channelId} failed to start: ${message.error}`);
+        }
+    });
 
-    child.on('exit', (code) => {
+    child.on('exit', (code, signal) => {
         log('INFO', `Channel ${channelId} exited (code ${code})`);
-        children.delete(channelId);
+        // Only record failures for channels that were not stopped on purpose
+        if (children.get(channelId) === child) {
+            children.delete(channelId);
+            ready.delete(channelId);
+            failures.set(channelId, {
+                error: reportedError || stderrTail[stderrTail.length - 1] || `exited with ${signal ?? `code ${code}`}`,
+                exitCode: code,
+            });
+        }
     });
 
     children.set(channelId, child);
+    startedAt.set(channelId, Date.now());
+    failures.delete(channelId);
+    ready.delete(channelId);
     return true;
 }
 
@@ -129,29 +150,29 @@ export function restartChannel(channelId: string): boolean {
 }
 
 export function stopChannels(): void {
-    for (const [channelId, child] of children) {
+    const running = [...children];
+    children.clear();
+    ready.clear();
+    for (const [channelId, child] of running) {
         log('INFO', `Stopping ${channelId} channel...`);
         child.kill('SIGTERM');
     }
-    children.clear();
 }
 
-export function getChannelStatus(): Record<string, { running: boolean; pid?: number }> {
+export function getChannelStatus(): Record<string, ChannelState> {
     const settings = getSettings();
     const enabled = settings.channels?.enabled ?? [];
-    const status: Record<string, { running: boolean; pid?: number }> = {};
-    for (const ch of enabled) {
+    const status: Record<string, ChannelState> = {};
+    for (const ch of [...enabled, ...children.keys()]) {
         const child = children.get(ch);
+        const failure = failures.get(ch);
         status[ch] = {
             running: !!child && !child.killed,
+            ready: !!child && ready.has(ch),
             pid: child?.pid,
+            startedAt: startedAt.get(ch),
+            ...(child ? {} : failure),
         };
     }
-    // Also include any running channels not in enabled list
-    for (const [ch, child] of children) {
-        if (!status[ch]) {
-            status[ch] = { running: !child.killed, pid: child.pid };
-        }
-    }
     return status;
 }
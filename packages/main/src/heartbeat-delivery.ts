@@ -0,0 +1,113 @@
+/**
+ * Heartbeat delivery — forwards heartbeat replies from the `heartbeat`
+ * response channel to monitoring.heartbeat_route, and prunes old ones.
+ *
+ * Chat channels (Telegram, Discord, WhatsApp) get the reply re-queued for
+ * their channel client; ntfy and email are sent directly from here.
+ */
+
+import { spawn } from 'child_process';
+import {
+    getSettings, log, getResponsesForChannel, enqueueResponse, ackResponse, pruneChannelResponses, Settings,
+} from '@tinyagi/core';
+
+const CHAT_CHANNELS = ['telegram', 'discord', 'whatsapp'];
+/** After a failed ntfy/email send, wait this long before trying that reply again. */
+const RETRY_DELAY_MS = 60_000;
+const DEFAULT_RETENTION_HOURS = 24;
+
+type Route = NonNullable<NonNullable<Settings['monitoring']>['heartbeat_route']>;
+
+const retryAt = new Map<number, number>();
+let delivering = false;
+
+async function sendNtfy(route: Route, title: string, message: string): Promise<void> {
+    if (!route.url) throw new Error('heartbeat_route.url is not set');
+    const headers: Record<string, string> = { Title: title };
+    if (route.token) headers.Authorization = `Bearer ${route.token}`;
+    const res = await fetch(route.url, { method: 'POST', headers, body: message });
+    if (!res.ok) throw new Error(`ntfy returned HTTP ${res.status}`);
+}
+
+function sendEmail(route: Route, subject: string, message: string): Promise<void> {
+    if (!route.to) return Promise.reject(new Error('heartbeat_route.to is not set'));
+    const mail = [
+        `To: ${route.to}`,
+        ...(route.sender ? [`From: ${route.sender}`] : []),
+        `Subject: ${subject}`,
+        'Content-Type: text/plain; charset=utf-8',
+        '',
+        message,
+    ].join('\n');
+
+    return new Promise((resolve, reject) => {
+        // -i: a line with a lone '.' in the reply must not end the message
+        const child = spawn('sendmail', ['-t', '-i'], { stdio: ['pipe', 'ignore', 'pipe'] });
+        let stderr = '';
+        child.stderr.on('data', chunk => { stderr += chunk; });
+        child.on('error', err => reject(new Error(`sendmail: ${err.message}`)));
+        child.on('close', code => code === 0 ? resolve() : reject(new Error(`sendmail exited ${code}: ${stderr.trim()}`)));
+        child.stdin.end(mail);
+    });
+}
+
+/**
+ * Deliver pending heartbeat replies. Without a route they stay on the
+ * heartbeat channel until pruned.
+ */
+export async function deliverHeartbeatResponses(): Promise<void> {
+    const route = getSettings().monitoring?.heartbeat_route;
+    if (!route?.channel || delivering) return;
+    delivering = true;
+
+    try {
+        const pending = getResponsesForChannel('heartbeat');
+        // Forget retry times for replies that were pruned meanwhile
+        const ids = new Set(pending.map(r => r.id));
+        for (const id of retryAt.keys()) if (!ids.has(id)) retryAt.delete(id);
+
+        for (const r of pending) {
+            if ((retryAt.get(r.id) ?? 0) > Date.now()) continue;
+            const title = `TinyAGI heartbeat from @${r.agent}`;
+
+            try {
+                if (CHAT_CHANNELS.includes(route.channel)) {
+                    enqueueResponse({
+                        channel: route.channel,
+                        sender: route.sender || route.sender_id || 'Heartbeat',
+                        senderId: route.sender_id,
+                        message: r.message,
+                        originalMessage: r.original_message,
+                        messageId: r.message_id,
+                        agent: r.agent || undefined,
+                        files: r.files ? JSON.parse(r.files) : undefined,
+                    });
+                } else if (route.channel === 'ntfy') {
+                    await sendNtfy(route, title, r.message);
+                } else if (route.channel === 'email') {
+                    await sendEmail(route, title, r.message);
+                } else {
+                    log('WARN', `Heartbeat route: unknown channel '${route.channel}'`);
+                    return;
+                }
+            } catch (err) {
+                retryAt.set(r.id, Date.now() + RETRY_DELAY_MS);
+                log('ERROR', `Heartbeat delivery to ${route.channel} failed (retrying in ${RETRY_DELAY_MS / 1000}s): ${(err as Error).message}`);
+                continue;
+            }
+
+            ackResponse(r.id);
+            retryAt.delete(r.id);
+            log('INFO', `Heartbeat reply from @${r.agent} delivered to ${route.channel}`);
+        }
+    } finally {
+        delivering = false;
+    }
+}
+
+/** Delete heartbeat replies older than monitoring.heartbeat_retention_hours. */
+export function pruneHeartbeatResponses(): void {
+    const hours = getSettings().monitoring?.heartbeat_retention_hours ?? DEFAULT_RETENTION_HOURS;
+    const pruned = pruneChannelResponses('heartbeat', hours * 3600 * 1000);
+    if (pruned > 0) log('INFO', `Pruned ${pruned} heartbeat repl${pruned === 1 ? 'y' : 'ies'} older than ${hours}h`);
+}
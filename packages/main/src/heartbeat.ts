@@ -5,13 +5,18 @@
 
 import fs from 'fs';
 import path from 'path';
-import { getSettings, getAgents, SETTINGS_FILE, log } from '@tinyagi/core';
+import { getSettings, getAgents, SETTINGS_FILE, log, AgentConfig } from '@tinyagi/core';
+import { deliverHeartbeatResponses } from './heartbeat-delivery';
 
 const API_PORT = parseInt(process.env.TINYAGI_API_PORT || '3777', 10);
 const API_URL = `http://localhost:${API_PORT}`;
 
+/** How often heartbeat replies are forwarded to monitoring.heartbeat_route. */
+const DELIVERY_INTERVAL_MS = 5000;
+
 const lastSentAt = new Map<string, number>();
 let timer: ReturnType<typeof setInterval> | null = null;
+let deliveryTimer: ReturnType<typeof setInterval> | null = null;
 
 function getHeartbeatInterval(): number {
     const settings = getSettings();
@@ -34,12 +39,50 @@ function getBaseInterval(): number {
     return Math.max(minOverride, 10);
 }
 
+function workspacePath(): string {
+    return getSettings()?.workspace?.path
+        || path.join(require('os').homedir(), 'tinyagi-workspace');
+}
+
+async function sendHeartbeat(agentId: string, agent: AgentConfig): Promise<boolean> {
+    const agentDir = agent.working_directory || path.join(workspacePath(), agentId);
+    const heartbeatFile = path.join(agentDir, 'heartbeat.md');
+
+    let prompt: string;
+    if (fs.existsSync(heartbeatFile)) {
+        prompt = fs.readFileSync(heartbeatFile, 'utf8');
+    } else {
+        prompt = 'Quick status check: Any pending tasks? Keep response brief.';
+    }
+
+    try {
+        const res = await fetch(`${API_URL}/api/message`, {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify({
+                message: prompt,
+                agent: agentId,
+                channel: 'heartbeat',
+                sender: 'System',
+            }),
+        });
+        const data = await res.json() as { ok?: boolean; messageId?: string };
+        if (data.ok) {
+            log('INFO', `Heartbeat queued for @${agentId}: ${data.messageId}`);
+            lastSentAt.set(agentId, Math.floor(Date.now() / 1000));
+            return true;
+        }
+        log('ERROR', `Heartbeat failed for @${agentId}: ${JSON.stringify(data)}`);
+    } catch (err) {
+        log('ERROR', `Heartbeat error for @${agentId}: ${(err as Error).message}`);
+    }
+    return false;
+}
+
 async function tick(): Promise<void> {
     const settings = getSettings();
     const agents = getAgents(settings);
     const defaultInterval = getHeartbeatInterval();
-    const workspacePath = settings?.workspace?.path
-        || path.join(require('os').homedir(), 'tinyagi-workspace');
 
     const now = Math.floor(Date.now() / 1000);
 
@@ -50,44 +93,35 @@ async function tick(): Promise<void> {
         const last = lastSentAt.get(agentId);
         if (last !== undefined && (now - last) < agentInterval) continue;
 
-        const agentDir = agent.working_directory || path.join(workspacePath, agentId);
-        const heartbeatFile = path.join(agentDir, 'heartbeat.md');
-
-        let prompt: string;
-        if (fs.existsSync(heartbeatFile)) {
-            prompt = fs.readFileSync(heartbeatFile, 'utf8');
-        } else {
-            prompt = 'Quick status check: Any pending tasks? Keep response brief.';
-        }
+        await sendHeartbeat(agentId, agent);
+    }
+}
 
-        try {
-            const res = await fetch(`${API_URL}/api/message`, {
-                method: 'POST',
-                headers: { 'Content-Type': 'application/json' },
-                body: JSON.stringify({
-                    message: prompt,
-                    agent: agentId,
-                    channel: 'heartbeat',
-                    sender: 'System',
-                }),
-            });
-            const data = await res.json() as { ok?: boolean; messageId?: string };
-            if (data.ok) {
-                log('INFO', `Heartbeat queued for @${agentId}: ${data.messageId}`);
-                lastSentAt.set(agentId, now);
-            } else {
-                log('ERROR', `Heartbeat failed for @${agentId}: ${JSON.stringify(data)}`);
-            }
-        } catch (err) {
-            log('ERROR', `Heartbeat error for @${agentId}: ${(err as Error).message}`);
-        }
+/**
+ * Send the heartbeat prompt now, to one agent or to every agent with
+ * heartbeats enabled. Returns the agents it was queued for.
+ */
+export async function triggerHeartbeat(agentId?: string): Promise<string[]> {
+    const agents = getAgents(getSettings());
+    if (agentId && !agents[agentId]) throw new Error(`Agent '${agentId}' not found`);
+
+    const targets = agentId
+        ? [agentId]
+        : Object.keys(agents).filter(id => agents[id].heartbeat?.enabled !== false);
+    const queued: string[] = [];
+    for (const id of targets) {
+        if (await sendHeartbeat(id, agents[id])) queued.push(id);
     }
+    return queued;
 }
 
 export function startHeartbeat(): void {
     const interval = getBaseInterval();
     log('INFO', `Heartbeat started (interval: ${interval}s)`);
     timer = setInterval(() => { tick().catch(() => {}); }, interval * 1000);
+    deliveryTimer = setInterval(() => {
+        deliverHeartbeatResponses().catch(err => log('ERROR', `Heartbeat delivery error: ${(err as Error).message}`));
+    }, DELIVERY_INTERVAL_MS);
 }
 
 export function stopHeartbeat(): void {
@@ -95,6 +129,10 @@ export function stopHeartbeat(): void {
         clearInterval(timer);
         timer = null;
     }
+    if (deliveryTimer) {
+        clearInterval(deliveryTimer);
+        deliveryTimer = null;
+    }
 }
 
 export function getHeartbeatStatus(): { running: boolean; interval: number; lastSent: Record<string, number> } {
@@ -12,20 +12,24 @@ import {
     MessageJobData,
     getSettings, getAgents, getTeams, LOG_FILE, FILES_DIR, TINYAGI_HOME,
     log, emitEvent,
-    parseAgentRouting, getAgentResetFlag,
-    invokeAgent, killAgentProcess,
-    loadPlugins, runIncomingHooks,
-    streamResponse,
+    parseAgentRouting, resolveModelRoute, getAgentResetFlag,
+    invokeAgent, refineResponse, extractMemories, killAgentProcess, stopLlamaServer, BackendUnavailableError, EmptyResponseError,
+    trackInvocation, updateInvocation, untrackInvocation, cancelInvocations, getInFlightInvocations,
+    loadPlugins, runIncomingHooks, moderate, transcribeAttachments, handleConversationCommand,
+    streamResponse, enqueueResponse, isVerbose,
     initQueueDb, getPendingAgents, claimAllPendingMessages,
-    markProcessing, completeMessage, failMessage,
+    markProcessing, completeMessage, failMessage, deadLetterMessage,
     recoverStaleMessages, pruneAckedResponses, pruneCompletedMessages,
     closeQueueDb, queueEvents,
-    insertAgentMessage,
+    insertAgentMessage, recordUsage, recordTranscript, checkBudget, awaitApproval,
     startScheduler, stopScheduler,
+    startRagWatcher, stopRagWatcher,
+    startWarmup, stopWarmup,
 } from '@tinyagi/core';
 import { startApiServer } from '@tinyagi/server';
 import { startChannels, stopChannels, startChannel, stopChannel, restartChannel, getChannelStatus } from './channels';
-import { startHeartbeat, stopHeartbeat, getHeartbeatStatus } from './heartbeat';
+import { startHeartbeat, stopHeartbeat, getHeartbeatStatus, triggerHeartbeat } from './heartbeat';
+import { pruneHeartbeatResponses } from './heartbeat-delivery';
 import {
     handleTeamResponse,
     groupChatroomMessages,
@@ -40,6 +44,16 @@ import {
 
 // ── Message Processing ──────────────────────────────────────────────────────
 
+/** How often verbose mode reports generation progress. */
+const VERBOSE_TOKEN_REPORT_MS = 15_000;
+
+/** Thrown after a timed-out or cancelled message has been answered. */
+class InvocationAborted extends Error {
+    constructor(readonly reason: 'timeout' | 'cancelled', message: string) {
+        super(message);
+    }
+}
+
 async function processMessage(dbMsg: any): Promise<void> {
     const data: MessageJobData = {
         channel: dbMsg.channel,
@@ -49,6 +63,7 @@ async function processMessage(dbMsg: any): Promise<void> {
         messageId: dbMsg.message_id,
         agent: dbMsg.agent ?? undefined,
         fromAgent: dbMsg.from_agent ?? undefined,
+        responseFormat: dbMsg.response_format ? JSON.parse(dbMsg.response_format) : undefined,
     };
 
     const { channel, sender, message: rawMessage, messageId, agent: preRoutedAgent } = data;
@@ -84,7 +99,31 @@ async function processMessage(dbMsg: any): Promise<void> {
         agentId = Object.keys(agents)[0];
     }
 
-    const agent = agents[agentId];
+    let agent = agents[agentId];
+
+    // Per-channel / per-#tag model override (not for agent-to-agent messages)
+    if (!isInternal) {
+        const routed = resolveModelRoute(channel, message, settings);
+        if (routed.route) {
+            agent = { ...agent, provider: routed.route.provider, model: routed.route.model || '' };
+            message = routed.message;
+            log('INFO', `Routing ${agentId} to ${agent.provider}/${agent.model || 'default'} (${routed.reason})`);
+        }
+    }
+
+    // ── Conversation commands ───────────────────────────────────────────────
+    // /undo, /branch, /switch run here, in the agent's queue, so they never
+    // change the stored history while a turn is using it
+    if (!isInternal) {
+        const reply = handleConversationCommand(message, agentId, agent.provider);
+        if (reply !== null) {
+            enqueueResponse({
+                channel, sender, senderId: data.senderId ?? undefined, message: reply,
+                originalMessage: rawMessage, messageId, agent: agentId,
+            });
+            return;
+        }
+    }
 
     // ── Invoke agent ────────────────────────────────────────────────────────
     const agentResetFlag = getAgentResetFlag(agentId, workspacePath);
@@ -93,31 +132,220 @@ async function processMessage(dbMsg: any): Promise<void> {
         fs.unlinkSync(agentResetFlag);
     }
 
+    // Voice notes become text before hooks and moderation see the message
+    if (!isInternal) message = await transcribeAttachments(message);
+
     ({ text: message } = await runIncomingHooks(message, { channel, sender, messageId, originalMessage: rawMessage }));
 
+    // Agent-to-agent messages were already checked as the sending agent's reply
+    if (!isInternal) {
+        const moderated = await moderate(message, 'incoming', { channel, sender, messageId });
+        if (moderated.blocked) {
+            enqueueResponse({
+                channel, sender, senderId: data.senderId ?? undefined, message: moderated.text,
+                originalMessage: rawMessage, messageId, agent: agentId,
+            });
+            return;
+        }
+        message = moderated.text;
+    }
+
     emitEvent('agent:invoke', { agentId, agentName: agent.name, fromAgent: data.fromAgent || null });
+    const invokeStartedAt = Date.now();
+    let inputTokens = 0;
+    let outputTokens = 0;
+    let servedBy = agent.provider;
+    let servedModel: string | undefined = agent.model;
+    let partial = '';
     let response: string;
+
+    // Progress notes go to SSE, and to the sender too when they turned on /verbose
+    const verbose = !isInternal && isVerbose(channel, data.senderId || sender);
+    const reportStatus = (status: string) => {
+        emitEvent('agent:status', { agentId, messageId, status });
+        updateInvocation(messageId, { status });
+        if (verbose) {
+            enqueueResponse({
+                channel, sender, senderId: data.senderId ?? undefined, message: `… ${status}`,
+                originalMessage: rawMessage, messageId, agent: agentId,
+            });
+        }
+    };
+    let deltaCount = 0;
+    let lastTokenReport = Date.now();
+
+    // Cancellable by timeout, `/stop` from the sender, or the kill API. Also
+    // listed in /api/status so a long generation does not look like a hang.
+    const controller = trackInvocation(messageId, {
+        agentId, channel, sender: isInternal ? `@${data.fromAgent}` : sender, senderKey: data.senderId || sender,
+    });
+    reportStatus(`Working on it (@${agentId}, ${agent.provider || 'anthropic'}${agent.model ? `/${agent.model}` : ''})`);
+
+    // Draft-and-refine: the draft is held back while a second pass improves it
+    const refine = !isInternal && !data.responseFormat
+        ? settings.refine?.channels?.[channel] ?? settings.refine?.channels?.['*']
+        : undefined;
+    const addUsage = (usage: { inputTokens: number; outputTokens: number }) => {
+        inputTokens += usage.inputTokens;
+        outputTokens += usage.outputTokens;
+    };
+
+    const timeoutSec = settings.processing?.timeout || 0;
+    const timer = timeoutSec > 0 ? setTimeout(() => controller.abort('timeout'), timeoutSec * 1000) : undefined;
     try {
-        response = await invokeAgent(agent, agentId, message, workspacePath, shouldReset, agents, teams, (text) => {
-            log('INFO', `Agent ${agentId}: ${text}`);
-            insertAgentMessage({ agentId, role: 'assistant', channel, sender: agentId, messageId, content: text });
-            emitEvent('agent:progress', { agentId, agentName: agent.name, text, messageId });
-            sendDirectResponse(text, {
+        response = await invokeAgent(agent, agentId, message, workspacePath, shouldReset, agents, teams, {
+            onEvent: (text, provider, model) => {
+                servedBy = provider;
+                servedModel = model;
+                log('INFO', `Agent ${agentId}${provider !== agent.provider ? ` (via ${provider})` : ''}: ${text}`);
+                emitEvent('agent:progress', { agentId, agentName: agent.name, text, messageId });
+                if (refine) return;
+                insertAgentMessage({ agentId, role: 'assistant', channel, sender: agentId, messageId, content: text, provider });
+                // Structured replies are sent once validated (below)
+                if (data.responseFormat) return;
+                sendDirectResponse(text, {
+                    channel, sender, senderId: data.senderId,
+                    messageId, originalMessage: rawMessage, agentId,
+                });
+            },
+            onUsage: addUsage,
+            onDelta: (delta) => {
+                // Token deltas only go to SSE; channels get the full text via onEvent
+                partial += delta;
+                emitEvent('agent:delta', { agentId, messageId, delta });
+                deltaCount++;
+                updateInvocation(messageId, { tokens: deltaCount });
+                if (Date.now() - lastTokenReport >= VERBOSE_TOKEN_REPORT_MS) {
+                    lastTokenReport = Date.now();
+                    reportStatus(`~${deltaCount} tokens generated so far`);
+                }
+            },
+            approve: isInternal || channel === 'heartbeat' ? undefined : async (question) => {
+                // The sender's next message answers the question (see resolveApproval)
+                const answer = awaitApproval(channel, data.senderId || sender, controller.signal);
+                await sendDirectResponse(`${question} Reply "yes" to approve.`, {
+                    channel, sender, senderId: data.senderId,
+                    messageId, originalMessage: rawMessage, agentId,
+                });
+                return answer;
+            },
+            signal: controller.signal,
+            onStatus: reportStatus,
+            responseFormat: data.responseFormat,
+        });
+
+        if (refine && response && !controller.signal.aborted) {
+            reportStatus('Refining the draft');
+            try {
+                response = await refineResponse(agent, agentId, message, response, workspacePath, {
+                    refiner: refine.provider ? { provider: refine.provider, model: refine.model } : undefined,
+                    prompt: refine.prompt,
+                    onUsage: addUsage,
+                    signal: controller.signal,
+                });
+            } catch (error) {
+                if (controller.signal.aborted) throw error;
+                log('WARN', `Refine pass failed for agent ${agentId}, sending the draft: ${(error as Error).message}`);
+            }
+            insertAgentMessage({ agentId, role: 'assistant', channel, sender: agentId, messageId, content: response, provider: servedBy });
+            await sendDirectResponse(response, {
                 channel, sender, senderId: data.senderId,
                 messageId, originalMessage: rawMessage, agentId,
             });
-        });
+        }
     } catch (error) {
-        const provider = agent.provider || 'anthropic';
-        const providerLabel = provider === 'openai' ? 'Codex' : provider === 'opencode' ? 'OpenCode' : 'Claude';
-        log('ERROR', `${providerLabel} error (agent: ${agentId}): ${(error as Error).message}`);
-        response = "Sorry, I encountered an error processing your request. Please check the queue logs.";
-        const msgSender = isInternal ? data.fromAgent! : sender;
-        insertAgentMessage({ agentId, role: 'assistant', channel, sender: msgSender, messageId, content: response });
+        response = '';
+        if (!controller.signal.aborted) {
+            const provider = agent.provider || 'anthropic';
+            const providerLabel = provider === 'openai' ? 'Codex' : provider === 'opencode' ? 'OpenCode' : 'Claude';
+            log('ERROR', `${providerLabel} error (agent: ${agentId}): ${(error as Error).message}`);
+            response = error instanceof BackendUnavailableError
+                ? 'The model is warming up or temporarily unavailable. Please try again in a minute.'
+                : error instanceof EmptyResponseError
+                    ? 'Sorry, I could not generate a response.'
+                    : "Sorry, I encountered an error processing your request. Please check the queue logs.";
+            const msgSender = isInternal ? data.fromAgent! : sender;
+            insertAgentMessage({ agentId, role: 'assistant', channel, sender: msgSender, messageId, content: response });
+            await sendDirectResponse(response, {
+                channel, sender, senderId: data.senderId,
+                messageId, originalMessage: rawMessage, agentId,
+            });
+        }
+    } finally {
+        clearTimeout(timer);
+        untrackInvocation(messageId);
+    }
+
+    // Checked even on success: a killed CLI process can exit cleanly
+    let aborted: InvocationAborted | undefined;
+    if (controller.signal.aborted) {
+        const reason = controller.signal.reason === 'timeout' ? 'timeout' : 'cancelled';
+        const note = reason === 'timeout'
+            ? `Sorry, this was taking too long (over ${timeoutSec}s), so I gave up.`
+            : 'Stopped.';
+        response = partial.trim() ? `${note} Here is what I had so far:\n\n${partial.trim()}` : note;
+        log('WARN', `Message ${messageId} for agent ${agentId} ${reason === 'timeout' ? `timed out after ${timeoutSec}s` : 'was cancelled'}`);
+        insertAgentMessage({ agentId, role: 'assistant', channel, sender: agentId, messageId, content: response });
         await sendDirectResponse(response, {
             channel, sender, senderId: data.senderId,
             messageId, originalMessage: rawMessage, agentId,
         });
+        aborted = new InvocationAborted(reason, reason === 'timeout' ? `Timed out after ${timeoutSec}s` : 'Cancelled by user');
+    }
+
+    const accounting = {
+        messageId, agentId, channel,
+        sender: isInternal ? `@${data.fromAgent}` : sender,
+        provider: servedBy, model: servedModel,
+        inputTokens, outputTokens,
+        durationMs: Date.now() - invokeStartedAt,
+    };
+    recordUsage(accounting);
+    checkBudget();
+    if (response) recordTranscript({ ...accounting, prompt: message, response });
+
+    if (aborted) throw aborted;
+
+    // Automatic memory: runs after the reply is out, so it never delays it
+    const memory = settings.memory;
+    if (memory?.auto_extract && response && !isInternal && channel !== 'heartbeat' && !data.responseFormat) {
+        const extractor = memory.provider ? { provider: memory.provider, model: memory.model } : undefined;
+        const extractUsage = { inputTokens: 0, outputTokens: 0 };
+        const extractStartedAt = Date.now();
+        // Its own usage row, counted against the sender like the reply itself
+        const recordExtraction = () => {
+            recordUsage({
+                ...accounting,
+                agentId: `${agentId}-memory`,
+                provider: extractor?.provider ?? agent.provider,
+                model: extractor ? extractor.model : agent.model,
+                ...extractUsage,
+                durationMs: Date.now() - extractStartedAt,
+            });
+            checkBudget();
+        };
+        extractMemories(agent, agentId, message, response, workspacePath, {
+            extractor,
+            onUsage: usage => {
+                extractUsage.inputTokens += usage.inputTokens;
+                extractUsage.outputTokens += usage.outputTokens;
+            },
+        })
+            .then(files => { if (files.length > 0) log('INFO', `Agent ${agentId} remembered: ${files.join(', ')}`); })
+            .catch(error => log('WARN', `Memory extraction failed for agent ${agentId}: ${(error as Error).message}`))
+            .finally(recordExtraction);
+    }
+
+    // Structured output goes back as-is: no signature, no team routing
+    if (data.responseFormat) {
+        if (response) {
+            await streamResponse(response, {
+                channel, sender, senderId: data.senderId ?? undefined,
+                messageId, originalMessage: rawMessage, agentId,
+            });
+        }
+        emitEvent('agent:response', { agentId, agentName: agent.name, role: 'assistant', channel, sender, messageId, content: response });
+        return;
     }
 
     emitEvent('agent:response', {
@@ -157,7 +385,11 @@ async function sendDirectResponse(
 
 const agentChains = new Map<string, Promise<void>>();
 
+/** Set on shutdown: no new messages are started while in-flight ones finish. */
+let draining = false;
+
 async function processQueue(): Promise<void> {
+    if (draining) return;
     const pendingAgents = getPendingAgents();
     if (pendingAgents.length === 0) return;
 
@@ -169,7 +401,12 @@ async function processQueue(): Promise<void> {
         // .catch() prevents a rejected chain from blocking subsequent messages
         const newChain = currentChain.catch(() => {}).then(async () => {
             const { messages: groupedMessages, messageIds } = groupChatroomMessages(messages);
+            const finished = (id: number, status: string, error?: string) => emitEvent('message:finished', {
+                messageId: messages.find(m => m.id === id)?.message_id, agentId, status, error,
+            });
             for (let i = 0; i < groupedMessages.length; i++) {
+                // Left 'queued'; recovered as pending on the next start
+                if (draining) break;
                 const msg = groupedMessages[i];
                 const ids = messageIds[i];
                 try {
@@ -177,11 +414,22 @@ async function processQueue(): Promise<void> {
                     await processMessage(msg);
                     for (const id of ids) {
                         completeMessage(id);
+                        finished(id, 'completed');
                     }
                 } catch (error) {
+                    if (error instanceof InvocationAborted) {
+                        // Already answered; a timeout is recorded as dead, a cancel is done
+                        for (const id of ids) {
+                            if (error.reason === 'timeout') deadLetterMessage(id, error.message);
+                            else completeMessage(id);
+                            finished(id, error.reason === 'timeout' ? 'dead' : 'completed', error.message);
+                        }
+                        continue;
+                    }
                     log('ERROR', `Failed to process message ${msg.id}: ${(error as Error).message}`);
                     for (const id of ids) {
-                        failMessage(id, (error as Error).message);
+                        const status = failMessage(id, (error as Error).message);
+                        if (status) finished(id, status, (error as Error).message);
                     }
                 }
             }
@@ -235,6 +483,7 @@ const apiServer = startApiServer({
     restartChannel,
     getChannelStatus,
     getHeartbeatStatus,
+    triggerHeartbeat,
     restart() {
         log('INFO', 'Restart requested via API');
         shutdown(75);
@@ -246,6 +495,7 @@ queueEvents.on('message:enqueued', () => processQueue());
 
 // When user manually kills an agent session, clear its promise chain
 queueEvents.on('agent:killed', ({ agentId }: { agentId: string }) => {
+    cancelInvocations({ agentId });
     agentChains.delete(agentId);
     log('INFO', `Cleared agent chain for ${agentId}`);
 });
@@ -253,10 +503,11 @@ queueEvents.on('agent:killed', ({ agentId }: { agentId: string }) => {
 // Also poll periodically in case events are missed
 const pollInterval = setInterval(() => processQueue(), 5000);
 
-// Periodic maintenance (prune old completed/acked records)
+// Periodic maintenance (prune old completed/acked records and heartbeat replies)
 const maintenanceInterval = setInterval(() => {
     pruneAckedResponses();
     pruneCompletedMessages();
+    pruneHeartbeatResponses();
 }, 60 * 1000);
 
 // Load plugins
@@ -267,6 +518,12 @@ const maintenanceInterval = setInterval(() => {
 // Start in-process cron scheduler
 startScheduler();
 
+// Index the RAG watch folder (if configured) and keep it up to date
+startRagWatcher();
+
+// Load local models now rather than on the first message (models.warmup)
+startWarmup();
+
 // Start channels and heartbeat
 startChannels();
 startHeartbeat();
@@ -276,12 +533,39 @@ logAgentConfig();
 log('INFO', `Agents: ${Object.keys(getAgents(getSettings())).join(', ')}, Teams: ${Object.keys(getTeams(getSettings())).join(', ')}`);
 
 // Graceful shutdown. Exit code 75 signals "restart" to the Docker entrypoint loop.
-function shutdown(exitCode = 0): void {
+// In-flight messages get `processing.drain_timeout` seconds (default 60) to
+// finish; a second signal exits right away. Anything unfinished is picked up
+// again on the next start.
+async function shutdown(exitCode = 0): Promise<void> {
+    if (draining) {
+        log('WARN', 'Second shutdown request, exiting without waiting for in-flight messages');
+        process.exit(exitCode);
+    }
+    draining = true;
     log('INFO', exitCode === 75 ? 'Restarting queue processor...' : 'Shutting down queue processor...');
     stopHeartbeat();
-    stopChannels();
     stopScheduler();
+    stopRagWatcher();
+    stopWarmup();
     clearInterval(pollInterval);
+
+    const chains = [...agentChains.values()];
+    if (chains.length > 0) {
+        const drainSec = getSettings().processing?.drain_timeout ?? 60;
+        log('INFO', `Waiting up to ${drainSec}s for ${getInFlightInvocations().length} in-flight message(s) to finish...`);
+        let timer: NodeJS.Timeout | undefined;
+        const finished = await Promise.race([
+            Promise.allSettled(chains).then(() => true),
+            new Promise<boolean>(resolve => { timer = setTimeout(() => resolve(false), drainSec * 1000); }),
+        ]);
+        clearTimeout(timer);
+        log(finished ? 'INFO' : 'WARN', finished
+            ? 'In-flight messages finished'
+            : `Drain timed out after ${drainSec}s; unfinished messages will be retried on the next start`);
+    }
+
+    stopChannels();
+    stopLlamaServer();
     clearInterval(maintenanceInterval);
     apiServer.close();
     closeQueueDb();
@@ -11,7 +11,7 @@ import { Hono } from 'hono';
 import { cors } from 'hono/cors';
 import { serve } from '@hono/node-server';
 import { RESPONSE_ALREADY_SENT } from '@hono/node-server/utils/response';
-import { log } from '@tinyagi/core';
+import { log, getLlamaServerStatus, getBackendHealth, getInFlightInvocations, getLastUsage } from '@tinyagi/core';
 import { addSSEClient, removeSSEClient } from './sse';
 
 import messagesRoutes from './routes/messages';
@@ -29,6 +29,11 @@ import { createServicesRoutes, type ServiceHandlers } from './routes/services';
 import pairingRoutes from './routes/pairing';
 export type { ServiceHandlers } from './routes/services';
 import schedulesRoutes from './routes/schedules';
+import usageRoutes from './routes/usage';
+import embeddingsRoutes from './routes/embeddings';
+import moderationRoutes from './routes/moderation';
+import transcriptsRoutes from './routes/transcripts';
+import transcribeRoutes from './routes/transcribe';
 import { initTasksDb } from './tasks-db';
 
 const API_PORT = parseInt(process.env.TINYAGI_API_PORT || '3777', 10);
@@ -64,6 +69,14 @@ export function startApiServer(services?: ServiceHandlers): http.Server {
     app.route('/', createServicesRoutes(services));
     app.route('/', pairingRoutes);
     app.route('/', schedulesRoutes);
+    app.route('/', usageRoutes);
+    app.route('/', embeddingsRoutes);
+    app.route('/', moderationRoutes);
+    app.route('/', transcriptsRoutes);
+    app.route('/', transcribeRoutes);
+
+    // GET /health — liveness probe for container health checks (no lookups)
+    app.get('/health', (c) => c.json({ ok: true, uptime: Math.floor((Date.now() - startedAt) / 1000) }));
 
     // GET /api/status — overall system status
     app.get('/api/status', (c) => {
@@ -73,8 +86,14 @@ export function startApiServer(services?: ServiceHandlers): http.Server {
             ok: true,
             uptime: Math.floor((Date.now() - startedAt) / 1000),
             server: { running: true, port: API_PORT },
+            // Started by a plain `tinyagi start`: no supervisor restarts it after exit
+            detached: process.env.TINYAGI_DETACHED === '1',
             channels: channelStatus,
             heartbeat: heartbeatStatus,
+            llamaServer: getLlamaServerStatus(),
+            backends: getBackendHealth(),
+            processing: getInFlightInvocations(),
+            lastInference: getLastUsage() ?? null,
         });
     });
 
@@ -0,0 +1,29 @@
+import { Hono } from 'hono';
+import { embedWithUsage, log } from '@tinyagi/core';
+
+const app = new Hono();
+
+// POST /v1/embeddings — OpenAI-compatible, backed by the RAG embedding model
+// so other local tools can reuse it. Body: { input: string | string[], model? }
+app.post('/v1/embeddings', async (c) => {
+    const body = await c.req.json().catch(() => ({}));
+    const input = typeof body.input === 'string' ? [body.input] : body.input;
+    if (!Array.isArray(input) || input.length === 0 || !input.every((t: unknown) => typeof t === 'string')) {
+        return c.json({ error: { message: 'input must be a string or a non-empty array of strings', type: 'invalid_request_error' } }, 400);
+    }
+
+    try {
+        const { model, embeddings, promptTokens } = await embedWithUsage(input, body.model);
+        return c.json({
+            object: 'list',
+            data: embeddings.map((embedding, index) => ({ object: 'embedding', index, embedding })),
+            model,
+            usage: { prompt_tokens: promptTokens, total_tokens: promptTokens },
+        });
+    } catch (error) {
+        log('WARN', `Embeddings request failed: ${(error as Error).message}`);
+        return c.json({ error: { message: (error as Error).message, type: 'server_error' } }, 502);
+    }
+});
+
+export default app;
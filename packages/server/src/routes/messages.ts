@@ -1,5 +1,11 @@
+import fs from 'fs';
+import path from 'path';
 import { Hono } from 'hono';
-import { log, emitEvent, enqueueMessage, insertAgentMessage, genId, parseAgentRouting, getAgents, getTeams, getSettings } from '@tinyagi/core';
+import {
+    log, emitEvent, enqueueMessage, insertAgentMessage, genId, parseAgentRouting,
+    getAgents, getTeams, getSettings, resolveApproval, rememberNote, enqueueResponse,
+    cancelInvocations, isVerbose, setVerbose, toResponseFormat, ResponseFormat, FILES_DIR,
+} from '@tinyagi/core';
 
 const app = new Hono();
 
@@ -15,10 +21,89 @@ app.post('/api/message', async (c) => {
         return c.json({ error: 'message is required' }, 400);
     }
 
+    let responseFormat: ResponseFormat | undefined;
+    if (body.responseFormat !== undefined) {
+        try {
+            responseFormat = toResponseFormat(body.responseFormat);
+        } catch (err) {
+            return c.json({ error: (err as Error).message }, 400);
+        }
+    }
+
     const resolvedChannel = channel || 'api';
     const resolvedSender = sender || 'API';
     const messageId = clientMessageId || genId('api');
 
+    // /stop — cancel the sender's in-flight message; the agent replies "Stopped."
+    // Checked first, so it is not taken as the answer to a pending approval
+    if (/^(?:@\S+\s+)?[!/]stop\s*$/i.test(message.trim())) {
+        const cancelled = cancelInvocations({ channel: resolvedChannel, senderKey: senderId || resolvedSender });
+        log('INFO', `[API] /stop from ${resolvedSender}: cancelled ${cancelled} message(s)`);
+        if (cancelled === 0) {
+            enqueueResponse({
+                channel: resolvedChannel, sender: resolvedSender, senderId: senderId || undefined,
+                message: 'Nothing to stop.', originalMessage: message, messageId,
+            });
+        }
+        return c.json({ ok: true, messageId, cancelled });
+    }
+
+    // A reply to a pending tool approval goes straight to the waiting agent
+    if (resolveApproval(resolvedChannel, senderId || resolvedSender, message)) {
+        log('INFO', `[API] Approval reply from ${resolvedSender}: ${message}`);
+        return c.json({ ok: true, messageId, approval: true });
+    }
+
+    // /remember <text> — store a note in the RAG knowledge base and reply directly
+    const rememberMatch = message.trim().match(/^(?:@\S+\s+)?[!/]remember\s+([\s\S]+)$/i);
+    if (rememberMatch) {
+        let reply = 'Remembered.';
+        try {
+            await rememberNote(rememberMatch[1].trim(), resolvedSender);
+        } catch (err) {
+            reply = `Could not remember that: ${(err as Error).message}`;
+        }
+        enqueueResponse({
+            channel: resolvedChannel, sender: resolvedSender, senderId: senderId || undefined,
+            message: reply, originalMessage: message, messageId,
+        });
+        return c.json({ ok: true, messageId });
+    }
+
+    // /verbose [on|off] — toggle progress updates for this sender's conversation
+    const verboseMatch = message.trim().match(/^(?:@\S+\s+)?[!/]verbose(?:\s+(on|off))?\s*$/i);
+    if (verboseMatch) {
+        const senderKey = senderId || resolvedSender;
+        const on = verboseMatch[1] ? verboseMatch[1].toLowerCase() === 'on' : !isVerbose(resolvedChannel, senderKey);
+        setVerbose(resolvedChannel, senderKey, on);
+        enqueueResponse({
+            channel: resolvedChannel, sender: resolvedSender, senderId: senderId || undefined,
+            message: on
+                ? 'Verbose mode on: I will post progress updates while I work. Send /verbose off to stop.'
+                : 'Verbose mode off.',
+            originalMessage: message, messageId,
+        });
+        return c.json({ ok: true, messageId, verbose: on });
+    }
+
+    // images: data URLs or bare base64 — saved to the files dir and referenced
+    // like channel attachments, so vision models can see them
+    let fullMessage = message;
+    if (body.images !== undefined) {
+        if (!Array.isArray(body.images) || !body.images.every((i: unknown) => typeof i === 'string')) {
+            return c.json({ error: 'images must be an array of base64 strings or data URLs' }, 400);
+        }
+        fs.mkdirSync(FILES_DIR, { recursive: true });
+        const refs = (body.images as string[]).map((image, i) => {
+            const match = image.match(/^data:image\/(\w+);base64,/);
+            const ext = match ? (match[1] === 'jpeg' ? 'jpg' : match[1]) : 'png';
+            const file = path.join(FILES_DIR, `api_${Date.now()}_${i}.${ext}`);
+            fs.writeFileSync(file, Buffer.from(match ? image.slice(match[0].length) : image, 'base64'));
+            return `[file: ${file}]`;
+        });
+        fullMessage = `${message}\n\n${refs.join('\n')}`;
+    }
+
     // Resolve agent routing at enqueue time so channel messages and scheduled
     // messages end up in the same per-agent promise chain. Without this,
     // messages without an explicit `agent` field go into the 'default' chain
@@ -36,9 +121,10 @@ app.post('/api/message', async (c) => {
         channel: resolvedChannel,
         sender: resolvedSender,
         senderId: senderId || undefined,
-        message,
+        message: fullMessage,
         messageId,
         agent: resolvedAgent,
+        responseFormat,
     });
 
     if (rowId === null) {
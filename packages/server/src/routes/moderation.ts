@@ -0,0 +1,12 @@
+import { Hono } from 'hono';
+import { getModerationLog } from '@tinyagi/core';
+
+const app = new Hono();
+
+// GET /api/moderation?limit=100 — recent blocked / redacted messages, newest first
+app.get('/api/moderation', (c) => {
+    const limit = Math.min(parseInt(c.req.query('limit') || '100', 10) || 100, 1000);
+    return c.json(getModerationLog(limit));
+});
+
+export default app;
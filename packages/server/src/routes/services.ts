@@ -6,8 +6,9 @@ export interface ServiceHandlers {
     startChannel?: (channelId: string) => boolean;
     stopChannel?: (channelId: string) => boolean;
     restartChannel?: (channelId: string) => boolean;
-    getChannelStatus?: () => Record<string, { running: boolean; pid?: number }>;
+    getChannelStatus?: () => Record<string, { running: boolean; pid?: number; startedAt?: number; error?: string }>;
     getHeartbeatStatus?: () => { running: boolean; interval: number; lastSent: Record<string, number> };
+    triggerHeartbeat?: (agentId?: string) => Promise<string[]>;
     restart?: () => void;
 }
 
@@ -64,6 +65,20 @@ export function createServicesRoutes(handlers?: ServiceHandlers): Hono {
         return c.json({ ok: false, error: `Failed to restart ${channelId}` }, 400);
     });
 
+    // POST /api/heartbeat/trigger — send the heartbeat prompt now (body: { agent? })
+    app.post('/api/heartbeat/trigger', async (c) => {
+        if (!handlers?.triggerHeartbeat) {
+            return c.json({ ok: false, error: 'Heartbeat not available' }, 501);
+        }
+        const body = await c.req.json().catch(() => ({})) as { agent?: string };
+        try {
+            const queued = await handlers.triggerHeartbeat(body.agent);
+            return c.json({ ok: true, queued });
+        } catch (err) {
+            return c.json({ ok: false, error: (err as Error).message }, 404);
+        }
+    });
+
     // POST /api/services/restart — restart the process (exit code 75 triggers entrypoint loop)
     app.post('/api/services/restart', (c) => {
         if (!handlers?.restart) {
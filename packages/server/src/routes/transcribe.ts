@@ -0,0 +1,34 @@
+import fs from 'fs';
+import path from 'path';
+import { Hono, Context } from 'hono';
+import { transcribeFile, FILES_DIR, log } from '@tinyagi/core';
+
+const app = new Hono();
+
+// POST /v1/transcribe — multipart form with an audio `file`; returns { text }.
+// Also served at the OpenAI path so existing clients work unchanged.
+const transcribe = async (c: Context) => {
+    const body: Record<string, unknown> = await c.req.parseBody().catch(() => ({}));
+    const file = body.file;
+    if (!(file instanceof File)) {
+        return c.json({ error: { message: 'file is required (multipart/form-data)', type: 'invalid_request_error' } }, 400);
+    }
+
+    fs.mkdirSync(FILES_DIR, { recursive: true });
+    const ext = path.extname(file.name || '') || '.ogg';
+    const tmp = path.join(FILES_DIR, `transcribe_${Date.now()}${ext}`);
+    try {
+        fs.writeFileSync(tmp, Buffer.from(await file.arrayBuffer()));
+        return c.json({ text: await transcribeFile(tmp) });
+    } catch (error) {
+        log('WARN', `Transcription request failed: ${(error as Error).message}`);
+        return c.json({ error: { message: (error as Error).message, type: 'server_error' } }, 502);
+    } finally {
+        fs.rmSync(tmp, { force: true });
+    }
+};
+
+app.post('/v1/transcribe', transcribe);
+app.post('/v1/audio/transcriptions', transcribe);
+
+export default app;
@@ -0,0 +1,21 @@
+import { Hono } from 'hono';
+import { listTranscripts, searchTranscripts } from '@tinyagi/core';
+
+const app = new Hono();
+
+// GET /api/transcripts?q=&messageId=&agent=&channel=&since=&limit=20&offset=0
+// Newest exchanges first, or full-text search results when q is given
+app.get('/api/transcripts', (c) => {
+    const filter = {
+        messageId: c.req.query('messageId') || undefined,
+        agentId: c.req.query('agent') || undefined,
+        channel: c.req.query('channel') || undefined,
+        since: parseInt(c.req.query('since') || '0', 10) || undefined,
+        limit: Math.min(parseInt(c.req.query('limit') || '20', 10) || 20, 200),
+        offset: parseInt(c.req.query('offset') || '0', 10) || 0,
+    };
+    const q = c.req.query('q');
+    return c.json(q ? searchTranscripts(q, filter) : listTranscripts(filter));
+});
+
+export default app;
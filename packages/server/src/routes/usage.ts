@@ -0,0 +1,22 @@
+import { Hono } from 'hono';
+import { getUsageSummary, UsageGroupBy } from '@tinyagi/core';
+
+const USAGE_GROUPINGS: UsageGroupBy[] = ['sender', 'channel', 'agent'];
+
+const app = new Hono();
+
+// GET /api/usage?by=sender&since=0 — aggregated usage (messages, tokens, inference time)
+app.get('/api/usage', (c) => {
+    const by = (c.req.query('by') || 'sender') as UsageGroupBy;
+    if (!USAGE_GROUPINGS.includes(by)) {
+        return c.json({ error: `by must be one of: ${USAGE_GROUPINGS.join(', ')}` }, 400);
+    }
+    const sinceParam = c.req.query('since') || '0';
+    if (!/^\d+$/.test(sinceParam)) {
+        return c.json({ error: 'since must be a timestamp in milliseconds' }, 400);
+    }
+    const since = parseInt(sinceParam, 10);
+    return c.json({ by, since, rows: getUsageSummary(by, since) });
+});
+
+export default app;
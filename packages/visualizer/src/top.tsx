@@ -0,0 +1,359 @@
+#!/usr/bin/env node
+/**
+ * Top — live TUI monitor for a running TinyAGI instance.
+ *
+ * Polls /api/status and the queue endpoints once a second, and follows the
+ * SSE event stream for per-channel throughput and generation speed. Shows
+ * queue depth, in-flight inferences, recent errors, and the memory used by
+ * the local inference server.
+ *
+ * Usage:  node dist/top.js [--port <num>]
+ */
+
+import React, { useState, useEffect, useRef } from 'react';
+import { render, Box, Text, useApp, useInput } from 'ink';
+import { execFile } from 'child_process';
+import http from 'http';
+
+// ─── Types ──────────────────────────────────────────────────────────────────
+
+interface InFlight {
+    messageId: string;
+    agentId: string;
+    channel: string;
+    sender: string;
+    elapsedMs: number;
+    tokens: number;
+    status?: string;
+}
+
+interface Status {
+    uptime: number;
+    processing: InFlight[];
+    llamaServer?: { managed: boolean; state: string; pid?: number };
+    backends?: Record<string, { healthy: boolean; lastError?: string }>;
+    lastInference?: { agentId: string; provider?: string; model?: string; durationMs: number; at: number } | null;
+}
+
+interface QueueStatus {
+    queued: number;
+    processing: number;
+    dead: number;
+    outgoing: number;
+}
+
+interface DeadMessage {
+    id: number;
+    data: { channel: string; sender: string; agent?: string };
+    failedReason?: string;
+    timestamp: number;
+}
+
+interface ErrorLine {
+    at: number;
+    text: string;
+}
+
+interface ServerMemory {
+    label: string;
+    bytes: number;
+    vramBytes?: number;
+}
+
+// ─── Helpers ────────────────────────────────────────────────────────────────
+
+const REFRESH_MS = 1000;
+const THROUGHPUT_WINDOW_MS = 5 * 60 * 1000;
+const MAX_ERRORS = 6;
+
+function formatTime(ts: number): string {
+    return new Date(ts).toLocaleTimeString('en-US', {
+        hour12: false, hour: '2-digit', minute: '2-digit', second: '2-digit',
+    });
+}
+
+function formatDuration(seconds: number): string {
+    if (seconds < 60) return `${Math.floor(seconds)}s`;
+    if (seconds < 3600) return `${Math.floor(seconds / 60)}m ${Math.floor(seconds % 60)}s`;
+    return `${Math.floor(seconds / 3600)}h ${Math.floor((seconds % 3600) / 60)}m`;
+}
+
+function formatBytes(bytes: number): string {
+    const units = ['B', 'KB', 'MB', 'GB', 'TB'];
+    let i = 0;
+    while (bytes >= 1024 && i < units.length - 1) {
+        bytes /= 1024;
+        i++;
+    }
+    return `${bytes.toFixed(i === 0 ? 0 : 1)} ${units[i]}`;
+}
+
+function truncate(text: string, max: number): string {
+    const flat = (text || '').replace(/\s+/g, ' ').trim();
+    return flat.length > max ? `${flat.slice(0, max - 1)}…` : flat;
+}
+
+// ─── HTTP helpers ───────────────────────────────────────────────────────────
+
+function fetchJson(url: string): Promise<any> {
+    return new Promise((resolve, reject) => {
+        http.get(url, (res) => {
+            if (res.statusCode !== 200) {
+                res.resume();
+                reject(new Error(`HTTP ${res.statusCode}`));
+                return;
+            }
+            let data = '';
+            res.setEncoding('utf8');
+            res.on('data', (chunk: string) => { data += chunk; });
+            res.on('end', () => {
+                try { resolve(JSON.parse(data)); }
+                catch (e) { reject(e); }
+            });
+        }).on('error', reject);
+    });
+}
+
+/** Follow the SSE stream; reconnects after a short pause if it drops. */
+function followEvents(apiPort: number, onEvent: (type: string, data: any) => void): () => void {
+    let stopped = false;
+    let req: http.ClientRequest | null = null;
+
+    const connect = () => {
+        if (stopped) return;
+        req = http.get(`http://localhost:${apiPort}/api/events/stream`, (res) => {
+            let buffer = '';
+            res.setEncoding('utf8');
+            res.on('data', (chunk: string) => {
+                buffer += chunk;
+                let end: number;
+                while ((end = buffer.indexOf('\n\n')) !== -1) {
+                    const block = buffer.slice(0, end);
+                    buffer = buffer.slice(end + 2);
+                    const type = block.match(/^event: (.*)$/m)?.[1];
+                    const data = block.match(/^data: (.*)$/m)?.[1];
+                    if (type && data) {
+                        try { onEvent(type, JSON.parse(data)); } catch { /* ignore bad frames */ }
+                    }
+                }
+            });
+            res.on('end', () => setTimeout(connect, 2000));
+        });
+        req.on('error', () => setTimeout(connect, 2000));
+    };
+
+    connect();
+    return () => {
+        stopped = true;
+        req?.destroy();
+    };
+}
+
+/** Resident memory of a process in bytes, via ps (Linux and macOS). */
+function processRss(pid: number): Promise<number | null> {
+    return new Promise((resolve) => {
+        execFile('ps', ['-o', 'rss=', '-p', String(pid)], (err, stdout) => {
+            const kb = parseInt(stdout.trim(), 10);
+            resolve(err || isNaN(kb) ? null : kb * 1024);
+        });
+    });
+}
+
+/** Memory of the managed llama-server and of models loaded in Ollama. */
+async function serverMemory(status: Status | null): Promise<ServerMemory[]> {
+    const result: ServerMemory[] = [];
+    const llamaPid = status?.llamaServer?.managed ? status.llamaServer.pid : undefined;
+    if (llamaPid) {
+        const bytes = await processRss(llamaPid);
+        if (bytes !== null) result.push({ label: `llama-server (PID ${llamaPid})`, bytes });
+    }
+    const ollamaUrl = (process.env.OLLAMA_HOST || 'http://localhost:11434').replace(/\/+$/, '');
+    try {
+        const ps = await fetchJson(`${ollamaUrl}/api/ps`);
+        for (const m of ps.models || []) {
+            result.push({ label: `ollama ${m.name}`, bytes: m.size, vramBytes: m.size_vram });
+        }
+    } catch {
+        // Ollama not running
+    }
+    return result;
+}
+
+// ─── Components ─────────────────────────────────────────────────────────────
+
+function Section({ title, children }: { title: string; children: React.ReactNode }) {
+    return (
+        <Box flexDirection="column" paddingX={1} marginTop={1}>
+            <Text bold color="cyan">{title}</Text>
+            <Box flexDirection="column" marginLeft={2}>{children}</Box>
+        </Box>
+    );
+}
+
+function App({ apiPort }: { apiPort: number }) {
+    const { exit } = useApp();
+    const [status, setStatus] = useState<Status | null>(null);
+    const [queue, setQueue] = useState<QueueStatus | null>(null);
+    const [dead, setDead] = useState<DeadMessage[]>([]);
+    const [memory, setMemory] = useState<ServerMemory[]>([]);
+    const [events, setEvents] = useState<ErrorLine[]>([]);
+    const [connected, setConnected] = useState(false);
+    const [now, setNow] = useState(Date.now());
+
+    // Per-channel message timestamps, and when each message's first token arrived
+    const arrivals = useRef<{ channel: string; at: number }[]>([]);
+    const firstDelta = useRef(new Map<string, number>());
+
+    // Poll status, queue, and memory
+    useEffect(() => {
+        const poll = async () => {
+            try {
+                const [s, q, d] = await Promise.all([
+                    fetchJson(`http://localhost:${apiPort}/api/status`),
+                    fetchJson(`http://localhost:${apiPort}/api/queue/status`),
+                    fetchJson(`http://localhost:${apiPort}/api/queue/dead`),
+                ]);
+                setStatus(s);
+                setQueue(q);
+                setDead(d);
+                setConnected(true);
+                setMemory(await serverMemory(s));
+
+                // Forget generation timings for messages that finished
+                const active = new Set((s.processing || []).map((m: InFlight) => m.messageId));
+                for (const id of firstDelta.current.keys()) {
+                    if (!active.has(id)) firstDelta.current.delete(id);
+                }
+            } catch {
+                setConnected(false);
+            }
+            const cutoff = Date.now() - THROUGHPUT_WINDOW_MS;
+            arrivals.current = arrivals.current.filter(a => a.at >= cutoff);
+            setNow(Date.now());
+        };
+        poll();
+        const timer = setInterval(poll, REFRESH_MS);
+        return () => clearInterval(timer);
+    }, [apiPort]);
+
+    // Event stream: throughput, first-token times, and warmup failures
+    useEffect(() => followEvents(apiPort, (type, data) => {
+        if (type === 'message:incoming') {
+            arrivals.current.push({ channel: data.channel, at: Date.now() });
+        } else if (type === 'agent:delta' && !firstDelta.current.has(data.messageId)) {
+            firstDelta.current.set(data.messageId, Date.now());
+        } else if (type === 'model:warmup' && data.status === 'failed') {
+            setEvents(prev => [{ at: Date.now(), text: `warmup ${data.provider}: ${data.error}` }, ...prev].slice(0, MAX_ERRORS));
+        } else if (type === 'budget:exceeded') {
+            setEvents(prev => [{ at: Date.now(), text: `budget: ${data.limit} ${data.used}/${data.budget}` }, ...prev].slice(0, MAX_ERRORS));
+        }
+    }), [apiPort]);
+
+    useInput((ch, key) => {
+        if (key.escape || ch === 'q') exit();
+    });
+
+    // Throughput per channel over the last minute and five minutes
+    const throughput = new Map<string, { minute: number; five: number }>();
+    for (const a of arrivals.current) {
+        const entry = throughput.get(a.channel) || { minute: 0, five: 0 };
+        entry.five++;
+        if (now - a.at < 60_000) entry.minute++;
+        throughput.set(a.channel, entry);
+    }
+
+    // Errors: dead messages, unhealthy backends, and warmup/budget events
+    const errors: ErrorLine[] = [
+        ...events,
+        ...dead.map(m => ({ at: m.timestamp, text: `dead #${m.id} ${m.data.channel}/${m.data.sender}: ${m.failedReason || 'unknown error'}` })),
+        ...Object.entries(status?.backends || {})
+            .filter(([, h]) => !h.healthy)
+            .map(([name, h]) => ({ at: now, text: `${name} unhealthy: ${h.lastError || 'no response'}` })),
+    ].sort((a, b) => b.at - a.at).slice(0, MAX_ERRORS);
+
+    const inFlight = status?.processing || [];
+    const last = status?.lastInference;
+
+    return (
+        <Box flexDirection="column" width="100%">
+            {/* Header */}
+            <Box borderStyle="single" borderBottom={false} borderLeft={false} borderRight={false} paddingX={1}>
+                <Text bold color="cyan">TinyAGI top</Text>
+                <Text> </Text>
+                {connected
+                    ? <Text color="green">{'●'} connected (up {formatDuration(status?.uptime || 0)})</Text>
+                    : <Text color="red">{'○'} not responding on port {apiPort}</Text>
+                }
+                <Text color="gray">  {formatTime(now)}</Text>
+            </Box>
+
+            <Section title="Queue">
+                {queue
+                    ? <Text>
+                        <Text color="yellow">{queue.queued}</Text> queued  <Text color="green">{queue.processing}</Text> processing  <Text color={queue.dead > 0 ? 'red' : undefined}>{queue.dead}</Text> dead  {queue.outgoing} outgoing
+                    </Text>
+                    : <Text color="gray">unknown</Text>}
+            </Section>
+
+            <Section title="Throughput (messages, last 1m / 5m)">
+                {throughput.size === 0
+                    ? <Text color="gray">No messages since top started</Text>
+                    : [...throughput.entries()].map(([channel, t]) => (
+                        <Text key={channel}>{channel.padEnd(12)} {String(t.minute).padStart(4)} / {t.five}</Text>
+                    ))}
+            </Section>
+
+            <Section title="Inference">
+                {inFlight.length === 0
+                    ? <Text color="gray">Idle{last ? ` — last: @${last.agentId} ${(last.durationMs / 1000).toFixed(1)}s (${formatDuration((now - last.at) / 1000)} ago)` : ''}</Text>
+                    : inFlight.map(m => {
+                        const started = firstDelta.current.get(m.messageId);
+                        const genSeconds = started ? (now - started) / 1000 : 0;
+                        const rate = genSeconds > 1 && m.tokens > 0 ? `${(m.tokens / genSeconds).toFixed(1)} tok/s` : '';
+                        return (
+                            <Text key={m.messageId}>
+                                <Text color="green">@{m.agentId}</Text> ← {m.channel}/{m.sender}  {formatDuration(m.elapsedMs / 1000)}
+                                {m.tokens > 0 ? `  ~${m.tokens} tokens` : ''}{rate ? `  ${rate}` : ''}
+                                {m.status ? <Text color="gray">  {truncate(m.status, 40)}</Text> : null}
+                            </Text>
+                        );
+                    })}
+            </Section>
+
+            <Section title="Inference server memory">
+                {memory.length === 0
+                    ? <Text color="gray">No local inference server running</Text>
+                    : memory.map(m => (
+                        <Text key={m.label}>{m.label}: {formatBytes(m.bytes)}{m.vramBytes ? ` (${formatBytes(m.vramBytes)} VRAM)` : ''}</Text>
+                    ))}
+            </Section>
+
+            <Section title="Recent errors">
+                {errors.length === 0
+                    ? <Text color="gray">None</Text>
+                    : errors.map((e, i) => (
+                        <Text key={i} wrap="truncate-end"><Text color="gray">[{formatTime(e.at)}]</Text> <Text color="red">{e.text}</Text></Text>
+                    ))}
+            </Section>
+
+            {/* Help */}
+            <Box paddingX={1} marginTop={1}>
+                <Text color="gray" dimColor>q or Esc to quit</Text>
+            </Box>
+        </Box>
+    );
+}
+
+// ─── Entry point ────────────────────────────────────────────────────────────
+
+const args = process.argv.slice(2);
+let apiPort = parseInt(process.env.TINYAGI_API_PORT || '3777', 10);
+
+for (let i = 0; i < args.length; i++) {
+    if ((args[i] === '--port' || args[i] === '-p') && args[i + 1]) {
+        apiPort = parseInt(args[i + 1], 10);
+        i++;
+    }
+}
+
+render(<App apiPort={apiPort} />);